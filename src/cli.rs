@@ -0,0 +1,234 @@
+// src/cli.rs
+// 非交互命令行入口：`onekey <命令> [选项]`，映射到与主菜单相同的任务 ID 和参数。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::models::{MenuItem, TaskConfig, TaskStatus};
+use crate::params::{ParamKind, ParamValue};
+use crate::tasks::{execute_task, registry, Task};
+use crate::ui::get_menu_items;
+use crate::report::RunReport;
+use crate::sysinfo::HostFacts;
+use crate::utils::{get_current_time, save_output, set_output_dir};
+
+/// 任务执行成功
+pub const EXIT_OK: i32 = 0;
+/// 任务执行失败
+pub const EXIT_FAILURE: i32 = 1;
+/// 命令行用法错误
+pub const EXIT_USAGE: i32 = 2;
+
+/// 解析后的命令行调用
+struct Invocation {
+    item: MenuItem,
+    params: HashMap<String, ParamValue>,
+    save: bool,
+    report: bool,
+    /// `--output-dir` 指定的输出目录
+    output_dir: Option<PathBuf>,
+}
+
+/// 解析并执行命令行参数，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        None | Some("help") | Some("-h") | Some("--help") => {
+            print_usage();
+            return EXIT_OK;
+        }
+        Some("list") => {
+            print_commands();
+            return EXIT_OK;
+        }
+        _ => {}
+    }
+
+    let invocation = match parse(args) {
+        Ok(Some(invocation)) => invocation,
+        Ok(None) => return EXIT_OK,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            eprintln!("使用 `onekey help` 查看用法");
+            return EXIT_USAGE;
+        }
+    };
+
+    if let Some(dir) = invocation.output_dir {
+        set_output_dir(dir);
+    }
+    let config = TaskConfig {
+        item: invocation.item,
+        params: invocation.params,
+        interactive: false,
+    };
+    let host = invocation.report.then(HostFacts::collect);
+    let started_at = get_current_time();
+    let result = execute_task(&config);
+    let code = if result.status == TaskStatus::Fail {
+        eprint!("{}", result.text);
+        EXIT_FAILURE
+    } else {
+        print!("{}", result.text);
+        EXIT_OK
+    };
+    if invocation.save {
+        save_output(&format!("single_{}", config.item.id), &result.text);
+    }
+    if let Some(host) = host {
+        let mut report = RunReport::new(host);
+        report.push(&config, started_at, &result);
+        report.save(&format!("report_{}", config.item.id));
+    }
+    code
+}
+
+/// 解析命令行；`Ok(None)` 表示已打印帮助信息
+fn parse(args: &[String]) -> Result<Option<Invocation>, String> {
+    let (command, item_id) = match_command(args)
+        .ok_or_else(|| format!("未知命令: {}", args[0]))?;
+    let item = get_menu_items()
+        .into_iter()
+        .find(|i| i.id == item_id)
+        .ok_or_else(|| format!("任务 {} 不存在", item_id))?;
+    let task = &registry()[item_id];
+    if !task.cli_enabled() {
+        return Err(format!("命令 `{}` 尚未实现，仅可在交互菜单中模拟执行", command));
+    }
+    let specs = task.params();
+
+    let mut raw_params = HashMap::new();
+    let mut positional = Vec::new();
+    let mut save = false;
+    let mut report = false;
+    let mut output_dir = None;
+    let mut rest = args[command.split(' ').count()..].iter().peekable();
+    while let Some(arg) = rest.next() {
+        if arg == "-h" || arg == "--help" {
+            print_command_help(command, task.as_ref());
+            return Ok(None);
+        }
+        match arg.as_str() {
+            "--save" => {
+                save = true;
+                continue;
+            }
+            "--report" => {
+                report = true;
+                continue;
+            }
+            "--output-dir" => {
+                let dir = rest.next().ok_or("选项 --output-dir 缺少参数值")?;
+                output_dir = Some(PathBuf::from(dir));
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(dir) = arg.strip_prefix("--output-dir=") {
+            output_dir = Some(PathBuf::from(dir));
+            continue;
+        }
+        if let Some(flag) = arg.strip_prefix("--") {
+            let (name, inline_value) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (flag, None),
+            };
+            let spec = specs
+                .iter()
+                .find(|s| s.flag == name || s.key == name)
+                .ok_or_else(|| format!("命令 `{}` 不支持选项 --{}", command, name))?;
+            let value = match inline_value {
+                Some(value) => value,
+                // 布尔选项可单独出现，如 `--time-based`
+                None if matches!(spec.kind, ParamKind::Bool) && rest.peek().is_none_or(|next| spec.parse(next).is_err()) => {
+                    "yes".to_string()
+                }
+                None => rest.next().cloned().ok_or_else(|| format!("选项 --{} 缺少参数值", name))?,
+            };
+            raw_params.insert(spec.key, value);
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+
+    // 位置参数按定义顺序填充尚未通过选项给出的参数，随后逐项校验
+    let mut positional = positional.into_iter();
+    let mut params = HashMap::new();
+    for spec in &specs {
+        let raw = match raw_params.remove(spec.key) {
+            Some(raw) => raw,
+            None => positional.next().unwrap_or_default(),
+        };
+        let value = spec
+            .parse(&raw)
+            .and_then(|value| task.check_param(spec.key, &value).map(|_| value))
+            .map_err(|e| format!("参数 --{}: {}", spec.flag, e))?;
+        params.insert(spec.key.to_string(), value);
+    }
+    if let Some(extra) = positional.next() {
+        return Err(format!("多余的参数: {}", extra));
+    }
+
+    Ok(Some(Invocation { item, params, save, report, output_dir }))
+}
+
+/// 匹配最长的命令名或别名（支持 `ports open` 这样的多级命令）
+fn match_command(args: &[String]) -> Option<(&'static str, usize)> {
+    registry()
+        .iter()
+        .enumerate()
+        .flat_map(|(id, task)| {
+            std::iter::once(task.command())
+                .chain(task.aliases().iter().copied())
+                .map(move |name| (name, id))
+        })
+        .filter(|(name, _)| {
+            let words: Vec<&str> = name.split(' ').collect();
+            words.len() <= args.len() && words.iter().zip(args).all(|(w, a)| w == a)
+        })
+        .max_by_key(|(name, _)| name.len())
+}
+
+fn print_usage() {
+    println!("用法: onekey [命令] [选项]");
+    println!();
+    println!("不带参数运行时进入交互式菜单。");
+    println!();
+    println!("命令:");
+    println!("  list              列出所有任务命令");
+    println!("  help              显示本帮助");
+    println!("  <任务命令> -h     显示任务的参数说明");
+    println!();
+    println!("通用选项:");
+    println!("  --save            将任务输出保存为日志文件");
+    println!("  --report          生成 JSON、Markdown 与 HTML 报告");
+    println!("  --output-dir DIR  日志与报告的输出目录 (默认: $ONEKEY_OUTPUT_DIR 或当前目录)");
+    println!();
+    println!("退出码: {} 成功/警告/跳过, {} 任务失败, {} 用法错误", EXIT_OK, EXIT_FAILURE, EXIT_USAGE);
+}
+
+fn print_commands() {
+    for task in registry().iter().filter(|t| t.cli_enabled()) {
+        println!("  {:<14} {} ({})", task.command(), task.name(), task.description());
+    }
+}
+
+fn print_command_help(command: &str, task: &dyn Task) {
+    println!("onekey {} - {} ({})", command, task.name(), task.description());
+    if !task.aliases().is_empty() {
+        println!("别名: {}", task.aliases().join(", "));
+    }
+    let specs = task.params();
+    if specs.is_empty() {
+        println!("该任务无参数");
+        return;
+    }
+    println!("选项:");
+    for spec in specs {
+        match spec.default {
+            Some(default) => println!("  --{:<12} {} <{}> [默认: {}]", spec.flag, spec.prompt, spec.constraint(), default),
+            None => println!("  --{:<12} {} <{}> [必填]", spec.flag, spec.prompt, spec.constraint()),
+        }
+        if !spec.help.is_empty() {
+            println!("  {:<14} {}", "", spec.help);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+// src/dns.rs
+// DNS 解析器发现与最小化的 DNS 查询客户端（UDP，截断时改用 TCP）
+use std::ffi::CString;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+/// systemd-resolved 记录的上游服务器
+const RESOLVED_UPSTREAM_CONF: &str = "/run/systemd/resolve/resolv.conf";
+const RESOLVED_STUB: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 53));
+const EDNS_PAYLOAD: u16 = 1232;
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const TYPE_OPT: u16 = 41;
+
+pub const RCODE_NOERROR: u8 = 0;
+pub const RCODE_SERVFAIL: u8 = 2;
+pub const RCODE_NXDOMAIN: u8 = 3;
+
+/// 解析器的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolverSource {
+    ResolvConf,
+    /// systemd-resolved 的上游服务器
+    SystemdResolved,
+    /// 公共解析器，附带服务商名称
+    Public(&'static str),
+    /// 用户指定
+    Custom,
+}
+
+impl ResolverSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResolverSource::ResolvConf => "resolv.conf",
+            ResolverSource::SystemdResolved => "systemd-resolved",
+            ResolverSource::Public(_) => "公共",
+            ResolverSource::Custom => "自定义",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolver {
+    pub addr: SocketAddr,
+    pub source: ResolverSource,
+}
+
+impl Resolver {
+    pub fn new(ip: IpAddr, source: ResolverSource) -> Self {
+        Resolver { addr: SocketAddr::new(ip, 53), source }
+    }
+
+    pub fn label(&self) -> String {
+        match self.source {
+            ResolverSource::Public(name) => format!("{} ({})", name, self.addr.ip()),
+            _ if self.addr.ip() == RESOLVED_STUB => format!("{} (resolved 本地)", self.addr.ip()),
+            _ => self.addr.ip().to_string(),
+        }
+    }
+}
+
+/// 解析 `nameserver` 行，支持带作用域的 IPv6 链路本地地址（如 `fe80::1%eth0`）
+fn parse_nameserver(raw: &str) -> Option<SocketAddr> {
+    let (ip, scope) = match raw.split_once('%') {
+        Some((ip, scope)) => (ip, Some(scope)),
+        None => (raw, None),
+    };
+    match ip.parse::<IpAddr>().ok()? {
+        IpAddr::V6(v6) => {
+            let scope_id = scope.map_or(0, |s| {
+                s.parse().unwrap_or_else(|_| {
+                    CString::new(s).map_or(0, |name| unsafe { libc::if_nametoindex(name.as_ptr()) })
+                })
+            });
+            Some(SocketAddr::V6(SocketAddrV6::new(v6, 53, 0, scope_id)))
+        }
+        v4 => Some(SocketAddr::new(v4, 53)),
+    }
+}
+
+fn read_nameservers(path: &str) -> Vec<SocketAddr> {
+    let Ok(content) = std::fs::read_to_string(path) else { return Vec::new() };
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            (parts.next() == Some("nameserver")).then(|| parts.next()).flatten()
+        })
+        .filter_map(parse_nameserver)
+        .collect()
+}
+
+/// 系统配置的全部解析器：resolv.conf 中的每个 `nameserver`，以及 systemd-resolved 的上游服务器
+pub fn system_resolvers() -> Vec<Resolver> {
+    let mut resolvers: Vec<Resolver> = read_nameservers(RESOLV_CONF)
+        .into_iter()
+        .map(|addr| Resolver { addr, source: ResolverSource::ResolvConf })
+        .collect();
+    for addr in read_nameservers(RESOLVED_UPSTREAM_CONF) {
+        if !resolvers.iter().any(|r| r.addr == addr) {
+            resolvers.push(Resolver { addr, source: ResolverSource::SystemdResolved });
+        }
+    }
+    resolvers
+}
+
+/// 查询结果
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub rcode: u8,
+    /// AD 标志：解析器已完成 DNSSEC 验证
+    pub authenticated: bool,
+    pub answers: Vec<IpAddr>,
+    pub latency: Duration,
+}
+
+impl Response {
+    pub fn rcode_label(&self) -> String {
+        match self.rcode {
+            RCODE_NOERROR => "NOERROR".to_string(),
+            1 => "FORMERR".to_string(),
+            RCODE_SERVFAIL => "SERVFAIL".to_string(),
+            RCODE_NXDOMAIN => "NXDOMAIN".to_string(),
+            4 => "NOTIMP".to_string(),
+            5 => "REFUSED".to_string(),
+            code => format!("RCODE {}", code),
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// 构造 A 记录查询报文；`dnssec` 时携带 EDNS0 DO 位并请求 AD 标志
+fn build_query(id: u16, name: &str, dnssec: bool) -> io::Result<Vec<u8>> {
+    let mut packet = Vec::with_capacity(64);
+    packet.extend_from_slice(&id.to_be_bytes());
+    let flags: u16 = if dnssec { 0x0120 } else { 0x0100 };
+    packet.extend_from_slice(&flags.to_be_bytes());
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 1]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("无效的域名: {}", name)));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&TYPE_A.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    // EDNS0 OPT 记录
+    packet.push(0);
+    packet.extend_from_slice(&TYPE_OPT.to_be_bytes());
+    packet.extend_from_slice(&EDNS_PAYLOAD.to_be_bytes());
+    let ttl: u32 = if dnssec { 0x8000 } else { 0 };
+    packet.extend_from_slice(&ttl.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    Ok(packet)
+}
+
+fn skip_name(packet: &[u8], mut pos: usize) -> io::Result<usize> {
+    loop {
+        let len = *packet.get(pos).ok_or_else(|| invalid("域名越界"))?;
+        match len {
+            0 => return Ok(pos + 1),
+            l if l & 0xc0 == 0xc0 => return Ok(pos + 2),
+            l => pos += 1 + l as usize,
+        }
+    }
+}
+
+fn u16_at(packet: &[u8], pos: usize) -> io::Result<u16> {
+    packet
+        .get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("报文过短"))
+}
+
+/// 解析应答报文，返回 (是否截断, 结果)
+fn parse_response(packet: &[u8], latency: Duration) -> io::Result<(bool, Response)> {
+    let flags = u16_at(packet, 2)?;
+    let qdcount = u16_at(packet, 4)?;
+    let ancount = u16_at(packet, 6)?;
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        pos = skip_name(packet, pos)? + 4;
+    }
+    let mut answers = Vec::new();
+    for _ in 0..ancount {
+        pos = skip_name(packet, pos)?;
+        let rtype = u16_at(packet, pos)?;
+        let rdlen = u16_at(packet, pos + 8)? as usize;
+        pos += 10;
+        let rdata = packet.get(pos..pos + rdlen).ok_or_else(|| invalid("记录越界"))?;
+        match (rtype, rdlen) {
+            (TYPE_A, 4) => answers.push(IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))),
+            (TYPE_AAAA, 16) => answers.push(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(rdata).unwrap()))),
+            _ => {}
+        }
+        pos += rdlen;
+    }
+    let response = Response {
+        rcode: (flags & 0x000f) as u8,
+        authenticated: flags & 0x0020 != 0,
+        answers,
+        latency,
+    };
+    Ok((flags & 0x0200 != 0, response))
+}
+
+fn query_udp(server: SocketAddr, packet: &[u8], id: u16, timeout: Duration) -> io::Result<Vec<u8>> {
+    let bind: SocketAddr = if server.is_ipv6() { (Ipv6Addr::UNSPECIFIED, 0).into() } else { (Ipv4Addr::UNSPECIFIED, 0).into() };
+    let sock = UdpSocket::bind(bind)?;
+    sock.connect(server)?;
+    sock.send(packet)?;
+    let deadline = Instant::now() + timeout;
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "查询超时"));
+        }
+        sock.set_read_timeout(Some(remaining))?;
+        let n = match sock.recv(&mut buf) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "查询超时"));
+            }
+            Err(e) => return Err(e),
+        };
+        // 忽略迟到的旧应答
+        if n >= 12 && buf[..2] == id.to_be_bytes() && buf[2] & 0x80 != 0 {
+            buf.truncate(n);
+            return Ok(buf);
+        }
+    }
+}
+
+fn query_tcp(server: SocketAddr, packet: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect_timeout(&server, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut message = (packet.len() as u16).to_be_bytes().to_vec();
+    message.extend_from_slice(packet);
+    stream.write_all(&message)?;
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len)?;
+    let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// 向 `server` 查询 `name` 的 A 记录；`tcp` 为真时直接使用 TCP，否则 UDP 应答被截断时改用 TCP 重试
+pub fn query(server: SocketAddr, name: &str, dnssec: bool, tcp: bool, timeout: Duration) -> io::Result<Response> {
+    let id: u16 = rand::random();
+    let packet = build_query(id, name, dnssec)?;
+    let start = Instant::now();
+    if !tcp {
+        let reply = query_udp(server, &packet, id, timeout)?;
+        let (truncated, response) = parse_response(&reply, start.elapsed())?;
+        if !truncated {
+            return Ok(response);
+        }
+    }
+    let reply = query_tcp(server, &packet, timeout)?;
+    if reply.get(..2) != Some(&id.to_be_bytes()[..]) {
+        return Err(invalid("应答 ID 不匹配"));
+    }
+    Ok(parse_response(&reply, start.elapsed())?.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 应答报文：问题为 example.com A，`answers` 为已编码的资源记录
+    fn reply(flags: u16, answers: &[Vec<u8>]) -> Vec<u8> {
+        let mut packet = vec![0x12, 0x34];
+        packet.extend_from_slice(&flags.to_be_bytes());
+        packet.extend_from_slice(&[0, 1]);
+        packet.extend_from_slice(&(answers.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0]);
+        packet.extend_from_slice(b"\x07example\x03com\x00\x00\x01\x00\x01");
+        for answer in answers {
+            packet.extend_from_slice(answer);
+        }
+        packet
+    }
+
+    fn record(name: &[u8], rtype: u16, rdata: &[u8]) -> Vec<u8> {
+        let mut record = name.to_vec();
+        record.extend_from_slice(&rtype.to_be_bytes());
+        record.extend_from_slice(&[0, 1, 0, 0, 0x0e, 0x10]);
+        record.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        record.extend_from_slice(rdata);
+        record
+    }
+
+    /// 指向问题中 example.com 的压缩指针
+    const PTR: &[u8] = &[0xc0, 0x0c];
+
+    #[test]
+    fn query_encodes_name_and_edns() {
+        let packet = build_query(0xabcd, "www.example.com.", true).unwrap();
+        assert_eq!(&packet[..12], &[0xab, 0xcd, 0x01, 0x20, 0, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(&packet[12..29], b"\x03www\x07example\x03com\x00");
+        assert_eq!(u16_at(&packet, 29).unwrap(), TYPE_A);
+        // OPT 记录：根域名、类型 41、DO 位
+        assert_eq!(&packet[33..], &[0, 0, 41, 0x04, 0xd0, 0, 0, 0x80, 0, 0, 0]);
+        assert_eq!(build_query(1, "example.com", false).unwrap()[2..4], [0x01, 0x00]);
+
+        assert!(build_query(1, "a..com", false).is_err());
+        assert!(build_query(1, &format!("{}.com", "a".repeat(64)), false).is_err());
+    }
+
+    #[test]
+    fn parses_a_and_aaaa_answers() {
+        let packet = reply(
+            0x81a0,
+            &[
+                record(PTR, TYPE_A, &[93, 184, 216, 34]),
+                record(PTR, TYPE_AAAA, &"2606:2800:220:1:248:1893:25c8:1946".parse::<Ipv6Addr>().unwrap().octets()),
+            ],
+        );
+        let (truncated, response) = parse_response(&packet, Duration::ZERO).unwrap();
+        assert!(!truncated);
+        assert_eq!(response.rcode, RCODE_NOERROR);
+        assert!(response.authenticated);
+        assert_eq!(
+            response.answers,
+            vec!["93.184.216.34".parse::<IpAddr>().unwrap(), "2606:2800:220:1:248:1893:25c8:1946".parse().unwrap()]
+        );
+    }
+
+    #[test]
+    fn follows_compressed_names_and_skips_other_types() {
+        // CNAME www.example.com → 压缩指针，随后的 A 记录名称为 "www" 标签加指针
+        let www = [&b"\x03www"[..], PTR].concat();
+        let packet = reply(
+            0x8180,
+            &[record(&www, 5, &[0xc0, 0x0c]), record(&[0xc0, 0x1d], TYPE_A, &[192, 0, 2, 1])],
+        );
+        let (_, response) = parse_response(&packet, Duration::ZERO).unwrap();
+        assert!(!response.authenticated);
+        assert_eq!(response.answers, vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))]);
+        assert_eq!(skip_name(&www, 0).unwrap(), www.len());
+    }
+
+    #[test]
+    fn reports_truncation_and_rcode() {
+        let (truncated, response) = parse_response(&reply(0x8380, &[]), Duration::ZERO).unwrap();
+        assert!(truncated);
+        assert!(response.answers.is_empty());
+
+        let (_, response) = parse_response(&reply(0x8183, &[]), Duration::ZERO).unwrap();
+        assert_eq!(response.rcode, RCODE_NXDOMAIN);
+        assert_eq!(response.rcode_label(), "NXDOMAIN");
+    }
+
+    #[test]
+    fn rejects_short_and_out_of_bounds_packets() {
+        assert!(parse_response(&[0x12, 0x34, 0x81], Duration::ZERO).is_err());
+
+        let full = reply(0x8180, &[record(PTR, TYPE_A, &[192, 0, 2, 1])]);
+        for len in [11, 20, full.len() - 12, full.len() - 1] {
+            assert!(parse_response(&full[..len], Duration::ZERO).is_err(), "截断到 {} 字节应报错", len);
+        }
+        // 标签长度超出报文
+        assert!(skip_name(b"\x3fabc", 0).is_err());
+        // 声明的长度超出报文
+        let mut bad = full.clone();
+        let rdlen = bad.len() - 6;
+        bad[rdlen..rdlen + 2].copy_from_slice(&100u16.to_be_bytes());
+        assert!(parse_response(&bad, Duration::ZERO).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+// src/history.rs
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::models::{Direction, Metric, ResultTable, TaskConfig, TaskResult, TaskStatus};
+
+/// 历史记录中的一次任务结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp: String,
+    pub host: String,
+    /// 任务命令名，如 `io`
+    pub task: String,
+    /// 校验后的任务参数，参数不同的两次结果不可直接对比
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    pub status: TaskStatus,
+    pub duration_secs: f64,
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub tables: Vec<ResultTable>,
+}
+
+impl HistoryRecord {
+    pub fn new(task: &str, config: &TaskConfig, result: &TaskResult) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            host: current_host(),
+            task: task.to_string(),
+            params: config.params.iter().map(|(k, v)| (k.clone(), v.to_string())).collect(),
+            status: result.status,
+            duration_secs: result.duration.as_secs_f64(),
+            metrics: result.metrics.clone(),
+            tables: result.tables.clone(),
+        }
+    }
+
+    /// 与另一条记录取值不同的参数：`(参数, 本条取值, 对方取值)`
+    pub fn param_diff(&self, other: &HistoryRecord) -> Vec<(String, String, String)> {
+        let keys: BTreeSet<&String> = self.params.keys().chain(other.params.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let mine = self.params.get(key).map_or("-", String::as_str);
+                let theirs = other.params.get(key).map_or("-", String::as_str);
+                (mine != theirs).then(|| (key.clone(), mine.to_string(), theirs.to_string()))
+            })
+            .collect()
+    }
+
+    /// 全部指标，表格按 `标题/行/列` 展开
+    pub fn all_metrics(&self) -> Vec<Metric> {
+        let mut metrics = self.metrics.clone();
+        metrics.extend(self.tables.iter().flat_map(|t| t.flatten()));
+        metrics
+    }
+}
+
+/// 单项指标的对比结果
+#[derive(Debug, Clone)]
+pub struct MetricDelta {
+    pub key: String,
+    pub unit: String,
+    pub previous: f64,
+    pub latest: f64,
+    /// 相对变化百分比
+    pub change_pct: f64,
+    /// 是否朝不利方向变化且超出阈值
+    pub regression: bool,
+    /// 是否朝有利方向变化且超出阈值
+    pub improvement: bool,
+}
+
+/// 历史文件路径：`$ONEKEY_HISTORY` > `$XDG_DATA_HOME/onekey/history.jsonl` > `~/.local/share/onekey/history.jsonl`
+pub fn history_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("ONEKEY_HISTORY") {
+        return PathBuf::from(path);
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    data_home.join("onekey").join("history.jsonl")
+}
+
+pub fn current_host() -> String {
+    hostname::get().ok().and_then(|h| h.into_string().ok()).unwrap_or_default()
+}
+
+/// 追加一条记录
+pub fn append(record: &HistoryRecord) -> Result<(), String> {
+    let path = history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建目录 {} 失败: {}", dir.display(), e))?;
+    }
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开 {} 失败: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+/// 读取指定主机与任务的历史记录（按写入顺序，跳过无法解析的行）
+pub fn load(host: &str, task: &str) -> Vec<HistoryRecord> {
+    let Ok(file) = fs::File::open(history_path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<HistoryRecord>(&line).ok())
+        .filter(|r| r.host == host && r.task == task)
+        .collect()
+}
+
+/// 对比两次记录中的同名指标；`threshold_pct` 为判定退化的相对变化阈值
+pub fn compare(previous: &HistoryRecord, latest: &HistoryRecord, threshold_pct: f64) -> Vec<MetricDelta> {
+    let previous_metrics = previous.all_metrics();
+    latest
+        .all_metrics()
+        .into_iter()
+        .filter_map(|metric| {
+            let old = previous_metrics.iter().find(|m| m.key == metric.key && m.unit == metric.unit)?;
+            let change_pct = if old.value.abs() > f64::EPSILON {
+                (metric.value - old.value) / old.value.abs() * 100.0
+            } else {
+                0.0
+            };
+            let worse = match metric.direction {
+                Direction::Higher => -change_pct,
+                Direction::Lower => change_pct,
+                Direction::Neutral => 0.0,
+            };
+            Some(MetricDelta {
+                key: metric.key.clone(),
+                unit: metric.unit.clone(),
+                previous: old.value,
+                latest: metric.value,
+                change_pct,
+                regression: worse > threshold_pct,
+                improvement: -worse > threshold_pct,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(metrics: Vec<Metric>, tables: Vec<ResultTable>) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: String::new(),
+            host: String::new(),
+            task: "test".to_string(),
+            params: BTreeMap::new(),
+            status: TaskStatus::Ok,
+            duration_secs: 0.0,
+            metrics,
+            tables,
+        }
+    }
+
+    fn table(value: f64, direction: Direction) -> ResultTable {
+        let mut table = ResultTable::new("数据校验", "块", vec!["损坏".to_string()], direction);
+        table.push_row("第 1 轮", vec![Some(value)]);
+        table
+    }
+
+    #[test]
+    fn compare_uses_the_declared_direction() {
+        let previous = record(
+            vec![
+                Metric::new("吞吐量", 100.0, "MB/s", Direction::Higher),
+                Metric::new("延迟", 10.0, "ms", Direction::Lower),
+                Metric::new("核数", 4.0, "核", Direction::Neutral),
+            ],
+            vec![table(1.0, Direction::Lower)],
+        );
+        let latest = record(
+            vec![
+                Metric::new("吞吐量", 50.0, "MB/s", Direction::Higher),
+                Metric::new("延迟", 5.0, "ms", Direction::Lower),
+                Metric::new("核数", 8.0, "核", Direction::Neutral),
+            ],
+            vec![table(3.0, Direction::Lower)],
+        );
+        let deltas = compare(&previous, &latest, 10.0);
+        let find = |key: &str| deltas.iter().find(|d| d.key == key).unwrap();
+
+        assert!(find("吞吐量").regression);
+        assert!(find("延迟").improvement && !find("延迟").regression);
+        assert!(!find("核数").regression && !find("核数").improvement);
+        let corrupted = find("数据校验/第 1 轮/损坏");
+        assert!(corrupted.regression);
+        assert_eq!(corrupted.change_pct, 200.0);
+    }
+
+    #[test]
+    fn records_without_direction_are_neutral() {
+        let json = r#"{"key": "latency", "value": 1.0, "unit": "ms"}"#;
+        let metric: Metric = serde_json::from_str(json).unwrap();
+        assert_eq!(metric.direction, Direction::Neutral);
+    }
+}
@@ -1,10 +1,16 @@
 // src/main.rs
 mod models;
+mod params;
 mod ui;
 mod tasks;
 mod utils;
+mod cli;
+mod report;
+mod history;
 
 // 功能模块
+mod dns;
+mod netprobe;
 mod sysinfo;
 mod performance;
 mod portmgr;
@@ -16,6 +22,12 @@ use std::io::stdout;
 use crate::models::MenuAction;
 
 fn main() -> std::io::Result<()> {
+    // 带参数运行时走非交互命令行模式
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     loop {
         // 进入TUI模式
         enable_raw_mode()?;
@@ -1,9 +1,14 @@
 // src/models.rs
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::params::{ParamValue, PortSpec};
 
 /// 定义了主菜单返回的动作
 #[derive(Debug, Clone)]
 pub enum MenuAction {
+    #[allow(dead_code)]
     Continue, // 在某些场景下可能需要，暂时保留
     Exit,
     ExecuteSingle(usize),
@@ -29,5 +34,248 @@ impl MenuItem {
 #[derive(Debug, Clone)]
 pub struct TaskConfig {
     pub item: MenuItem,
-    pub params: HashMap<String, String>,
-}
\ No newline at end of file
+    pub params: HashMap<String, ParamValue>,
+    /// 是否处于交互模式；命令行模式下任务不得进入 TUI 或等待按键
+    pub interactive: bool,
+}
+
+impl TaskConfig {
+    pub fn int(&self, key: &str) -> Option<i64> {
+        match self.params.get(key) {
+            Some(ParamValue::Integer(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn path(&self, key: &str) -> Option<&Path> {
+        match self.params.get(key) {
+            Some(ParamValue::Path(p)) => Some(p.as_path()),
+            _ => None,
+        }
+    }
+
+    /// 枚举选项或文本参数
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.params.get(key) {
+            Some(ParamValue::Choice(s)) | Some(ParamValue::Text(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        match self.params.get(key) {
+            Some(ParamValue::Bool(b)) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// 多选参数
+    pub fn list(&self, key: &str) -> Option<&[String]> {
+        match self.params.get(key) {
+            Some(ParamValue::List(items)) => Some(items.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn sizes(&self, key: &str) -> Option<&[u64]> {
+        match self.params.get(key) {
+            Some(ParamValue::Sizes(sizes)) => Some(sizes.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn ports(&self, key: &str) -> Option<&[PortSpec]> {
+        match self.params.get(key) {
+            Some(ParamValue::Ports(ports)) => Some(ports.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+/// 任务执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Ok,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+impl TaskStatus {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TaskStatus::Ok => "✅",
+            TaskStatus::Warn => "⚠️",
+            TaskStatus::Fail => "❌",
+            TaskStatus::Skipped => "⏭️",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Ok => "成功",
+            TaskStatus::Warn => "警告",
+            TaskStatus::Fail => "失败",
+            TaskStatus::Skipped => "跳过",
+        }
+    }
+}
+
+/// 指标的优劣方向，由产生指标的任务给出，对比时据此判断退化
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// 越大越好，如吞吐量、得分
+    Higher,
+    /// 越小越好，如延迟、丢包、错误数
+    Lower,
+    /// 仅作记录，不判断退化，如核数、时间占比
+    #[default]
+    Neutral,
+}
+
+/// 单项测量值，如 `seq_read = 512.3 MB/s`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub key: String,
+    pub value: f64,
+    pub unit: String,
+    /// 旧版历史记录没有该字段，读取时视为不判断退化
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl Metric {
+    pub fn new(key: impl Into<String>, value: f64, unit: impl Into<String>, direction: Direction) -> Self {
+        Self { key: key.into(), value, unit: unit.into(), direction }
+    }
+}
+
+/// 数值表格，如硬盘测试中 模式 × 块大小 的吞吐量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultTable {
+    pub title: String,
+    pub unit: String,
+    /// 列标题（不含首列行标题）
+    pub columns: Vec<String>,
+    pub rows: Vec<TableRow>,
+    /// 表中全部数值的优劣方向
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableRow {
+    pub label: String,
+    /// 与 `columns` 一一对应；`None` 表示未测量
+    pub cells: Vec<Option<f64>>,
+}
+
+impl ResultTable {
+    pub fn new(title: impl Into<String>, unit: impl Into<String>, columns: Vec<String>, direction: Direction) -> Self {
+        Self { title: title.into(), unit: unit.into(), columns, rows: Vec::new(), direction }
+    }
+
+    pub fn push_row(&mut self, label: impl Into<String>, cells: Vec<Option<f64>>) {
+        self.rows.push(TableRow { label: label.into(), cells });
+    }
+
+    /// 将表格展开为 `标题/行/列` 形式的指标
+    pub fn flatten(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for row in &self.rows {
+            for (column, cell) in self.columns.iter().zip(&row.cells) {
+                if let Some(value) = cell {
+                    metrics.push(Metric::new(
+                        format!("{}/{}/{}", self.title, row.label, column),
+                        *value,
+                        self.unit.clone(),
+                        self.direction,
+                    ));
+                }
+            }
+        }
+        metrics
+    }
+
+    /// 表中最大值，用于绘制条形图
+    pub fn max_value(&self) -> f64 {
+        self.rows
+            .iter()
+            .flat_map(|r| r.cells.iter().flatten())
+            .fold(0.0, |acc, v| acc.max(*v))
+    }
+}
+
+/// 任务执行结果：报告、导出和对比都基于此结构而非输出文本
+#[derive(Debug, Clone)]
+pub struct TaskResult {
+    pub status: TaskStatus,
+    /// 执行耗时，由任务分发器填写
+    pub duration: Duration,
+    pub metrics: Vec<Metric>,
+    pub tables: Vec<ResultTable>,
+    /// 面向用户的文本输出
+    pub text: String,
+}
+
+impl TaskResult {
+    pub fn new(status: TaskStatus, text: impl Into<String>) -> Self {
+        Self { status, duration: Duration::ZERO, metrics: Vec::new(), tables: Vec::new(), text: text.into() }
+    }
+
+    pub fn ok(text: impl Into<String>) -> Self {
+        Self::new(TaskStatus::Ok, text)
+    }
+
+    pub fn fail(text: impl Into<String>) -> Self {
+        Self::new(TaskStatus::Fail, text)
+    }
+
+    pub fn skipped(text: impl Into<String>) -> Self {
+        Self::new(TaskStatus::Skipped, text)
+    }
+
+    pub fn with_metric(
+        mut self,
+        key: impl Into<String>,
+        value: f64,
+        unit: impl Into<String>,
+        direction: Direction,
+    ) -> Self {
+        self.push_metric(key, value, unit, direction);
+        self
+    }
+
+    pub fn push_metric(&mut self, key: impl Into<String>, value: f64, unit: impl Into<String>, direction: Direction) {
+        self.metrics.push(Metric::new(key, value, unit, direction));
+    }
+}
+
+/// 测试使用的 IP 协议族，网络诊断与解锁检测共用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    V4,
+    V6,
+}
+
+impl Family {
+    pub const ALL: [Family; 2] = [Family::V4, Family::V6];
+    pub const KEYS: &'static [&'static str] = &["ipv4", "ipv6"];
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "ipv4" => Some(Family::V4),
+            "ipv6" => Some(Family::V6),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Family::V4 => "IPv4",
+            Family::V6 => "IPv6",
+        }
+    }
+}
@@ -0,0 +1,552 @@
+// src/netprobe.rs
+// 原始套接字探测：发送 ICMP Echo / UDP / TCP SYN 探测包并解析 ICMP 回应
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
+
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_DEST_UNREACH: u8 = 3;
+const ICMP_ECHO: u8 = 8;
+const ICMP_TIME_EXCEEDED: u8 = 11;
+/// 目的不可达中的“需要分片但设置了 DF”
+const ICMP_FRAG_NEEDED: u8 = 4;
+const ICMP6_DEST_UNREACH: u8 = 1;
+const ICMP6_PACKET_TOO_BIG: u8 = 2;
+const ICMP6_TIME_EXCEEDED: u8 = 3;
+const ICMP6_ECHO: u8 = 128;
+const ICMP6_ECHO_REPLY: u8 = 129;
+const IPV6_HEADER: usize = 40;
+
+static NEXT_ECHO_ID: AtomicU16 = AtomicU16::new(0);
+
+/// 探测包的标识，用于将 ICMP 回应与发出的探测对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProbeId {
+    /// ICMP Echo 的标识符与序号
+    Echo { id: u16, seq: u16 },
+    /// UDP/TCP 的协议号、源端口与目的端口
+    Port { proto: u8, src: u16, dst: u16 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcmpKind {
+    EchoReply,
+    TimeExceeded,
+    /// 目的不可达，附带 ICMP code
+    Unreachable(u8),
+    /// 报文过大（IPv4 需要分片 / ICMPv6 Packet Too Big），附带下一跳 MTU
+    PacketTooBig(u32),
+}
+
+/// 收到的 ICMP 报文
+#[derive(Debug, Clone, Copy)]
+pub struct IcmpMessage {
+    pub from: IpAddr,
+    pub kind: IcmpKind,
+    /// Echo Reply 取自报文本身，差错报文取自其中引用的原始报文
+    pub probe: ProbeId,
+}
+
+impl IcmpMessage {
+    /// 是否为目标主机本身给出的终点回应（Echo Reply 或端口不可达等）
+    pub fn is_final(&self) -> bool {
+        !matches!(self.kind, IcmpKind::TimeExceeded)
+    }
+}
+
+fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 { Err(io::Error::last_os_error()) } else { Ok(ret) }
+}
+
+fn socket(v6: bool, ty: libc::c_int, proto: libc::c_int) -> io::Result<OwnedFd> {
+    let domain = if v6 { libc::AF_INET6 } else { libc::AF_INET };
+    let fd = cvt(unsafe { libc::socket(domain, ty | libc::SOCK_CLOEXEC, proto) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// 原始 ICMP 套接字，可接收本机收到的全部 ICMP 报文
+pub fn icmp_socket(v6: bool) -> io::Result<OwnedFd> {
+    let proto = if v6 { libc::IPPROTO_ICMPV6 } else { libc::IPPROTO_ICMP };
+    socket(v6, libc::SOCK_RAW, proto).map_err(|e| {
+        if e.raw_os_error() == Some(libc::EPERM) {
+            io::Error::new(io::ErrorKind::PermissionDenied, "创建原始套接字需要 root 权限或 CAP_NET_RAW")
+        } else {
+            e
+        }
+    })
+}
+
+/// 未连接的 UDP 套接字，已绑定临时端口
+pub fn udp_socket(v6: bool) -> io::Result<(OwnedFd, u16)> {
+    let fd = socket(v6, libc::SOCK_DGRAM, 0)?;
+    let any = if v6 { SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)) } else { SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)) };
+    let (addr, len) = to_sockaddr(any);
+    cvt(unsafe { libc::bind(fd.as_raw_fd(), &addr as *const _ as *const libc::sockaddr, len) })?;
+    let port = local_port(&fd)?;
+    Ok((fd, port))
+}
+
+fn setsockopt_int(fd: &impl AsRawFd, level: libc::c_int, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    cvt(unsafe {
+        libc::setsockopt(
+            fd.as_raw_fd(),
+            level,
+            name,
+            &value as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    })?;
+    Ok(())
+}
+
+/// 发出的报文设置 DF 且不受已缓存的路径 MTU 限制，超过出口 MTU 的报文发送时返回 `EMSGSIZE`
+fn set_pmtu_probe(fd: &impl AsRawFd, v6: bool) -> io::Result<()> {
+    if v6 {
+        setsockopt_int(fd, libc::IPPROTO_IPV6, libc::IPV6_MTU_DISCOVER, libc::IPV6_PMTUDISC_PROBE)
+    } else {
+        setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER, libc::IP_PMTUDISC_PROBE)
+    }
+}
+
+/// 到 `addr` 的路由出口 MTU；无路由时返回 `ENETUNREACH` 等错误（不发送任何报文）
+pub fn route_mtu(addr: IpAddr) -> io::Result<u32> {
+    let v6 = addr.is_ipv6();
+    let fd = socket(v6, libc::SOCK_DGRAM, 0)?;
+    let (storage, len) = to_sockaddr(SocketAddr::new(addr, 9));
+    cvt(unsafe { libc::connect(fd.as_raw_fd(), &storage as *const _ as *const libc::sockaddr, len) })?;
+    let (level, name) = if v6 { (libc::IPPROTO_IPV6, libc::IPV6_MTU) } else { (libc::IPPROTO_IP, libc::IP_MTU) };
+    let mut mtu: libc::c_int = 0;
+    let mut optlen = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    cvt(unsafe {
+        libc::getsockopt(fd.as_raw_fd(), level, name, &mut mtu as *mut libc::c_int as *mut libc::c_void, &mut optlen)
+    })?;
+    Ok(mtu as u32)
+}
+
+/// 设置后续发出报文的 TTL (IPv4) 或跳数限制 (IPv6)
+pub fn set_ttl(fd: &impl AsRawFd, v6: bool, ttl: u8) -> io::Result<()> {
+    if v6 {
+        setsockopt_int(fd, libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS, ttl as libc::c_int)
+    } else {
+        setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TTL, ttl as libc::c_int)
+    }
+}
+
+fn to_sockaddr(addr: SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let len = match addr {
+        SocketAddr::V4(a) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = a.port().to_be();
+            sin.sin_addr.s_addr = u32::from(*a.ip()).to_be();
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(a) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = a.port().to_be();
+            sin6.sin6_addr.s6_addr = a.ip().octets();
+            sin6.sin6_scope_id = a.scope_id();
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    (storage, len as libc::socklen_t)
+}
+
+fn from_sockaddr(storage: &libc::sockaddr_storage) -> Option<SocketAddr> {
+    match storage.ss_family as libc::c_int {
+        libc::AF_INET => {
+            let sin = unsafe { &*(storage as *const _ as *const libc::sockaddr_in) };
+            Some(SocketAddr::from((Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)), u16::from_be(sin.sin_port))))
+        }
+        libc::AF_INET6 => {
+            let sin6 = unsafe { &*(storage as *const _ as *const libc::sockaddr_in6) };
+            Some(SocketAddr::from((Ipv6Addr::from(sin6.sin6_addr.s6_addr), u16::from_be(sin6.sin6_port))))
+        }
+        _ => None,
+    }
+}
+
+fn local_port(fd: &impl AsRawFd) -> io::Result<u16> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    cvt(unsafe { libc::getsockname(fd.as_raw_fd(), &mut storage as *mut _ as *mut libc::sockaddr, &mut len) })?;
+    from_sockaddr(&storage)
+        .map(|a| a.port())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "未知的地址族"))
+}
+
+pub fn send_to(fd: &impl AsRawFd, buf: &[u8], addr: SocketAddr) -> io::Result<()> {
+    let (storage, len) = to_sockaddr(addr);
+    cvt(unsafe {
+        libc::sendto(
+            fd.as_raw_fd(),
+            buf.as_ptr() as *const libc::c_void,
+            buf.len(),
+            0,
+            &storage as *const _ as *const libc::sockaddr,
+            len,
+        ) as libc::c_int
+    })?;
+    Ok(())
+}
+
+/// Internet 校验和 (RFC 1071)
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum = 0u32;
+    for chunk in data.chunks(2) {
+        let word = if chunk.len() == 2 { u16::from_be_bytes([chunk[0], chunk[1]]) } else { u16::from(chunk[0]) << 8 };
+        sum += word as u32;
+    }
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// 分配 ICMP Echo 标识符；原始套接字会收到本机全部回应，各探测须使用不同的标识符
+pub fn next_echo_id() -> u16 {
+    (std::process::id() as u16).wrapping_add(NEXT_ECHO_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// 构造 ICMP Echo 请求；ICMPv6 的校验和由内核填写
+pub fn echo_request(v6: bool, id: u16, seq: u16, payload_len: usize) -> Vec<u8> {
+    let mut packet = vec![0u8; 8 + payload_len];
+    packet[0] = if v6 { ICMP6_ECHO } else { ICMP_ECHO };
+    packet[4..6].copy_from_slice(&id.to_be_bytes());
+    packet[6..8].copy_from_slice(&seq.to_be_bytes());
+    for (i, byte) in packet[8..].iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    if !v6 {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+/// 解析差错报文中引用的原始报文（IP 头 + 至少 8 字节传输层头）
+fn parse_quoted(v6: bool, quoted: &[u8]) -> Option<ProbeId> {
+    let (proto, l4) = if v6 {
+        (*quoted.get(6)?, quoted.get(IPV6_HEADER..)?)
+    } else {
+        let ihl = (*quoted.first()? & 0x0f) as usize * 4;
+        (*quoted.get(9)?, quoted.get(ihl..)?)
+    };
+    let u16_at = |at: usize| Some(u16::from_be_bytes([*l4.get(at)?, *l4.get(at + 1)?]));
+    let echo = if v6 { ICMP6_ECHO } else { ICMP_ECHO };
+    match proto as libc::c_int {
+        libc::IPPROTO_ICMP | libc::IPPROTO_ICMPV6 if *l4.first()? == echo => {
+            Some(ProbeId::Echo { id: u16_at(4)?, seq: u16_at(6)? })
+        }
+        libc::IPPROTO_UDP | libc::IPPROTO_TCP => Some(ProbeId::Port { proto, src: u16_at(0)?, dst: u16_at(2)? }),
+        _ => None,
+    }
+}
+
+/// 解析收到的 ICMP 报文；`ip_header` 表示报文前带有 IPv4 头（仅 IPv4 原始套接字）
+fn parse_icmp(v6: bool, ip_header: bool, from: IpAddr, packet: &[u8]) -> Option<IcmpMessage> {
+    let icmp = if ip_header { packet.get((*packet.first()? & 0x0f) as usize * 4..)? } else { packet };
+    let (ty, code) = (*icmp.first()?, *icmp.get(1)?);
+    let kind = match (v6, ty) {
+        (false, ICMP_ECHO_REPLY) | (true, ICMP6_ECHO_REPLY) => {
+            let probe = ProbeId::Echo {
+                id: u16::from_be_bytes([*icmp.get(4)?, *icmp.get(5)?]),
+                seq: u16::from_be_bytes([*icmp.get(6)?, *icmp.get(7)?]),
+            };
+            return Some(IcmpMessage { from, kind: IcmpKind::EchoReply, probe });
+        }
+        (false, ICMP_TIME_EXCEEDED) | (true, ICMP6_TIME_EXCEEDED) => IcmpKind::TimeExceeded,
+        (false, ICMP_DEST_UNREACH) if code == ICMP_FRAG_NEEDED => {
+            IcmpKind::PacketTooBig(u16::from_be_bytes([*icmp.get(6)?, *icmp.get(7)?]) as u32)
+        }
+        (false, ICMP_DEST_UNREACH) | (true, ICMP6_DEST_UNREACH) => IcmpKind::Unreachable(code),
+        (true, ICMP6_PACKET_TOO_BIG) => IcmpKind::PacketTooBig(u32::from_be_bytes(icmp.get(4..8)?.try_into().ok()?)),
+        _ => return None,
+    };
+    let probe = parse_quoted(v6, icmp.get(8..)?)?;
+    Some(IcmpMessage { from, kind, probe })
+}
+
+/// 非阻塞读取原始套接字上下一个可识别的 ICMP 报文，无数据时返回 `None`
+pub fn recv_icmp(fd: &impl AsRawFd, v6: bool, buf: &mut [u8]) -> io::Result<Option<IcmpMessage>> {
+    recv_message(fd, v6, !v6, buf)
+}
+
+fn recv_message(fd: &impl AsRawFd, v6: bool, ip_header: bool, buf: &mut [u8]) -> io::Result<Option<IcmpMessage>> {
+    loop {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let n = unsafe {
+            libc::recvfrom(
+                fd.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                libc::MSG_DONTWAIT,
+                &mut storage as *mut _ as *mut libc::sockaddr,
+                &mut len,
+            )
+        };
+        if n < 0 {
+            let e = io::Error::last_os_error();
+            return if e.kind() == io::ErrorKind::WouldBlock { Ok(None) } else { Err(e) };
+        }
+        if let Some(message) = from_sockaddr(&storage).and_then(|from| parse_icmp(v6, ip_header, from.ip(), &buf[..n as usize])) {
+            return Ok(Some(message));
+        }
+    }
+}
+
+/// ICMP Echo 套接字：优先使用原始套接字，无权限时退回内核 ping 套接字
+/// (`SOCK_DGRAM`，受 `net.ipv4.ping_group_range` 限制)
+pub struct EchoSocket {
+    fd: OwnedFd,
+    v6: bool,
+    raw: bool,
+    /// Echo 标识符；ping 套接字由内核按绑定的端口号改写
+    id: u16,
+}
+
+impl EchoSocket {
+    pub fn open(v6: bool) -> io::Result<Self> {
+        let raw_err = match icmp_socket(v6) {
+            Ok(fd) => return Ok(EchoSocket { fd, v6, raw: true, id: next_echo_id() }),
+            Err(e) => e,
+        };
+        let proto = if v6 { libc::IPPROTO_ICMPV6 } else { libc::IPPROTO_ICMP };
+        let fd = match socket(v6, libc::SOCK_DGRAM, proto) {
+            Ok(fd) => fd,
+            // ping 套接字也不可用时报告原始套接字的权限错误
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EACCES | libc::EPERM)) => return Err(raw_err),
+            Err(e) => return Err(e),
+        };
+        let any = if v6 { SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)) } else { SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)) };
+        let (addr, len) = to_sockaddr(any);
+        cvt(unsafe { libc::bind(fd.as_raw_fd(), &addr as *const _ as *const libc::sockaddr, len) })?;
+        let id = local_port(&fd)?;
+        Ok(EchoSocket { fd, v6, raw: false, id })
+    }
+
+    /// 是否为原始套接字；ping 套接字收不到 ICMP 差错报文
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn set_pmtu_probe(&self) -> io::Result<()> {
+        set_pmtu_probe(&self.fd, self.v6)
+    }
+
+    pub fn send(&self, addr: IpAddr, seq: u16, payload_len: usize) -> io::Result<()> {
+        send_to(&self.fd, &echo_request(self.v6, self.id, seq, payload_len), SocketAddr::new(addr, 0))
+    }
+
+    /// 非阻塞读取下一个属于本套接字的报文：Echo Reply 或引用本套接字请求的差错报文
+    pub fn recv_message(&self, buf: &mut [u8]) -> io::Result<Option<IcmpMessage>> {
+        while let Some(message) = recv_message(&self.fd, self.v6, self.raw && !self.v6, buf)? {
+            if matches!(message.probe, ProbeId::Echo { id, .. } if id == self.id) {
+                return Ok(Some(message));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 非阻塞读取下一个属于本套接字的 Echo Reply，返回其序号
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(IpAddr, u16)>> {
+        while let Some(message) = self.recv_message(buf)? {
+            if let (IcmpKind::EchoReply, ProbeId::Echo { seq, .. }) = (message.kind, message.probe) {
+                return Ok(Some((message.from, seq)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn pollfd(&self) -> libc::pollfd {
+        pollfd_in(self.fd.as_raw_fd())
+    }
+}
+
+/// 以指定 TTL 发起的非阻塞 TCP 连接，用于 TCP SYN 探测
+pub struct TcpProbe {
+    fd: OwnedFd,
+    pub local_port: u16,
+}
+
+impl TcpProbe {
+    pub fn connect(addr: SocketAddr, ttl: u8) -> io::Result<Self> {
+        let v6 = addr.is_ipv6();
+        let fd = socket(v6, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0)?;
+        set_ttl(&fd, v6, ttl)?;
+        let (storage, len) = to_sockaddr(addr);
+        let ret = unsafe { libc::connect(fd.as_raw_fd(), &storage as *const _ as *const libc::sockaddr, len) };
+        if ret < 0 {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(libc::EINPROGRESS) {
+                return Err(e);
+            }
+        }
+        let local_port = local_port(&fd)?;
+        Ok(TcpProbe { fd, local_port })
+    }
+
+    pub fn pollfd(&self) -> libc::pollfd {
+        libc::pollfd { fd: self.fd.as_raw_fd(), events: libc::POLLOUT, revents: 0 }
+    }
+
+    /// 连接结束后的结果：`true` 表示目标已回应（连接成功或被 RST 拒绝）
+    pub fn answered(&self) -> bool {
+        let mut error: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                self.fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut error as *mut libc::c_int as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        ret == 0 && (error == 0 || error == libc::ECONNREFUSED)
+    }
+}
+
+pub fn pollfd_in(fd: RawFd) -> libc::pollfd {
+    libc::pollfd { fd, events: libc::POLLIN, revents: 0 }
+}
+
+/// 等待任一描述符就绪，返回就绪数量
+pub fn poll(fds: &mut [libc::pollfd], timeout: Duration) -> io::Result<usize> {
+    let ret = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout.as_millis() as libc::c_int) };
+    if ret < 0 {
+        let e = io::Error::last_os_error();
+        return if e.kind() == io::ErrorKind::Interrupted { Ok(0) } else { Err(e) };
+    }
+    Ok(ret as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const ROUTER6: IpAddr = IpAddr::V6(Ipv6Addr::LOCALHOST);
+
+    /// 最小 IPv4 头：仅填写版本/头长与协议号
+    fn ipv4_header(proto: u8) -> Vec<u8> {
+        let mut header = vec![0u8; 20];
+        header[0] = 0x45;
+        header[9] = proto;
+        header
+    }
+
+    /// 最小 IPv6 头：仅填写版本与下一头部
+    fn ipv6_header(next: u8) -> Vec<u8> {
+        let mut header = vec![0u8; IPV6_HEADER];
+        header[0] = 0x60;
+        header[6] = next;
+        header
+    }
+
+    /// 引用一个 UDP 探测的原始报文
+    fn quoted_udp(src: u16, dst: u16) -> Vec<u8> {
+        let mut quoted = ipv4_header(libc::IPPROTO_UDP as u8);
+        quoted.extend_from_slice(&src.to_be_bytes());
+        quoted.extend_from_slice(&dst.to_be_bytes());
+        quoted.extend_from_slice(&[0, 8, 0, 0]);
+        quoted
+    }
+
+    #[test]
+    fn checksum_matches_rfc1071_example() {
+        assert_eq!(checksum(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]), !0xddf2);
+        // 奇数长度时末字节补零
+        assert_eq!(checksum(&[0x00, 0x01, 0xf2]), checksum(&[0x00, 0x01, 0xf2, 0x00]));
+        // 填入校验和后整包再算一次应为 0
+        let packet = echo_request(false, 0x1234, 7, 33);
+        assert_eq!(checksum(&packet), 0);
+    }
+
+    #[test]
+    fn parses_echo_reply_after_ipv4_header() {
+        let mut packet = ipv4_header(libc::IPPROTO_ICMP as u8);
+        let mut reply = echo_request(false, 0x1234, 7, 8);
+        reply[0] = ICMP_ECHO_REPLY;
+        packet.extend_from_slice(&reply);
+        let message = parse_icmp(false, true, ROUTER, &packet).expect("应解析出 Echo Reply");
+        assert_eq!(message.kind, IcmpKind::EchoReply);
+        assert_eq!(message.probe, ProbeId::Echo { id: 0x1234, seq: 7 });
+        assert!(message.is_final());
+        // ping 套接字收到的报文不带 IP 头
+        assert!(parse_icmp(false, false, ROUTER, &reply).is_some());
+    }
+
+    #[test]
+    fn parses_time_exceeded_quoting_udp_probe() {
+        let mut packet = vec![ICMP_TIME_EXCEEDED, 0, 0, 0, 0, 0, 0, 0];
+        packet.extend_from_slice(&quoted_udp(40000, 33434));
+        let message = parse_icmp(false, false, ROUTER, &packet).expect("应解析出超时报文");
+        assert_eq!(message.from, ROUTER);
+        assert_eq!(message.kind, IcmpKind::TimeExceeded);
+        assert_eq!(message.probe, ProbeId::Port { proto: libc::IPPROTO_UDP as u8, src: 40000, dst: 33434 });
+        assert!(!message.is_final());
+    }
+
+    #[test]
+    fn parses_frag_needed_and_packet_too_big() {
+        let mut packet = vec![ICMP_DEST_UNREACH, ICMP_FRAG_NEEDED, 0, 0, 0, 0, 0x05, 0xdc];
+        packet.extend_from_slice(&quoted_udp(1, 2));
+        let message = parse_icmp(false, false, ROUTER, &packet).unwrap();
+        assert_eq!(message.kind, IcmpKind::PacketTooBig(1500));
+
+        let mut packet = vec![ICMP6_PACKET_TOO_BIG, 0, 0, 0, 0, 0, 0x05, 0x00];
+        packet.extend_from_slice(&ipv6_header(libc::IPPROTO_ICMPV6 as u8));
+        packet.extend_from_slice(&echo_request(true, 0x4321, 3, 0));
+        let message = parse_icmp(true, false, ROUTER6, &packet).unwrap();
+        assert_eq!(message.kind, IcmpKind::PacketTooBig(1280));
+        assert_eq!(message.probe, ProbeId::Echo { id: 0x4321, seq: 3 });
+    }
+
+    #[test]
+    fn parse_quoted_honours_ipv4_header_length() {
+        // 带 4 字节选项的 IPv4 头 (IHL=6)
+        let mut quoted = ipv4_header(libc::IPPROTO_TCP as u8);
+        quoted[0] = 0x46;
+        quoted.extend_from_slice(&[0; 4]);
+        quoted.extend_from_slice(&[0x9c, 0x40, 0x01, 0xbb, 0, 0, 0, 0]);
+        assert_eq!(
+            parse_quoted(false, &quoted),
+            Some(ProbeId::Port { proto: libc::IPPROTO_TCP as u8, src: 40000, dst: 443 })
+        );
+    }
+
+    #[test]
+    fn parse_quoted_rejects_short_and_unknown_packets() {
+        let quoted = quoted_udp(1, 2);
+        // 传输层头不足 4 字节
+        assert_eq!(parse_quoted(false, &quoted[..22]), None);
+        assert_eq!(parse_quoted(false, &[]), None);
+        assert_eq!(parse_quoted(true, &ipv6_header(libc::IPPROTO_UDP as u8)), None);
+        // 非 Echo 请求的 ICMP 与其他协议都不认
+        let mut quoted = ipv4_header(libc::IPPROTO_ICMP as u8);
+        quoted.extend_from_slice(&[ICMP_ECHO_REPLY, 0, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(parse_quoted(false, &quoted), None);
+        let mut quoted = ipv4_header(47);
+        quoted.extend_from_slice(&[0; 8]);
+        assert_eq!(parse_quoted(false, &quoted), None);
+    }
+
+    #[test]
+    fn parse_icmp_ignores_unknown_and_truncated_messages() {
+        // Echo 请求本身不是回应
+        assert!(parse_icmp(false, false, ROUTER, &echo_request(false, 1, 1, 0)).is_none());
+        assert!(parse_icmp(false, false, ROUTER, &[ICMP_TIME_EXCEEDED]).is_none());
+        // 差错报文缺少引用的原始报文
+        assert!(parse_icmp(false, false, ROUTER, &[ICMP_TIME_EXCEEDED, 0, 0, 0, 0, 0, 0, 0]).is_none());
+        // IPv4 头长超出报文
+        assert!(parse_icmp(false, true, ROUTER, &[0x4f, 0, 0, 0]).is_none());
+        assert!(parse_icmp(false, true, ROUTER, &[]).is_none());
+    }
+}
@@ -0,0 +1,387 @@
+// src/params.rs
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 参数类型及其约束
+#[derive(Debug, Clone)]
+pub enum ParamKind {
+    /// 闭区间内的整数
+    Integer { min: i64, max: i64 },
+    /// 文件系统路径
+    Path { must_exist: bool, writable: bool },
+    /// 枚举选项之一
+    Choice(&'static [&'static str]),
+    /// 逗号分隔的多个枚举选项，`all` 表示全部
+    MultiChoice(&'static [&'static str]),
+    /// 逗号分隔的字节大小列表，支持 k/m/g 后缀，如 `4k,64k,1m`
+    SizeList,
+    /// 端口列表，如 `80,443/tcp,53/udp`
+    Ports,
+    Bool,
+    Text,
+}
+
+/// 端口号与协议
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortSpec {
+    pub port: u16,
+    pub protocol: String,
+}
+
+impl fmt::Display for PortSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.port, self.protocol)
+    }
+}
+
+/// 校验通过后的参数值
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Integer(i64),
+    Path(PathBuf),
+    Choice(String),
+    List(Vec<String>),
+    Sizes(Vec<u64>),
+    Ports(Vec<PortSpec>),
+    Bool(bool),
+    Text(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Integer(v) => write!(f, "{}", v),
+            ParamValue::Path(p) => write!(f, "{}", p.display()),
+            ParamValue::Choice(s) | ParamValue::Text(s) => write!(f, "{}", s),
+            ParamValue::List(items) => write!(f, "{}", items.join(",")),
+            ParamValue::Sizes(sizes) => {
+                let list: Vec<String> = sizes.iter().map(|s| format_size(*s)).collect();
+                write!(f, "{}", list.join(","))
+            }
+            ParamValue::Ports(ports) => {
+                let list: Vec<String> = ports.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", list.join(","))
+            }
+            ParamValue::Bool(b) => write!(f, "{}", if *b { "yes" } else { "no" }),
+        }
+    }
+}
+
+/// 任务参数声明，交互模式与命令行模式共用
+#[derive(Debug, Clone)]
+pub struct ParamSpec {
+    /// 写入 `TaskConfig.params` 的键
+    pub key: &'static str,
+    /// 命令行选项名（不含 `--`）
+    pub flag: &'static str,
+    /// 交互模式下的提示语
+    pub prompt: &'static str,
+    pub help: &'static str,
+    pub kind: ParamKind,
+    /// 默认值（原始文本）；`None` 表示必填
+    pub default: Option<&'static str>,
+}
+
+impl ParamSpec {
+    fn new(key: &'static str, flag: &'static str, prompt: &'static str, kind: ParamKind) -> Self {
+        Self { key, flag, prompt, help: "", kind, default: None }
+    }
+
+    pub fn integer(key: &'static str, flag: &'static str, prompt: &'static str, min: i64, max: i64) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Integer { min, max })
+    }
+
+    /// 必须存在且可写入的目录
+    pub fn writable_dir(key: &'static str, flag: &'static str, prompt: &'static str) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Path { must_exist: true, writable: true })
+    }
+
+    pub fn choice(key: &'static str, flag: &'static str, prompt: &'static str, options: &'static [&'static str]) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Choice(options))
+    }
+
+    pub fn multi_choice(key: &'static str, flag: &'static str, prompt: &'static str, options: &'static [&'static str]) -> Self {
+        Self::new(key, flag, prompt, ParamKind::MultiChoice(options))
+    }
+
+    pub fn size_list(key: &'static str, flag: &'static str, prompt: &'static str) -> Self {
+        Self::new(key, flag, prompt, ParamKind::SizeList)
+    }
+
+    pub fn ports(key: &'static str, flag: &'static str, prompt: &'static str) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Ports)
+    }
+
+    pub fn boolean(key: &'static str, flag: &'static str, prompt: &'static str) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Bool)
+    }
+
+    pub fn text(key: &'static str, flag: &'static str, prompt: &'static str) -> Self {
+        Self::new(key, flag, prompt, ParamKind::Text)
+    }
+
+    pub fn default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub fn help(mut self, help: &'static str) -> Self {
+        self.help = help;
+        self
+    }
+
+    /// 约束的简短描述，用于帮助信息
+    pub fn constraint(&self) -> String {
+        match &self.kind {
+            ParamKind::Integer { min, max } => format!("整数 {}..={}", min, max),
+            ParamKind::Path { writable: true, .. } => "可写目录".to_string(),
+            ParamKind::Path { must_exist: true, .. } => "已存在的路径".to_string(),
+            ParamKind::Path { .. } => "路径".to_string(),
+            ParamKind::Choice(options) => options.join("|"),
+            ParamKind::MultiChoice(options) => format!("{} 的逗号分隔组合或 all", options.join("|")),
+            ParamKind::SizeList => "大小列表，如 4k,64k,1m".to_string(),
+            ParamKind::Ports => "端口列表，如 80,443/tcp".to_string(),
+            ParamKind::Bool => "yes|no".to_string(),
+            ParamKind::Text => "文本".to_string(),
+        }
+    }
+
+    /// 校验原始输入；空输入使用默认值
+    pub fn parse(&self, raw: &str) -> Result<ParamValue, String> {
+        let raw = raw.trim();
+        let raw = if raw.is_empty() {
+            self.default.ok_or_else(|| "该参数为必填项".to_string())?
+        } else {
+            raw
+        };
+
+        match &self.kind {
+            ParamKind::Integer { min, max } => {
+                let value: i64 = raw.parse().map_err(|_| format!("'{}' 不是有效的整数", raw))?;
+                if value < *min || value > *max {
+                    return Err(format!("{} 超出范围 {}..={}", value, min, max));
+                }
+                Ok(ParamValue::Integer(value))
+            }
+            ParamKind::Path { must_exist, writable } => {
+                let path = PathBuf::from(raw);
+                if *must_exist && !path.exists() {
+                    return Err(format!("路径 {} 不存在", path.display()));
+                }
+                if *writable {
+                    check_writable_dir(&path)?;
+                }
+                Ok(ParamValue::Path(path))
+            }
+            ParamKind::Choice(options) => options
+                .iter()
+                .find(|o| o.eq_ignore_ascii_case(raw))
+                .map(|o| ParamValue::Choice(o.to_string()))
+                .ok_or_else(|| format!("'{}' 不是有效选项，可选: {}", raw, options.join(", "))),
+            ParamKind::MultiChoice(options) => parse_multi_choice(raw, options).map(ParamValue::List),
+            ParamKind::SizeList => parse_size_list(raw).map(ParamValue::Sizes),
+            ParamKind::Ports => parse_ports(raw).map(ParamValue::Ports),
+            ParamKind::Bool => match raw.to_lowercase().as_str() {
+                "y" | "yes" | "true" | "1" | "on" | "是" => Ok(ParamValue::Bool(true)),
+                "n" | "no" | "false" | "0" | "off" | "否" => Ok(ParamValue::Bool(false)),
+                _ => Err(format!("'{}' 不是有效的布尔值 (yes/no)", raw)),
+            },
+            ParamKind::Text => Ok(ParamValue::Text(raw.to_string())),
+        }
+    }
+}
+
+/// 检查目录可写：实际创建并删除一个临时文件
+fn check_writable_dir(path: &Path) -> Result<(), String> {
+    if !path.is_dir() {
+        return Err(format!("{} 不是目录", path.display()));
+    }
+    tempfile::tempfile_in(path)
+        .map(|_| ())
+        .map_err(|e| format!("目录 {} 不可写: {}", path.display(), e))
+}
+
+/// 解析逗号分隔的枚举选项，`all` 展开为全部选项，结果去重并保持输入顺序
+fn parse_multi_choice(raw: &str, options: &[&str]) -> Result<Vec<String>, String> {
+    if raw.eq_ignore_ascii_case("all") {
+        return Ok(options.iter().map(|o| o.to_string()).collect());
+    }
+    let mut selected: Vec<String> = Vec::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let option = options
+            .iter()
+            .find(|o| o.eq_ignore_ascii_case(part))
+            .ok_or_else(|| format!("'{}' 不是有效选项，可选: {}", part, options.join(", ")))?;
+        if !selected.iter().any(|s| s == option) {
+            selected.push(option.to_string());
+        }
+    }
+    if selected.is_empty() {
+        return Err("至少需要选择一项".to_string());
+    }
+    Ok(selected)
+}
+
+/// 解析 `4k`、`1m`、`512` 形式的字节大小（1k = 1024）
+pub fn parse_size(raw: &str) -> Result<u64, String> {
+    let lower = raw.trim().to_lowercase();
+    let lower = lower.strip_suffix('b').unwrap_or(&lower);
+    let (digits, multiplier) = if let Some(d) = lower.strip_suffix('k') {
+        (d, 1024)
+    } else if let Some(d) = lower.strip_suffix('m') {
+        (d, 1024 * 1024)
+    } else if let Some(d) = lower.strip_suffix('g') {
+        (d, 1024 * 1024 * 1024)
+    } else {
+        (lower, 1)
+    };
+    let value = digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(|| format!("'{}' 不是有效的大小", raw))?;
+    value.checked_mul(multiplier).ok_or_else(|| format!("'{}' 超出可表示的范围", raw))
+}
+
+/// 将字节数格式化为 `4k`、`1m` 形式
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[(u64, &str)] = &[(1024 * 1024 * 1024, "g"), (1024 * 1024, "m"), (1024, "k")];
+    for &(unit, suffix) in UNITS {
+        if bytes >= unit && bytes.is_multiple_of(unit) {
+            return format!("{}{}", bytes / unit, suffix);
+        }
+    }
+    bytes.to_string()
+}
+
+fn parse_size_list(raw: &str) -> Result<Vec<u64>, String> {
+    let sizes = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(parse_size)
+        .collect::<Result<Vec<u64>, String>>()?;
+    if sizes.is_empty() {
+        return Err("大小列表为空".to_string());
+    }
+    Ok(sizes)
+}
+
+/// 解析 `80,443/tcp,53/udp` 形式的端口列表，未指定协议时默认 tcp
+fn parse_ports(raw: &str) -> Result<Vec<PortSpec>, String> {
+    let mut ports = Vec::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (port, protocol) = match part.split_once('/') {
+            Some((port, protocol)) => (port, protocol.to_lowercase()),
+            None => (part, "tcp".to_string()),
+        };
+        if protocol != "tcp" && protocol != "udp" {
+            return Err(format!("'{}' 协议无效，仅支持 tcp/udp", part));
+        }
+        let port: u16 = port
+            .parse()
+            .ok()
+            .filter(|p| *p > 0)
+            .ok_or_else(|| format!("'{}' 不是有效的端口号 (1-65535)", part))?;
+        ports.push(PortSpec { port, protocol });
+    }
+    if ports.is_empty() {
+        return Err("端口列表为空".to_string());
+    }
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &[&str] = &["cpu", "mem", "disk"];
+
+    fn port(port: u16, protocol: &str) -> PortSpec {
+        PortSpec { port, protocol: protocol.to_string() }
+    }
+
+    #[test]
+    fn parse_size_accepts_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert_eq!(parse_size(" 64KB "), Ok(64 * 1024));
+        assert_eq!(parse_size("1m"), Ok(1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(format_size(parse_size("64k").unwrap()), "64k");
+        assert_eq!(format_size(1536), "1536");
+    }
+
+    #[test]
+    fn parse_size_rejects_zero_garbage_and_overflow() {
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("0k").is_err());
+        assert!(parse_size("").is_err());
+        assert!(parse_size("k").is_err());
+        assert!(parse_size("-4k").is_err());
+        assert!(parse_size("1.5m").is_err());
+        // 不支持的后缀
+        assert!(parse_size("99999999T").is_err());
+        // 乘以单位后溢出 u64
+        let err = parse_size("99999999999g").unwrap_err();
+        assert!(err.contains("超出"), "{}", err);
+        assert!(parse_size("18446744073709551615k").unwrap_err().contains("超出"));
+        assert_eq!(parse_size("18446744073709551615"), Ok(u64::MAX));
+        // 数字本身超出 u64
+        assert!(parse_size("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn parse_size_list_skips_empty_items() {
+        assert_eq!(parse_size_list("4k, ,1m,"), Ok(vec![4096, 1024 * 1024]));
+        assert!(parse_size_list(" , ").is_err());
+        assert!(parse_size_list("4k,0").is_err());
+    }
+
+    #[test]
+    fn parse_ports_defaults_to_tcp() {
+        assert_eq!(
+            parse_ports("80, 443/tcp,53/UDP"),
+            Ok(vec![port(80, "tcp"), port(443, "tcp"), port(53, "udp")])
+        );
+        assert_eq!(parse_ports("65535"), Ok(vec![port(65535, "tcp")]));
+    }
+
+    #[test]
+    fn parse_ports_rejects_invalid_entries() {
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("65536").is_err());
+        assert!(parse_ports("80/sctp").unwrap_err().contains("协议无效"));
+        assert!(parse_ports("/tcp").is_err());
+        // 不支持端口范围，倒序的范围同样拒绝
+        assert!(parse_ports("1000-80").is_err());
+        assert!(parse_ports("80,1000-80").is_err());
+        assert!(parse_ports(" , ").is_err());
+    }
+
+    #[test]
+    fn parse_multi_choice_expands_all_and_dedupes() {
+        assert_eq!(parse_multi_choice("all", OPTIONS), Ok(vec!["cpu".into(), "mem".into(), "disk".into()]));
+        assert_eq!(parse_multi_choice("ALL", OPTIONS).map(|v| v.len()), Ok(3));
+        assert_eq!(parse_multi_choice("Disk, cpu,disk", OPTIONS), Ok(vec!["disk".into(), "cpu".into()]));
+    }
+
+    #[test]
+    fn parse_multi_choice_rejects_unknown_and_empty() {
+        let err = parse_multi_choice("cpu,gpu", OPTIONS).unwrap_err();
+        assert!(err.contains("'gpu'"), "{}", err);
+        assert!(parse_multi_choice("all,cpu", OPTIONS).is_err());
+        assert!(parse_multi_choice(" , ", OPTIONS).is_err());
+    }
+
+    #[test]
+    fn spec_parse_uses_default_and_checks_range() {
+        let spec = ParamSpec::integer("n", "n", "n", 1, 10).default("3");
+        assert_eq!(spec.parse("  "), Ok(ParamValue::Integer(3)));
+        assert!(spec.parse("11").is_err());
+        assert!(ParamSpec::text("t", "t", "t").parse("").is_err());
+        let spec = ParamSpec::multi_choice("m", "m", "m", OPTIONS);
+        assert_eq!(spec.parse("mem").map(|v| v.to_string()), Ok("mem".to_string()));
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+/// 每个 2 的幂区间再细分的子桶数，决定了约 1/32 ≈ 3% 的相对精度
+const SUB_BUCKETS: u64 = 32;
+const SUB_BITS: u32 = 5;
+const BUCKETS: usize = ((64 - SUB_BITS as usize) + 1) * SUB_BUCKETS as usize;
+
+/// 对数-线性分桶的延迟直方图（纳秒），可在线程间合并
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum_ns: u128,
+    min_ns: u64,
+    max_ns: u64,
+}
+
+/// 延迟分布摘要（微秒）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencySummary {
+    pub min: f64,
+    pub avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    /// 列标题，与 [`LatencySummary::values`] 顺序一致
+    pub const COLUMNS: [&'static str; 7] = ["min", "avg", "p50", "p95", "p99", "p99.9", "max"];
+
+    pub fn values(&self) -> [f64; 7] {
+        [self.min, self.avg, self.p50, self.p95, self.p99, self.p999, self.max]
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self { counts: vec![0; BUCKETS], count: 0, sum_ns: 0, min_ns: u64::MAX, max_ns: 0 }
+    }
+
+    fn bucket_index(ns: u64) -> usize {
+        if ns < SUB_BUCKETS {
+            return ns as usize;
+        }
+        let msb = 63 - ns.leading_zeros();
+        let shift = msb - SUB_BITS;
+        let sub = (ns >> shift) - SUB_BUCKETS;
+        ((shift as u64 + 1) * SUB_BUCKETS + sub) as usize
+    }
+
+    /// 桶的代表值（区间中点）
+    fn bucket_value(index: usize) -> u64 {
+        let index = index as u64;
+        if index < SUB_BUCKETS {
+            return index;
+        }
+        let shift = index / SUB_BUCKETS - 1;
+        let sub = index % SUB_BUCKETS;
+        let lower = (SUB_BUCKETS + sub) << shift;
+        lower + ((1u64 << shift) >> 1)
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let ns = latency.as_nanos().min(u64::MAX as u128) as u64;
+        self.counts[Self::bucket_index(ns)] += 1;
+        self.count += 1;
+        self.sum_ns += ns as u128;
+        self.min_ns = self.min_ns.min(ns);
+        self.max_ns = self.max_ns.max(ns);
+    }
+
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (a, b) in self.counts.iter_mut().zip(&other.counts) {
+            *a += b;
+        }
+        self.count += other.count;
+        self.sum_ns += other.sum_ns;
+        self.min_ns = self.min_ns.min(other.min_ns);
+        self.max_ns = self.max_ns.max(other.max_ns);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 第 `p` 百分位 (0-100) 的延迟（纳秒）
+    pub fn percentile_ns(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((p / 100.0 * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::bucket_value(index).clamp(self.min_ns, self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        if self.count == 0 {
+            return LatencySummary::default();
+        }
+        let us = |ns: u64| ns as f64 / 1000.0;
+        LatencySummary {
+            min: us(self.min_ns),
+            avg: self.sum_ns as f64 / self.count as f64 / 1000.0,
+            p50: us(self.percentile_ns(50.0)),
+            p95: us(self.percentile_ns(95.0)),
+            p99: us(self.percentile_ns(99.0)),
+            p999: us(self.percentile_ns(99.9)),
+            max: us(self.max_ns),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_boundaries_are_contiguous() {
+        // 小于 32 的值各占一个桶，之后每个 2 的幂区间分 32 个子桶
+        assert_eq!(LatencyHistogram::bucket_index(0), 0);
+        assert_eq!(LatencyHistogram::bucket_index(31), 31);
+        assert_eq!(LatencyHistogram::bucket_index(32), 32);
+        assert_eq!(LatencyHistogram::bucket_index(63), 63);
+        assert_eq!(LatencyHistogram::bucket_index(64), 64);
+        assert_eq!(LatencyHistogram::bucket_index(65), 64);
+        assert_eq!(LatencyHistogram::bucket_index(66), 65);
+        assert_eq!(LatencyHistogram::bucket_index(127), 95);
+        assert_eq!(LatencyHistogram::bucket_index(128), 96);
+        assert_eq!(LatencyHistogram::bucket_value(31), 31);
+        assert_eq!(LatencyHistogram::bucket_value(64), 65);
+
+        let mut last = 0;
+        for ns in 0..1_000_000u64 {
+            let index = LatencyHistogram::bucket_index(ns);
+            assert!(index == last || index == last + 1, "{} 的桶不连续", ns);
+            last = index;
+        }
+        // 代表值与原值的相对误差不超过 1/64
+        for ns in (0..100_000u64).chain([1 << 40, (1 << 40) - 1, u64::MAX / 3]) {
+            let value = LatencyHistogram::bucket_value(LatencyHistogram::bucket_index(ns));
+            assert!(value.abs_diff(ns) <= ns / 64, "{} 的代表值 {}", ns, value);
+        }
+    }
+
+    #[test]
+    fn values_above_the_top_bucket_are_clamped() {
+        assert_eq!(LatencyHistogram::bucket_index(u64::MAX), BUCKETS - 1);
+        assert!(LatencyHistogram::bucket_value(BUCKETS - 1) > 1 << 63);
+
+        let mut hist = LatencyHistogram::new();
+        hist.record(Duration::MAX);
+        hist.record(Duration::from_nanos(u64::MAX));
+        assert_eq!(hist.count(), 2);
+        assert_eq!(hist.counts[BUCKETS - 1], 2);
+        assert_eq!(hist.percentile_ns(100.0), u64::MAX);
+    }
+
+    #[test]
+    fn percentiles_of_uniform_distribution() {
+        let mut hist = LatencyHistogram::new();
+        for us in 1..=1000 {
+            hist.record(Duration::from_micros(us));
+        }
+        let near = |actual: u64, expected: u64| actual.abs_diff(expected) <= expected / 32;
+        assert!(near(hist.percentile_ns(50.0), 500_000), "p50 = {}", hist.percentile_ns(50.0));
+        assert!(near(hist.percentile_ns(99.0), 990_000), "p99 = {}", hist.percentile_ns(99.0));
+        // 百分位被限制在实际的最小/最大值之间
+        assert_eq!(hist.percentile_ns(0.0), 1_000);
+        assert_eq!(hist.percentile_ns(100.0), 1_000_000);
+
+        let summary = hist.summary();
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 1000.0);
+        assert!((summary.avg - 500.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn merge_matches_single_histogram() {
+        let (mut a, mut b, mut all) = (LatencyHistogram::new(), LatencyHistogram::new(), LatencyHistogram::new());
+        for us in 1..=100 {
+            let latency = Duration::from_micros(us * 7);
+            if us % 2 == 0 { a.record(latency) } else { b.record(latency) }
+            all.record(latency);
+        }
+        a.merge(&b);
+        assert_eq!(a.count(), all.count());
+        assert_eq!(a.summary().values(), all.summary().values());
+        assert_eq!(LatencyHistogram::new().summary().values(), [0.0; 7]);
+    }
+}
@@ -1,3 +1,683 @@
-pub fn run_bandwidth_test() {
-    println!("带宽测试功能待实现");
+pub mod server;
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::models::{Direction, Metric, ResultTable};
+
+pub const DEFAULT_PORT: u16 = 5201;
+const TCP_BUF: usize = 128 * 1024;
+/// UDP 数据包大小，低于常见 MTU 以避免分片
+const UDP_PACKET: usize = 1400;
+/// 会话 ID (8) + 流序号 (4) + 包序号 (8) + 发送时间 (8)
+const UDP_HEADER: usize = 28;
+/// 反向 UDP 测试时客户端发送的登记包序号，服务端据此获知客户端地址
+const HELLO_SEQ: u64 = u64::MAX;
+const HELLO_INTERVAL: Duration = Duration::from_millis(100);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 建立数据连接、等待登记包的超时
+const SETUP_TIMEOUT: Duration = Duration::from_secs(5);
+/// 控制连接在测试时长之外额外等待的时间
+const CONTROL_MARGIN: Duration = Duration::from_secs(30);
+/// UDP 发送结束后等待在途数据包到达的时间
+const UDP_GRACE: Duration = Duration::from_millis(500);
+const RECV_POLL: Duration = Duration::from_millis(100);
+const MAX_LINE: usize = 64 * 1024;
+/// UDP 接收缓冲区，默认值在高码率下很快溢出
+const UDP_RECV_BUF: libc::c_int = 8 * 1024 * 1024;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// UDP 丢包率超过该百分比视为异常
+pub const LOSS_WARN_PCT: f64 = 1.0;
+
+/// 传输协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    pub const KEYS: &'static [&'static str] = &["tcp", "udp"];
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "tcp" => Some(Protocol::Tcp),
+            "udp" => Some(Protocol::Udp),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "TCP",
+            Protocol::Udp => "UDP",
+        }
+    }
+}
+
+/// 测试参数，由客户端发给服务端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestSpec {
+    pub protocol: Protocol,
+    pub streams: u32,
+    pub duration_ms: u64,
+    /// 反向模式：服务端发送，客户端接收
+    pub reverse: bool,
+    /// UDP 全部流合计的目标码率 (bit/s)
+    pub bitrate: u64,
+}
+
+impl TestSpec {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    fn stream_bitrate(&self) -> u64 {
+        self.bitrate / self.streams.max(1) as u64
+    }
+
+    fn direction(&self) -> &'static str {
+        if self.reverse { "服务端 → 客户端" } else { "客户端 → 服务端" }
+    }
+}
+
+/// 单条数据流在发送端或接收端的统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamStats {
+    pub bytes: u64,
+    pub packets: u64,
+    pub secs: f64,
+    /// 仅 UDP 接收端有效
+    pub out_of_order: u64,
+    /// 仅 UDP 接收端有效，按 RFC 3550 计算 (ms)
+    pub jitter_ms: f64,
+}
+
+impl StreamStats {
+    pub fn mbps(&self) -> f64 {
+        if self.secs > 0.0 { self.bytes as f64 * 8.0 / self.secs / 1e6 } else { 0.0 }
+    }
+}
+
+/// 控制连接与数据连接上的消息，每条为一行 JSON
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Start(TestSpec),
+    Data { session: u64, stream: u32 },
+    Accepted { session: u64 },
+    Error { message: String },
+    /// 发送端结束后告知接收端的发送统计
+    Sent { streams: Vec<StreamStats> },
+    /// 服务端作为接收端时回报的接收统计
+    Received { streams: Vec<StreamStats> },
+}
+
+fn send_message(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// 逐字节读取一行，不预读后续数据，数据连接的首行之后紧跟测试数据
+fn read_message(stream: &mut TcpStream) -> io::Result<Message> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "连接已关闭"));
+        }
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+        if line.len() > MAX_LINE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "消息过长"));
+        }
+    }
+    Ok(serde_json::from_slice(&line)?)
+}
+
+fn unexpected(message: Message) -> io::Error {
+    match message {
+        Message::Error { message } => io::Error::other(message),
+        other => io::Error::new(io::ErrorKind::InvalidData, format!("意外的消息: {:?}", other)),
+    }
+}
+
+fn tcp_send(mut stream: TcpStream, duration: Duration, counter: &AtomicU64) -> io::Result<StreamStats> {
+    let buf = vec![0u8; TCP_BUF];
+    let start = Instant::now();
+    let mut bytes = 0u64;
+    while start.elapsed() < duration {
+        let n = stream.write(&buf)?;
+        bytes += n as u64;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    let secs = start.elapsed().as_secs_f64();
+    stream.shutdown(Shutdown::Write)?;
+    Ok(StreamStats { bytes, secs, ..StreamStats::default() })
+}
+
+/// 接收直到对端关闭连接，计时从收到第一个字节开始
+fn tcp_recv(mut stream: TcpStream, counter: &AtomicU64) -> io::Result<StreamStats> {
+    let mut buf = vec![0u8; TCP_BUF];
+    let mut first = None;
+    let mut bytes = 0u64;
+    loop {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        first.get_or_insert_with(Instant::now);
+        bytes += n as u64;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    let secs = first.map(|t| t.elapsed().as_secs_f64()).unwrap_or(0.0);
+    Ok(StreamStats { bytes, secs, ..StreamStats::default() })
+}
+
+fn encode_packet(buf: &mut [u8], session: u64, stream: u32, seq: u64, sent_ns: u64) {
+    buf[0..8].copy_from_slice(&session.to_be_bytes());
+    buf[8..12].copy_from_slice(&stream.to_be_bytes());
+    buf[12..20].copy_from_slice(&seq.to_be_bytes());
+    buf[20..28].copy_from_slice(&sent_ns.to_be_bytes());
+}
+
+fn decode_packet(buf: &[u8]) -> Option<(u64, u32, u64, u64)> {
+    if buf.len() < UDP_HEADER {
+        return None;
+    }
+    let u64_at = |at: usize| u64::from_be_bytes(buf[at..at + 8].try_into().unwrap());
+    Some((u64_at(0), u32::from_be_bytes(buf[8..12].try_into().unwrap()), u64_at(12), u64_at(20)))
+}
+
+/// 按目标码率匀速发送 UDP 包；`peer` 为 `None` 时使用已 connect 的套接字
+fn udp_send(
+    sock: &UdpSocket,
+    peer: Option<SocketAddr>,
+    session: u64,
+    stream: u32,
+    bitrate: u64,
+    duration: Duration,
+    counter: &AtomicU64,
+) -> io::Result<StreamStats> {
+    let packets_per_sec = (bitrate as f64 / (UDP_PACKET * 8) as f64).max(1.0);
+    let mut buf = vec![0u8; UDP_PACKET];
+    let start = Instant::now();
+    let mut seq = 0u64;
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            break;
+        }
+        let due = (elapsed.as_secs_f64() * packets_per_sec) as u64 + 1;
+        if seq >= due {
+            thread::sleep(Duration::from_micros(200));
+            continue;
+        }
+        encode_packet(&mut buf, session, stream, seq, elapsed.as_nanos() as u64);
+        let sent = match peer {
+            Some(peer) => sock.send_to(&buf, peer),
+            None => sock.send(&buf),
+        };
+        match sent {
+            Ok(n) => {
+                counter.fetch_add(n as u64, Ordering::Relaxed);
+                seq += 1;
+            }
+            // 发送缓冲区满时稍后重试
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.raw_os_error() == Some(libc::ENOBUFS) => {
+                thread::sleep(Duration::from_micros(200));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(StreamStats {
+        bytes: seq * UDP_PACKET as u64,
+        packets: seq,
+        secs: start.elapsed().as_secs_f64(),
+        ..StreamStats::default()
+    })
+}
+
+/// 单条 UDP 流的接收状态
+#[derive(Default)]
+struct UdpRecvState {
+    stats: StreamStats,
+    next_seq: u64,
+    last_transit_ms: Option<f64>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+/// 增大接收缓冲区；root 下可突破 `net.core.rmem_max` 限制，失败时保持默认值
+fn enlarge_recv_buffer(sock: &UdpSocket) {
+    use std::os::fd::AsRawFd;
+    let size = UDP_RECV_BUF;
+    for option in [libc::SO_RCVBUFFORCE, libc::SO_RCVBUF] {
+        let ret = unsafe {
+            libc::setsockopt(
+                sock.as_raw_fd(),
+                libc::SOL_SOCKET,
+                option,
+                &size as *const libc::c_int as *const libc::c_void,
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if ret == 0 {
+            return;
+        }
+    }
+}
+
+/// 接收本会话的全部 UDP 流，直到 `stop` 被置位
+fn udp_recv(sock: &UdpSocket, session: u64, streams: u32, stop: &AtomicBool, counter: &AtomicU64) -> io::Result<Vec<StreamStats>> {
+    sock.set_read_timeout(Some(RECV_POLL))?;
+    enlarge_recv_buffer(sock);
+    let mut states: Vec<UdpRecvState> = (0..streams).map(|_| UdpRecvState::default()).collect();
+    let mut buf = vec![0u8; UDP_PACKET * 2];
+    let start = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        let n = match sock.recv(&mut buf) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => continue,
+            Err(e) => return Err(e),
+        };
+        let Some((id, stream, seq, sent_ns)) = decode_packet(&buf[..n]) else { continue };
+        let Some(state) = states.get_mut(stream as usize) else { continue };
+        if id != session || seq == HELLO_SEQ {
+            continue;
+        }
+        let now = Instant::now();
+        state.first.get_or_insert(now);
+        state.last = Some(now);
+        state.stats.packets += 1;
+        state.stats.bytes += n as u64;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+        if seq < state.next_seq {
+            state.stats.out_of_order += 1;
+        } else {
+            state.next_seq = seq + 1;
+        }
+        // 两端时钟的偏差在差值中抵消
+        let transit_ms = now.duration_since(start).as_secs_f64() * 1000.0 - sent_ns as f64 / 1e6;
+        if let Some(last) = state.last_transit_ms {
+            state.stats.jitter_ms += ((transit_ms - last).abs() - state.stats.jitter_ms) / 16.0;
+        }
+        state.last_transit_ms = Some(transit_ms);
+    }
+    Ok(states
+        .into_iter()
+        .map(|mut s| {
+            if let (Some(first), Some(last)) = (s.first, s.last) {
+                s.stats.secs = last.duration_since(first).as_secs_f64();
+            }
+            s.stats
+        })
+        .collect())
+}
+
+fn join_all(handles: Vec<JoinHandle<io::Result<StreamStats>>>) -> io::Result<Vec<StreamStats>> {
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap_or_else(|_| Err(io::Error::other("数据流线程异常退出"))))
+        .collect()
+}
+
+/// 单条流的发送端与接收端统计
+#[derive(Debug, Clone)]
+pub struct StreamResult {
+    pub sent: StreamStats,
+    pub received: StreamStats,
+}
+
+impl StreamResult {
+    pub fn lost(&self) -> u64 {
+        self.sent.packets.saturating_sub(self.received.packets)
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        if self.sent.packets > 0 { self.lost() as f64 / self.sent.packets as f64 * 100.0 } else { 0.0 }
+    }
+}
+
+/// 一次带宽测试的结果
+#[derive(Debug, Clone)]
+pub struct BandwidthReport {
+    pub spec: TestSpec,
+    pub server: SocketAddr,
+    pub streams: Vec<StreamResult>,
+    /// 客户端每秒吞吐量 (Mbps)
+    pub samples: Vec<f64>,
+}
+
+impl BandwidthReport {
+    pub fn sent_mbps(&self) -> f64 {
+        self.streams.iter().map(|s| s.sent.mbps()).sum()
+    }
+
+    pub fn received_mbps(&self) -> f64 {
+        self.streams.iter().map(|s| s.received.mbps()).sum()
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        let sent: u64 = self.streams.iter().map(|s| s.sent.packets).sum();
+        let lost: u64 = self.streams.iter().map(StreamResult::lost).sum();
+        if sent > 0 { lost as f64 / sent as f64 * 100.0 } else { 0.0 }
+    }
+
+    pub fn jitter_ms(&self) -> f64 {
+        let n = self.streams.len().max(1) as f64;
+        self.streams.iter().map(|s| s.received.jitter_ms).sum::<f64>() / n
+    }
+
+    pub fn lossy(&self) -> bool {
+        self.spec.protocol == Protocol::Udp && self.loss_pct() > LOSS_WARN_PCT
+    }
+
+    pub fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = vec![
+            Metric::new("bandwidth_sent", self.sent_mbps(), "Mbps", Direction::Higher),
+            Metric::new("bandwidth_received", self.received_mbps(), "Mbps", Direction::Higher),
+        ];
+        if self.spec.protocol == Protocol::Udp {
+            metrics.push(Metric::new("udp_loss", self.loss_pct(), "%", Direction::Lower));
+            metrics.push(Metric::new("udp_jitter", self.jitter_ms(), "ms", Direction::Lower));
+        }
+        metrics
+    }
+
+    pub fn tables(&self) -> Vec<ResultTable> {
+        let mut table = ResultTable::new(
+            format!("{}带宽", self.spec.protocol.label()),
+            "Mbps",
+            vec!["发送".to_string(), "接收".to_string()],
+            Direction::Higher,
+        );
+        for (i, s) in self.streams.iter().enumerate() {
+            table.push_row(format!("流 {}", i + 1), vec![Some(s.sent.mbps()), Some(s.received.mbps())]);
+        }
+        if self.streams.len() > 1 {
+            table.push_row("合计", vec![Some(self.sent_mbps()), Some(self.received_mbps())]);
+        }
+        vec![table]
+    }
+
+    pub fn format(&self) -> String {
+        let udp = self.spec.protocol == Protocol::Udp;
+        let mut out = format!(
+            "\n{} {} ({})，{} 条流，{} 秒\n",
+            self.spec.protocol.label(),
+            self.server,
+            self.spec.direction(),
+            self.streams.len(),
+            self.spec.duration().as_secs()
+        );
+        out.push_str(&format!("{:<8} | {:>14} | {:>14}", "流", "发送", "接收"));
+        if udp {
+            out.push_str(&format!(" | {:>16} | {:>10} | {:>6}", "丢包", "抖动", "乱序"));
+        }
+        out.push('\n');
+        let mut line = |label: &str, sent: f64, received: f64, s: &StreamResult| {
+            out.push_str(&format!("{:<8} | {:>9.1} Mbps | {:>9.1} Mbps", label, sent, received));
+            if udp {
+                out.push_str(&format!(
+                    " | {:>6}/{:<6} {:>5.2}% | {:>7.3} ms | {:>6}",
+                    s.lost(),
+                    s.sent.packets,
+                    s.loss_pct(),
+                    s.received.jitter_ms,
+                    s.received.out_of_order
+                ));
+            }
+            out.push('\n');
+        };
+        for (i, s) in self.streams.iter().enumerate() {
+            line(&format!("流 {}", i + 1), s.sent.mbps(), s.received.mbps(), s);
+        }
+        if self.streams.len() > 1 {
+            let total = StreamResult {
+                sent: StreamStats { packets: self.streams.iter().map(|s| s.sent.packets).sum(), ..StreamStats::default() },
+                received: StreamStats {
+                    packets: self.streams.iter().map(|s| s.received.packets).sum(),
+                    out_of_order: self.streams.iter().map(|s| s.received.out_of_order).sum(),
+                    jitter_ms: self.jitter_ms(),
+                    ..StreamStats::default()
+                },
+            };
+            line("合计", self.sent_mbps(), self.received_mbps(), &total);
+        }
+        if !self.samples.is_empty() {
+            let min = self.samples.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = self.samples.iter().copied().fold(0.0, f64::max);
+            out.push_str(&format!("每秒吞吐: 最低 {:.1} Mbps，最高 {:.1} Mbps\n", min, max));
+        }
+        if self.lossy() {
+            out.push_str(&format!(
+                "⚠️ UDP 丢包率 {:.2}% 超过 {}%，链路可能拥塞或目标码率超出可用带宽。\n",
+                self.loss_pct(),
+                LOSS_WARN_PCT
+            ));
+        }
+        out
+    }
+}
+
+fn resolve(addr: &str) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("无法解析地址 {}", addr)))
+}
+
+/// 连接服务端运行一次测试；`on_sample` 每秒接收一次客户端侧吞吐量 (Mbps)
+pub fn run_client(addr: &str, spec: &TestSpec, on_sample: &mut dyn FnMut(f64)) -> io::Result<BandwidthReport> {
+    let server = resolve(addr)?;
+    let mut control = TcpStream::connect_timeout(&server, CONNECT_TIMEOUT)?;
+    control.set_nodelay(true)?;
+    control.set_read_timeout(Some(spec.duration() + CONTROL_MARGIN))?;
+    send_message(&mut control, &Message::Start(spec.clone()))?;
+    let session = match read_message(&mut control)? {
+        Message::Accepted { session } => session,
+        other => return Err(unexpected(other)),
+    };
+
+    let duration = spec.duration();
+    let counter = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut handles: Vec<JoinHandle<io::Result<StreamStats>>> = Vec::new();
+    let mut udp_receiver = None;
+    match spec.protocol {
+        Protocol::Tcp => {
+            for stream in 0..spec.streams {
+                let mut data = TcpStream::connect_timeout(&server, CONNECT_TIMEOUT)?;
+                data.set_write_timeout(Some(duration + CONTROL_MARGIN))?;
+                data.set_read_timeout(Some(duration + CONTROL_MARGIN))?;
+                send_message(&mut data, &Message::Data { session, stream })?;
+                let counter = counter.clone();
+                let reverse = spec.reverse;
+                handles.push(thread::spawn(move || {
+                    if reverse { tcp_recv(data, &counter) } else { tcp_send(data, duration, &counter) }
+                }));
+            }
+        }
+        Protocol::Udp => {
+            let bind: SocketAddr = if server.is_ipv6() { "[::]:0".parse().unwrap() } else { "0.0.0.0:0".parse().unwrap() };
+            let sock = Arc::new(UdpSocket::bind(bind)?);
+            sock.connect(server)?;
+            if spec.reverse {
+                let (sock, stop, counter, streams) = (sock.clone(), stop.clone(), counter.clone(), spec.streams);
+                udp_receiver = Some(thread::spawn(move || {
+                    // 持续发送登记包，直到收到第一个数据包
+                    let hello = {
+                        let (sock, counter, stop) = (sock.clone(), counter.clone(), stop.clone());
+                        thread::spawn(move || {
+                            let mut buf = [0u8; UDP_HEADER];
+                            encode_packet(&mut buf, session, 0, HELLO_SEQ, 0);
+                            let start = Instant::now();
+                            while counter.load(Ordering::Relaxed) == 0 && !stop.load(Ordering::Relaxed) && start.elapsed() < SETUP_TIMEOUT {
+                                let _ = sock.send(&buf);
+                                thread::sleep(HELLO_INTERVAL);
+                            }
+                        })
+                    };
+                    let result = udp_recv(&sock, session, streams, &stop, &counter);
+                    let _ = hello.join();
+                    result
+                }));
+            } else {
+                for stream in 0..spec.streams {
+                    let (sock, counter, bitrate) = (sock.clone(), counter.clone(), spec.stream_bitrate());
+                    handles.push(thread::spawn(move || udp_send(&sock, None, session, stream, bitrate, duration, &counter)));
+                }
+            }
+        }
+    }
+
+    // 按秒采样客户端侧吞吐量
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    let mut last_bytes = 0;
+    let mut last_time = start;
+    let mut next = start + SAMPLE_INTERVAL;
+    loop {
+        thread::sleep(Duration::from_millis(20));
+        let now = Instant::now();
+        let idle = !handles.is_empty() && handles.iter().all(|h| h.is_finished());
+        if now >= next || idle {
+            let bytes = counter.load(Ordering::Relaxed);
+            let elapsed = now.duration_since(last_time).as_secs_f64();
+            if elapsed >= SAMPLE_INTERVAL.as_secs_f64() / 2.0 {
+                let mbps = (bytes - last_bytes) as f64 * 8.0 / elapsed / 1e6;
+                samples.push(mbps);
+                on_sample(mbps);
+            }
+            last_bytes = bytes;
+            last_time = now;
+            next += SAMPLE_INTERVAL;
+        }
+        if idle || now.duration_since(start) >= duration {
+            break;
+        }
+    }
+
+    let streams = match (spec.reverse, udp_receiver) {
+        (true, Some(receiver)) => {
+            let sent = read_message(&mut control);
+            thread::sleep(UDP_GRACE);
+            stop.store(true, Ordering::Relaxed);
+            let received = receiver.join().unwrap_or_else(|_| Err(io::Error::other("接收线程异常退出")))?;
+            pair_sent(sent?, received)?
+        }
+        (true, None) => {
+            let received = join_all(handles)?;
+            pair_sent(read_message(&mut control)?, received)?
+        }
+        (false, _) => {
+            let sent = join_all(handles)?;
+            send_message(&mut control, &Message::Sent { streams: sent.clone() })?;
+            match read_message(&mut control)? {
+                Message::Received { streams } => pair(sent, streams),
+                other => return Err(unexpected(other)),
+            }
+        }
+    };
+    Ok(BandwidthReport { spec: spec.clone(), server, streams, samples })
+}
+
+fn pair(sent: Vec<StreamStats>, received: Vec<StreamStats>) -> Vec<StreamResult> {
+    sent.into_iter()
+        .zip(received)
+        .map(|(sent, received)| StreamResult { sent, received })
+        .collect()
+}
+
+/// 反向模式下由服务端的发送统计与本地接收统计组成结果
+fn pair_sent(message: Message, received: Vec<StreamStats>) -> io::Result<Vec<StreamResult>> {
+    match message {
+        Message::Sent { streams } => Ok(pair(streams, received)),
+        other => Err(unexpected(other)),
+    }
+}
+
+/// 在本机回环地址上启动临时服务端并运行测试
+pub fn run_loopback(spec: &TestSpec, on_sample: &mut dyn FnMut(f64)) -> io::Result<BandwidthReport> {
+    let server = server::Server::bind("127.0.0.1:0".parse().unwrap())?;
+    let addr = server.local_addr()?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let stop = stop.clone();
+        thread::spawn(move || server.serve(&stop, &mut |_| {}))
+    };
+    let report = run_client(&addr.to_string(), spec, on_sample);
+    stop.store(true, Ordering::Relaxed);
+    let _ = handle.join();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(protocol: Protocol, reverse: bool) -> TestSpec {
+        TestSpec { protocol, streams: 2, duration_ms: 1000, reverse, bitrate: 20_000_000 }
+    }
+
+    fn run(spec: &TestSpec) -> BandwidthReport {
+        let mut samples = 0;
+        let report = run_loopback(spec, &mut |_| samples += 1).unwrap();
+        assert_eq!(report.samples.len(), samples);
+        assert_eq!(report.streams.len(), spec.streams as usize);
+        report
+    }
+
+    #[test]
+    fn tcp_forward_reports_server_received_bytes() {
+        let report = run(&spec(Protocol::Tcp, false));
+        for s in &report.streams {
+            assert!(s.sent.bytes > 0);
+            // 接收统计来自服务端的 Received 消息
+            assert_eq!(s.received.bytes, s.sent.bytes);
+            assert!(s.received.secs > 0.0);
+        }
+        assert!(report.received_mbps() > 0.0);
+    }
+
+    #[test]
+    fn tcp_reverse_reports_server_sent_bytes() {
+        let report = run(&spec(Protocol::Tcp, true));
+        for s in &report.streams {
+            assert!(s.received.bytes > 0);
+            // 发送统计来自服务端的 Sent 消息
+            assert_eq!(s.sent.bytes, s.received.bytes);
+            assert!(s.sent.secs > 0.0);
+        }
+    }
+
+    #[test]
+    fn udp_forward_counts_packets_on_both_ends() {
+        let report = run(&spec(Protocol::Udp, false));
+        for s in &report.streams {
+            assert!(s.sent.packets > 0);
+            assert_eq!(s.sent.bytes, s.sent.packets * UDP_PACKET as u64);
+            assert!(s.received.packets > 0 && s.received.packets <= s.sent.packets);
+            assert_eq!(s.received.bytes, s.received.packets * UDP_PACKET as u64);
+        }
+        assert!(report.metrics().iter().any(|m| m.key == "udp_loss"));
+    }
+
+    #[test]
+    fn udp_reverse_registers_client_and_receives() {
+        let report = run(&spec(Protocol::Udp, true));
+        for s in &report.streams {
+            assert!(s.sent.packets > 0);
+            assert!(s.received.packets > 0 && s.received.packets <= s.sent.packets);
+        }
+    }
 }
@@ -0,0 +1,254 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use super::{
+    decode_packet, join_all, read_message, send_message, tcp_recv, tcp_send, udp_recv, udp_send, unexpected, Message,
+    Protocol, StreamStats, TestSpec, CONTROL_MARGIN, HELLO_SEQ, RECV_POLL, SETUP_TIMEOUT, UDP_GRACE, UDP_PACKET,
+};
+
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+const MAX_STREAMS: u32 = 128;
+const MAX_DURATION_MS: u64 = 3600 * 1000;
+
+/// 带宽测试服务端，TCP 与 UDP 监听同一端口，同一时间只运行一个测试
+pub struct Server {
+    listener: TcpListener,
+    udp: UdpSocket,
+}
+
+impl Server {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let udp = UdpSocket::bind(listener.local_addr()?)?;
+        Ok(Server { listener, udp })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.udp.local_addr()
+    }
+
+    /// 处理客户端请求直到 `stop` 被置位；`on_event` 接收连接与测试结果日志
+    pub fn serve(&self, stop: &AtomicBool, on_event: &mut dyn FnMut(&str)) -> io::Result<()> {
+        self.listener.set_nonblocking(true)?;
+        while !stop.load(Ordering::Relaxed) {
+            match self.listener.accept() {
+                Ok((stream, peer)) => {
+                    if let Err(e) = stream.set_nonblocking(false) {
+                        on_event(&format!("{} 连接设置失败: {}", peer, e));
+                        continue;
+                    }
+                    if let Err(e) = self.handle(stream, peer, on_event) {
+                        on_event(&format!("{} 测试失败: {}", peer, e));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+                Err(e) if matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::ConnectionAborted) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&self, mut control: TcpStream, peer: SocketAddr, on_event: &mut dyn FnMut(&str)) -> io::Result<()> {
+        control.set_nodelay(true)?;
+        control.set_read_timeout(Some(SETUP_TIMEOUT))?;
+        let spec = match read_message(&mut control)? {
+            Message::Start(spec) => spec,
+            // 不属于当前测试的数据连接
+            Message::Data { .. } => {
+                return send_message(&mut control, &Message::Error { message: "测试会话不存在或已结束".to_string() });
+            }
+            other => return Err(unexpected(other)),
+        };
+        if let Err(message) = validate(&spec) {
+            send_message(&mut control, &Message::Error { message: message.clone() })?;
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        on_event(&format!(
+            "{} 开始 {} 测试 ({}，{} 条流，{} 秒)",
+            peer,
+            spec.protocol.label(),
+            spec.direction(),
+            spec.streams,
+            spec.duration().as_secs()
+        ));
+        let stats = self.run_session(control, &spec)?;
+        let mbps: f64 = stats.iter().map(StreamStats::mbps).sum();
+        on_event(&format!("{} 测试完成: 服务端{} {:.1} Mbps", peer, if spec.reverse { "发送" } else { "接收" }, mbps));
+        Ok(())
+    }
+
+    /// 运行一次测试，返回服务端一侧的统计
+    fn run_session(&self, mut control: TcpStream, spec: &TestSpec) -> io::Result<Vec<StreamStats>> {
+        let session: u64 = rand::random();
+        let duration = spec.duration();
+        let counter = Arc::new(AtomicU64::new(0));
+        control.set_read_timeout(Some(duration + CONTROL_MARGIN))?;
+
+        let stats = match spec.protocol {
+            Protocol::Tcp => {
+                send_message(&mut control, &Message::Accepted { session })?;
+                let handles = self
+                    .accept_streams(session, spec)?
+                    .into_iter()
+                    .map(|data| {
+                        let (counter, reverse) = (counter.clone(), spec.reverse);
+                        thread::spawn(move || if reverse { tcp_send(data, duration, &counter) } else { tcp_recv(data, &counter) })
+                    })
+                    .collect();
+                let stats = join_all(handles)?;
+                if !spec.reverse {
+                    expect_sent(read_message(&mut control)?)?;
+                }
+                stats
+            }
+            Protocol::Udp if spec.reverse => {
+                let sock = Arc::new(self.udp.try_clone()?);
+                send_message(&mut control, &Message::Accepted { session })?;
+                let peer = wait_hello(&sock, session, control.peer_addr()?.ip())?;
+                let handles = (0..spec.streams)
+                    .map(|stream| {
+                        let (sock, counter, bitrate) = (sock.clone(), counter.clone(), spec.stream_bitrate());
+                        thread::spawn(move || udp_send(&sock, Some(peer), session, stream, bitrate, duration, &counter))
+                    })
+                    .collect();
+                join_all(handles)?
+            }
+            Protocol::Udp => {
+                let sock = self.udp.try_clone()?;
+                let stop = Arc::new(AtomicBool::new(false));
+                let receiver = {
+                    let (stop, counter, streams) = (stop.clone(), counter.clone(), spec.streams);
+                    thread::spawn(move || udp_recv(&sock, session, streams, &stop, &counter))
+                };
+                send_message(&mut control, &Message::Accepted { session })?;
+                // 客户端发送结束后再等待在途数据包
+                let sent = read_message(&mut control);
+                thread::sleep(UDP_GRACE);
+                stop.store(true, Ordering::Relaxed);
+                let stats = receiver.join().unwrap_or_else(|_| Err(io::Error::other("接收线程异常退出")))?;
+                expect_sent(sent?)?;
+                stats
+            }
+        };
+
+        // 发送端把发送统计告知接收端；服务端作为接收端时回报接收统计
+        let reply = if spec.reverse {
+            Message::Sent { streams: stats.clone() }
+        } else {
+            Message::Received { streams: stats.clone() }
+        };
+        send_message(&mut control, &reply)?;
+        Ok(stats)
+    }
+
+    /// 接受本会话的全部 TCP 数据连接，按流序号排列
+    fn accept_streams(&self, session: u64, spec: &TestSpec) -> io::Result<Vec<TcpStream>> {
+        let mut streams: Vec<Option<TcpStream>> = (0..spec.streams).map(|_| None).collect();
+        let deadline = Instant::now() + SETUP_TIMEOUT;
+        while streams.iter().any(Option::is_none) {
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "等待数据连接超时"));
+            }
+            let mut data = match self.listener.accept() {
+                Ok((data, _)) => data,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            data.set_nonblocking(false)?;
+            data.set_read_timeout(Some(SETUP_TIMEOUT))?;
+            let slot = match read_message(&mut data) {
+                Ok(Message::Data { session: id, stream }) if id == session => streams.get_mut(stream as usize),
+                _ => None,
+            };
+            match slot {
+                Some(slot @ None) => {
+                    data.set_read_timeout(Some(spec.duration() + CONTROL_MARGIN))?;
+                    data.set_write_timeout(Some(spec.duration() + CONTROL_MARGIN))?;
+                    *slot = Some(data);
+                }
+                // 其他客户端的请求或重复的数据连接
+                _ => {
+                    let _ = send_message(&mut data, &Message::Error { message: "服务端正忙，请稍后重试".to_string() });
+                }
+            }
+        }
+        Ok(streams.into_iter().flatten().collect())
+    }
+}
+
+/// 客户端发送结束后会发来发送统计，服务端只需确认
+fn expect_sent(message: Message) -> io::Result<()> {
+    match message {
+        Message::Sent { .. } => Ok(()),
+        other => Err(unexpected(other)),
+    }
+}
+
+fn validate(spec: &TestSpec) -> Result<(), String> {
+    if spec.streams == 0 || spec.streams > MAX_STREAMS {
+        return Err(format!("流数须在 1-{} 之间", MAX_STREAMS));
+    }
+    if spec.duration_ms == 0 || spec.duration_ms > MAX_DURATION_MS {
+        return Err("测试时长须在 1 秒到 1 小时之间".to_string());
+    }
+    if spec.protocol == Protocol::Udp && spec.bitrate == 0 {
+        return Err("UDP 测试须指定目标码率".to_string());
+    }
+    Ok(())
+}
+
+/// 等待客户端的 UDP 登记包，返回其地址（端口可能经过 NAT 转换）
+///
+/// 只接受来自控制连接同一 IP 的登记包，防止他人猜中会话 ID 把数据流引向第三方。
+fn wait_hello(sock: &UdpSocket, session: u64, client: IpAddr) -> io::Result<SocketAddr> {
+    sock.set_read_timeout(Some(RECV_POLL))?;
+    let mut buf = vec![0u8; UDP_PACKET * 2];
+    let deadline = Instant::now() + SETUP_TIMEOUT;
+    while Instant::now() < deadline {
+        match sock.recv_from(&mut buf) {
+            Ok((n, peer)) => {
+                if let Some((id, _, HELLO_SEQ, _)) = decode_packet(&buf[..n]) {
+                    if id == session && peer.ip().to_canonical() == client.to_canonical() {
+                        return Ok(peer);
+                    }
+                }
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::TimedOut, "未收到客户端的 UDP 登记包，可能被防火墙拦截"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::perf_bandwidth::{encode_packet, UDP_HEADER};
+
+    fn hello(session: u64) -> [u8; UDP_HEADER] {
+        let mut buf = [0u8; UDP_HEADER];
+        encode_packet(&mut buf, session, 0, HELLO_SEQ, 0);
+        buf
+    }
+
+    #[test]
+    fn hello_from_other_ip_is_ignored() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let stranger = UdpSocket::bind("127.0.0.2:0").unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        stranger.send_to(&hello(7), addr).unwrap();
+        client.send_to(&hello(8), addr).unwrap();
+        client.send_to(&hello(7), addr).unwrap();
+
+        let peer = wait_hello(&server, 7, "127.0.0.1".parse().unwrap()).unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+    }
+}
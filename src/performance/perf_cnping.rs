@@ -0,0 +1,430 @@
+pub mod grid;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::{Direction, ResultTable};
+use crate::netprobe::{self, EchoSocket};
+use super::perf_cntraceroute::{self, Carrier, Target};
+
+const ICMP_PAYLOAD: usize = 56;
+/// 界面刷新间隔
+const TICK: Duration = Duration::from_millis(100);
+
+/// 内置目标：各省三网（多为当地运营商的 DNS 服务器，长期稳定可 ping）
+const DEFAULT_TARGETS: &[(&str, Carrier, &str)] = &[
+    ("北京", Carrier::Telecom, "219.141.136.10"),
+    ("北京", Carrier::Unicom, "202.106.196.115"),
+    ("北京", Carrier::Mobile, "221.179.155.161"),
+    ("上海", Carrier::Telecom, "202.96.209.133"),
+    ("上海", Carrier::Unicom, "210.22.97.1"),
+    ("上海", Carrier::Mobile, "211.136.112.200"),
+    ("广东", Carrier::Telecom, "202.96.128.86"),
+    ("广东", Carrier::Unicom, "210.21.196.6"),
+    ("广东", Carrier::Mobile, "211.136.192.6"),
+    ("江苏", Carrier::Telecom, "218.2.2.2"),
+    ("江苏", Carrier::Unicom, "221.6.4.66"),
+    ("江苏", Carrier::Mobile, "221.131.143.69"),
+    ("浙江", Carrier::Telecom, "202.101.172.35"),
+    ("浙江", Carrier::Unicom, "221.12.1.227"),
+    ("浙江", Carrier::Mobile, "211.140.13.188"),
+    ("四川", Carrier::Telecom, "61.139.2.69"),
+    ("四川", Carrier::Unicom, "119.6.6.6"),
+    ("四川", Carrier::Mobile, "223.87.238.22"),
+];
+
+/// 内置目标中属于 `carriers` 的部分
+pub fn default_targets(carriers: &[Carrier]) -> Vec<Target> {
+    DEFAULT_TARGETS
+        .iter()
+        .filter(|(_, carrier, _)| carriers.contains(carrier))
+        .map(|&(city, carrier, host)| Target { city: city.to_string(), carrier, host: host.to_string() })
+        .collect()
+}
+
+/// 探测方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingMethod {
+    /// 优先 ICMP，无法创建 ICMP 套接字时退回 TCP 连接
+    Auto,
+    Icmp,
+    Tcp,
+}
+
+impl PingMethod {
+    pub const KEYS: &'static [&'static str] = &["auto", "icmp", "tcp"];
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "auto" => Some(PingMethod::Auto),
+            "icmp" => Some(PingMethod::Icmp),
+            "tcp" => Some(PingMethod::Tcp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PingJob {
+    pub method: PingMethod,
+    /// 每个目标的探测次数
+    pub count: u32,
+    pub interval: Duration,
+    /// 单次探测的超时
+    pub timeout: Duration,
+    /// TCP 探测的目标端口
+    pub port: u16,
+}
+
+impl Default for PingJob {
+    fn default() -> Self {
+        PingJob {
+            method: PingMethod::Auto,
+            count: 10,
+            interval: Duration::from_millis(200),
+            timeout: Duration::from_secs(1),
+            port: 80,
+        }
+    }
+}
+
+/// 单个目标的延迟采样
+#[derive(Debug, Clone, Default)]
+pub struct PingStats {
+    /// 按发送顺序排列，`None` 表示尚未回应或已丢失
+    pub samples: Vec<Option<f64>>,
+    /// 超时未回应的探测数
+    pub lost: usize,
+}
+
+impl PingStats {
+    fn received(&self) -> impl Iterator<Item = f64> + '_ {
+        self.samples.iter().flatten().copied()
+    }
+
+    /// 已有结果（回应或超时）的探测数
+    pub fn completed(&self) -> usize {
+        self.received().count() + self.lost
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.received().reduce(f64::min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.received().reduce(f64::max)
+    }
+
+    pub fn avg(&self) -> Option<f64> {
+        let n = self.received().count();
+        (n > 0).then(|| self.received().sum::<f64>() / n as f64)
+    }
+
+    /// 相邻两次延迟差值的平均
+    pub fn jitter(&self) -> Option<f64> {
+        let rtts: Vec<f64> = self.received().collect();
+        (rtts.len() > 1).then(|| rtts.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / (rtts.len() - 1) as f64)
+    }
+
+    /// 丢包率 (%)
+    pub fn loss(&self) -> Option<f64> {
+        let completed = self.completed();
+        (completed > 0).then(|| self.lost as f64 * 100.0 / completed as f64)
+    }
+}
+
+/// 按延迟与丢包划分的质量等级，用于着色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    Good,
+    Fair,
+    Poor,
+    /// 全部丢失或测试失败
+    Down,
+}
+
+impl Grade {
+    pub fn of(stats: &PingStats) -> Option<Grade> {
+        let loss = stats.loss()?;
+        let Some(avg) = stats.avg() else { return Some(Grade::Down) };
+        let by_latency = if avg < 80.0 { Grade::Good } else if avg < 180.0 { Grade::Fair } else { Grade::Poor };
+        let by_loss = if loss == 0.0 { Grade::Good } else if loss < 10.0 { Grade::Fair } else { Grade::Poor };
+        Some(by_latency.max(by_loss))
+    }
+}
+
+/// 单个目标的测试状态与结果
+#[derive(Debug, Clone)]
+pub struct TargetPing {
+    pub target: Target,
+    pub addr: Option<IpAddr>,
+    /// 实际使用的方式，`Auto` 表示尚未开始
+    pub method: PingMethod,
+    pub stats: PingStats,
+    pub error: Option<String>,
+    pub done: bool,
+}
+
+impl TargetPing {
+    fn new(target: Target) -> Self {
+        TargetPing { target, addr: None, method: PingMethod::Auto, stats: PingStats::default(), error: None, done: false }
+    }
+
+    pub fn method_label(&self, port: u16) -> String {
+        match self.method {
+            PingMethod::Auto => "-".to_string(),
+            PingMethod::Icmp => "ICMP".to_string(),
+            PingMethod::Tcp => format!("TCP:{}", port),
+        }
+    }
+
+    pub fn grade(&self) -> Option<Grade> {
+        if self.error.is_some() {
+            return Some(Grade::Down);
+        }
+        Grade::of(&self.stats)
+    }
+}
+
+fn ping_icmp(
+    sock: &EchoSocket,
+    addr: IpAddr,
+    job: &PingJob,
+    stop: &AtomicBool,
+    stats: &mut PingStats,
+    on_change: &mut dyn FnMut(&PingStats),
+) -> io::Result<()> {
+    let count = job.count as usize;
+    let start = Instant::now();
+    let mut sent_at: Vec<Instant> = Vec::with_capacity(count);
+    // 下一个待判定超时的探测
+    let mut expired = 0;
+    let mut buf = [0u8; 1500];
+    while expired < count && !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        let next_send = start + job.interval * sent_at.len() as u32;
+        if sent_at.len() < count && now >= next_send {
+            sock.send(addr, sent_at.len() as u16, ICMP_PAYLOAD)?;
+            sent_at.push(now);
+            stats.samples.push(None);
+        }
+        let mut changed = false;
+        while expired < sent_at.len() && now >= sent_at[expired] + job.timeout {
+            if stats.samples[expired].is_none() {
+                stats.lost += 1;
+                changed = true;
+            }
+            expired += 1;
+        }
+
+        let mut wake = now + TICK;
+        if let Some(sent) = sent_at.get(expired) {
+            wake = wake.min(*sent + job.timeout);
+        }
+        if sent_at.len() < count {
+            wake = wake.min(start + job.interval * sent_at.len() as u32);
+        }
+        netprobe::poll(&mut [sock.pollfd()], wake.saturating_duration_since(Instant::now()))?;
+        while let Some((from, seq)) = sock.recv(&mut buf)? {
+            let seq = seq as usize;
+            if from == addr && seq >= expired && seq < sent_at.len() && stats.samples[seq].is_none() {
+                stats.samples[seq] = Some(sent_at[seq].elapsed().as_secs_f64() * 1000.0);
+                changed = true;
+            }
+        }
+        if changed {
+            on_change(stats);
+        }
+    }
+    Ok(())
+}
+
+/// TCP 连接耗时；被 RST 拒绝同样视为目标可达
+fn ping_tcp(addr: SocketAddr, job: &PingJob, stop: &AtomicBool, stats: &mut PingStats, on_change: &mut dyn FnMut(&PingStats)) {
+    let start = Instant::now();
+    for i in 0..job.count {
+        thread::sleep((start + job.interval * i).saturating_duration_since(Instant::now()));
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let begin = Instant::now();
+        let rtt = match TcpStream::connect_timeout(&addr, job.timeout) {
+            Ok(_) => Some(begin.elapsed()),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Some(begin.elapsed()),
+            Err(_) => None,
+        };
+        stats.samples.push(rtt.map(|d| d.as_secs_f64() * 1000.0));
+        if rtt.is_none() {
+            stats.lost += 1;
+        }
+        on_change(stats);
+    }
+}
+
+fn ping_target(target: Target, job: &PingJob, stop: &AtomicBool, report: &mut dyn FnMut(&TargetPing)) -> TargetPing {
+    let mut state = TargetPing::new(target);
+    let addr = match perf_cntraceroute::resolve(&state.target.host) {
+        Ok(addr) => addr,
+        Err(e) => {
+            state.error = Some(e);
+            return state;
+        }
+    };
+    state.addr = Some(addr);
+
+    let sock = match job.method {
+        PingMethod::Tcp => None,
+        _ => match EchoSocket::open(addr.is_ipv6()) {
+            Ok(sock) => Some(sock),
+            Err(e) if job.method == PingMethod::Auto && e.kind() == io::ErrorKind::PermissionDenied => None,
+            Err(e) => {
+                state.error = Some(e.to_string());
+                return state;
+            }
+        },
+    };
+    state.method = if sock.is_some() { PingMethod::Icmp } else { PingMethod::Tcp };
+    report(&state);
+
+    let mut stats = PingStats::default();
+    let mut on_change = |stats: &PingStats| {
+        state.stats = stats.clone();
+        report(&state);
+    };
+    match &sock {
+        Some(sock) => {
+            if let Err(e) = ping_icmp(sock, addr, job, stop, &mut stats, &mut on_change) {
+                state.error = Some(e.to_string());
+            }
+        }
+        None => ping_tcp(SocketAddr::new(addr, job.port), job, stop, &mut stats, &mut on_change),
+    }
+    state.stats = stats;
+    state
+}
+
+/// 并发测试全部目标；`on_tick` 定期收到当前全部目标的状态，返回值按目标顺序排列。
+/// `stop` 置位后尽快结束，尚未判定的探测不计入结果
+pub fn run_pings(targets: &[Target], job: &PingJob, stop: &AtomicBool, on_tick: &mut dyn FnMut(&[TargetPing])) -> Vec<TargetPing> {
+    let mut states: Vec<TargetPing> = targets.iter().cloned().map(TargetPing::new).collect();
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for (index, target) in targets.iter().enumerate() {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let mut state = ping_target(target.clone(), job, stop, &mut |state| {
+                    let _ = tx.send((index, state.clone()));
+                });
+                state.done = true;
+                let _ = tx.send((index, state));
+            });
+        }
+        drop(tx);
+        on_tick(&states);
+        loop {
+            match rx.recv_timeout(TICK) {
+                Ok((index, state)) => {
+                    states[index] = state;
+                    // 合并已到达的更新后再刷新
+                    for (index, state) in rx.try_iter() {
+                        states[index] = state;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            on_tick(&states);
+        }
+    });
+    states
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |v| format!("{:.1}", v))
+}
+
+/// 出现顺序排列的城市与运营商，作为矩阵的行与列
+pub fn matrix_axes(results: &[TargetPing]) -> (Vec<String>, Vec<Carrier>) {
+    let mut cities: Vec<String> = Vec::new();
+    for result in results {
+        if !cities.contains(&result.target.city) {
+            cities.push(result.target.city.clone());
+        }
+    }
+    let carriers = Carrier::ALL.into_iter().filter(|c| results.iter().any(|r| r.target.carrier == *c)).collect();
+    (cities, carriers)
+}
+
+/// 矩阵单元格的简短描述：平均延迟与丢包率
+pub fn cell_text(result: &TargetPing) -> String {
+    if result.error.is_some() {
+        return "失败".to_string();
+    }
+    match (result.stats.avg(), result.stats.loss()) {
+        (Some(avg), Some(loss)) => format!("{:.1} ms {:.0}%", avg, loss),
+        (None, Some(_)) => "超时".to_string(),
+        _ if result.done => "-".to_string(),
+        _ => "测试中".to_string(),
+    }
+}
+
+pub fn format_results(results: &[TargetPing], job: &PingJob) -> String {
+    let mut out = format!(
+        "\n{:<10} | {:<15} | {:<7} | {:>7} | {:>7} | {:>7} | {:>7} | {:>6}\n",
+        "目标", "地址", "方式", "最低", "平均", "最高", "抖动", "丢包"
+    );
+    for result in results {
+        let addr = result.addr.map_or_else(|| result.target.host.clone(), |a| a.to_string());
+        if let Some(e) = &result.error {
+            out.push_str(&format!("{:<10} | {:<15} | ❌ {}\n", result.target.name(), addr, e));
+            continue;
+        }
+        let stats = &result.stats;
+        out.push_str(&format!(
+            "{:<10} | {:<15} | {:<7} | {:>7} | {:>7} | {:>7} | {:>7} | {:>6}\n",
+            result.target.name(),
+            addr,
+            result.method_label(job.port),
+            ms(stats.min()),
+            ms(stats.avg()),
+            ms(stats.max()),
+            ms(stats.jitter()),
+            stats.loss().map_or("-".to_string(), |l| format!("{:.0}%", l))
+        ));
+    }
+
+    let (cities, carriers) = matrix_axes(results);
+    out.push_str(&format!("\n{:<8}", ""));
+    for carrier in &carriers {
+        out.push_str(&format!(" | {:<14}", carrier.label()));
+    }
+    out.push('\n');
+    for city in &cities {
+        out.push_str(&format!("{:<8}", city));
+        for carrier in &carriers {
+            let cell = results
+                .iter()
+                .find(|r| &r.target.city == city && r.target.carrier == *carrier)
+                .map_or("-".to_string(), cell_text);
+            out.push_str(&format!(" | {:<14}", cell));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 延迟表（最低/平均/最高/抖动）与丢包表
+pub fn result_tables(results: &[TargetPing]) -> Vec<ResultTable> {
+    let columns = ["最低", "平均", "最高", "抖动"].iter().map(|c| c.to_string()).collect();
+    let mut latency = ResultTable::new("三网延迟", "ms", columns, Direction::Lower);
+    let mut loss = ResultTable::new("三网丢包", "%", vec!["丢包".to_string()], Direction::Lower);
+    for result in results.iter().filter(|r| r.error.is_none()) {
+        let stats = &result.stats;
+        latency.push_row(result.target.name(), vec![stats.min(), stats.avg(), stats.max(), stats.jitter()]);
+        loss.push_row(result.target.name(), vec![stats.loss()]);
+    }
+    vec![latency, loss]
+}
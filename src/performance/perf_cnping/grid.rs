@@ -0,0 +1,165 @@
+use std::io::{self, stdout, Stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use super::{cell_text, matrix_axes, run_pings, Grade, PingJob, TargetPing};
+use crate::performance::perf_cntraceroute::Target;
+
+type Term = Terminal<CrosstermBackend<Stdout>>;
+
+/// 离开作用域时恢复终端
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+    }
+}
+
+fn grade_color(grade: Option<Grade>) -> Color {
+    match grade {
+        Some(Grade::Good) => Color::Green,
+        Some(Grade::Fair) => Color::Yellow,
+        Some(Grade::Poor) => Color::LightRed,
+        Some(Grade::Down) => Color::Red,
+        None => Color::Gray,
+    }
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |v| format!("{:.1}", v))
+}
+
+fn draw(f: &mut Frame, results: &[TargetPing], job: &PingJob, footer: &str) {
+    let (cities, carriers) = matrix_axes(results);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(cities.len() as u16 + 3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    // 城市 × 运营商矩阵
+    let header = Row::new(
+        std::iter::once(Cell::from(""))
+            .chain(carriers.iter().map(|c| Cell::from(c.label())))
+            .collect::<Vec<_>>(),
+    )
+    .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let rows = cities.iter().map(|city| {
+        let cells = carriers.iter().map(|carrier| {
+            match results.iter().find(|r| &r.target.city == city && r.target.carrier == *carrier) {
+                Some(result) => Cell::from(cell_text(result)).style(Style::default().fg(grade_color(result.grade()))),
+                None => Cell::from("-"),
+            }
+        });
+        Row::new(std::iter::once(Cell::from(city.as_str())).chain(cells).collect::<Vec<_>>())
+    });
+    let widths: Vec<Constraint> = std::iter::once(Constraint::Length(8))
+        .chain(carriers.iter().map(|_| Constraint::Length(16)))
+        .collect();
+    let matrix = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("📶 三网延迟矩阵 (平均延迟 丢包率)"));
+    f.render_widget(matrix, chunks[0]);
+
+    // 各目标明细
+    let header = Row::new(["目标", "地址", "方式", "进度", "最低", "平均", "最高", "抖动", "丢包"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let rows = results.iter().map(|result| {
+        let stats = &result.stats;
+        let addr = result.addr.map_or_else(|| result.target.host.clone(), |a| a.to_string());
+        let style = Style::default().fg(grade_color(result.grade()));
+        if let Some(e) = &result.error {
+            return Row::new(vec![Cell::from(result.target.name()), Cell::from(addr), Cell::from(e.clone())]).style(style);
+        }
+        Row::new(vec![
+            Cell::from(result.target.name()),
+            Cell::from(addr),
+            Cell::from(result.method_label(job.port)),
+            Cell::from(format!("{}/{}", stats.completed(), job.count)),
+            Cell::from(ms(stats.min())),
+            Cell::from(ms(stats.avg())),
+            Cell::from(ms(stats.max())),
+            Cell::from(ms(stats.jitter())),
+            Cell::from(stats.loss().map_or("-".to_string(), |l| format!("{:.0}%", l))),
+        ])
+        .style(style)
+    });
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(16),
+        Constraint::Length(8),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(6),
+    ];
+    let details = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("明细 (ms)"));
+    f.render_widget(details, chunks[1]);
+
+    f.render_widget(Paragraph::new(footer).style(Style::default().fg(Color::Gray)), chunks[2]);
+}
+
+/// 读取一个按键事件，超时返回 `None`
+fn read_key(timeout: Duration) -> io::Result<Option<event::KeyEvent>> {
+    if !event::poll(timeout)? {
+        return Ok(None);
+    }
+    match event::read()? {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Ok(Some(key)),
+        _ => Ok(None),
+    }
+}
+
+fn is_quit(key: &event::KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+/// 在终端界面中实时显示测试进度，测试结束后按任意键返回
+pub fn run(targets: &[Target], job: &PingJob) -> io::Result<Vec<TargetPing>> {
+    enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal: Term = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let stop = AtomicBool::new(false);
+    let mut error = None;
+    let results = run_pings(targets, job, &stop, &mut |states| {
+        if error.is_some() {
+            return;
+        }
+        let footer = if stop.load(Ordering::Relaxed) { "正在停止..." } else { "测试中，按 q 停止" };
+        let drawn = terminal.draw(|f| draw(f, states, job, footer)).map(|_| ());
+        let quit = drawn.and_then(|_| read_key(Duration::ZERO)).map(|key| key.is_some_and(|k| is_quit(&k)));
+        match quit {
+            Ok(true) => stop.store(true, Ordering::Relaxed),
+            Ok(false) => {}
+            Err(e) => {
+                stop.store(true, Ordering::Relaxed);
+                error = Some(e);
+            }
+        }
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    let footer = if stop.load(Ordering::Relaxed) { "测试已停止，按任意键返回" } else { "测试完成，按任意键返回" };
+    terminal.draw(|f| draw(f, &results, job, footer))?;
+    while read_key(Duration::from_millis(500))?.is_none() {}
+    Ok(results)
+}
@@ -1,3 +1,464 @@
-pub fn run_cntraceroute_test() {
-    println!("中国一线城市各大网络商来回程线路测试功能待实现");
+pub mod routes;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::{Direction, ResultTable};
+use crate::netprobe::{self, ProbeId, TcpProbe};
+use routes::{Network, RouteType};
+
+/// UDP 探测的起始目的端口，与 traceroute 一致
+const UDP_BASE_PORT: u16 = 33434;
+/// 相邻两个探测包的发送间隔，避免触发路由器的 ICMP 限速
+const SEND_INTERVAL: Duration = Duration::from_millis(10);
+const POLL_STEP: Duration = Duration::from_millis(10);
+const ICMP_PAYLOAD: usize = 32;
+
+/// 运营商
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Carrier {
+    Telecom,
+    Unicom,
+    Mobile,
+}
+
+impl Carrier {
+    pub const ALL: [Carrier; 3] = [Carrier::Telecom, Carrier::Unicom, Carrier::Mobile];
+    pub const KEYS: &'static [&'static str] = &["telecom", "unicom", "mobile"];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Carrier::Telecom => "telecom",
+            Carrier::Unicom => "unicom",
+            Carrier::Mobile => "mobile",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Carrier::Telecom => "电信",
+            Carrier::Unicom => "联通",
+            Carrier::Mobile => "移动",
+        }
+    }
+
+    /// 接受英文键名或中文名称
+    pub fn parse(raw: &str) -> Option<Self> {
+        Carrier::ALL.into_iter().find(|c| c.key() == raw || c.label() == raw)
+    }
+}
+
+/// 内置目标：北上广三网
+const DEFAULT_TARGETS: &[(&str, Carrier, &str)] = &[
+    ("北京", Carrier::Telecom, "219.141.136.12"),
+    ("北京", Carrier::Unicom, "202.106.50.1"),
+    ("北京", Carrier::Mobile, "221.179.155.161"),
+    ("上海", Carrier::Telecom, "202.96.209.133"),
+    ("上海", Carrier::Unicom, "210.22.97.1"),
+    ("上海", Carrier::Mobile, "211.136.112.200"),
+    ("广州", Carrier::Telecom, "58.60.188.222"),
+    ("广州", Carrier::Unicom, "210.21.196.6"),
+    ("广州", Carrier::Mobile, "120.196.165.24"),
+];
+
+/// 测试目标
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub city: String,
+    pub carrier: Carrier,
+    pub host: String,
+}
+
+impl Target {
+    pub fn name(&self) -> String {
+        format!("{}{}", self.city, self.carrier.label())
+    }
+}
+
+/// 内置目标中属于 `carriers` 的部分
+pub fn default_targets(carriers: &[Carrier]) -> Vec<Target> {
+    DEFAULT_TARGETS
+        .iter()
+        .filter(|(_, carrier, _)| carriers.contains(carrier))
+        .map(|&(city, carrier, host)| Target { city: city.to_string(), carrier, host: host.to_string() })
+        .collect()
+}
+
+/// 解析逗号分隔的 `城市/运营商/地址` 列表，运营商为 电信/联通/移动 或 telecom/unicom/mobile
+pub fn parse_targets(raw: &str) -> Result<Vec<Target>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let parts: Vec<&str> = entry.split('/').map(str::trim).collect();
+            let [city, carrier, host] = parts[..] else {
+                return Err(format!("'{}' 格式应为 城市/运营商/地址", entry));
+            };
+            let carrier = Carrier::parse(carrier).ok_or_else(|| format!("'{}' 不是有效的运营商", carrier))?;
+            Ok(Target { city: city.to_string(), carrier, host: host.to_string() })
+        })
+        .collect()
+}
+
+/// 探测协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeProtocol {
+    Icmp,
+    Udp,
+    Tcp,
+}
+
+impl ProbeProtocol {
+    pub const KEYS: &'static [&'static str] = &["icmp", "udp", "tcp"];
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "icmp" => Some(ProbeProtocol::Icmp),
+            "udp" => Some(ProbeProtocol::Udp),
+            "tcp" => Some(ProbeProtocol::Tcp),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProbeProtocol::Icmp => "ICMP",
+            ProbeProtocol::Udp => "UDP",
+            ProbeProtocol::Tcp => "TCP SYN",
+        }
+    }
+}
+
+/// 路由追踪配置
+#[derive(Debug, Clone)]
+pub struct TraceJob {
+    pub protocol: ProbeProtocol,
+    pub max_hops: u8,
+    /// 每跳探测次数
+    pub probes: u8,
+    /// TCP 探测的目的端口
+    pub port: u16,
+    /// 单个探测的等待时间
+    pub timeout: Duration,
+}
+
+impl Default for TraceJob {
+    fn default() -> Self {
+        TraceJob { protocol: ProbeProtocol::Icmp, max_hops: 30, probes: 3, port: 80, timeout: Duration::from_secs(2) }
+    }
+}
+
+/// 一跳的探测结果
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub ttl: u8,
+    /// 第一个回应该跳探测的地址
+    pub addr: Option<IpAddr>,
+    /// 每次探测的往返时间 (ms)，超时为 `None`
+    pub rtts: Vec<Option<f64>>,
+}
+
+impl Hop {
+    pub fn avg_rtt(&self) -> Option<f64> {
+        let rtts: Vec<f64> = self.rtts.iter().flatten().copied().collect();
+        (!rtts.is_empty()).then(|| rtts.iter().sum::<f64>() / rtts.len() as f64)
+    }
+
+    pub fn network(&self) -> Option<Network> {
+        self.addr.and_then(routes::lookup)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceResult {
+    pub hops: Vec<Hop>,
+    /// 目标主机本身作出了回应
+    pub reached: bool,
+}
+
+impl TraceResult {
+    pub fn route(&self) -> RouteType {
+        let networks: Vec<Network> = self.hops.iter().filter_map(Hop::network).collect();
+        routes::classify(&networks)
+    }
+
+    /// 到达目标时最后一跳的平均延迟
+    pub fn latency(&self) -> Option<f64> {
+        if self.reached { self.hops.last().and_then(Hop::avg_rtt) } else { None }
+    }
+}
+
+/// 已发出、等待回应的探测
+struct Probe {
+    ttl: u8,
+    round: usize,
+    id: ProbeId,
+    sent: Instant,
+    tcp: Option<TcpProbe>,
+}
+
+/// 各协议的探测发送方式
+enum Sender {
+    Icmp { echo_id: u16 },
+    Udp { sock: OwnedFd, src: u16 },
+    Tcp { port: u16 },
+}
+
+impl Sender {
+    fn open(job: &TraceJob, v6: bool) -> io::Result<Self> {
+        Ok(match job.protocol {
+            ProbeProtocol::Icmp => Sender::Icmp { echo_id: netprobe::next_echo_id() },
+            ProbeProtocol::Udp => {
+                let (sock, src) = netprobe::udp_socket(v6)?;
+                Sender::Udp { sock, src }
+            }
+            ProbeProtocol::Tcp => Sender::Tcp { port: job.port },
+        })
+    }
+}
+
+/// 打开接收 ICMP 超时报文的原始套接字；三种探测协议都依赖它识别中间跳，无权限时说明原因
+fn open_icmp(v6: bool) -> io::Result<OwnedFd> {
+    netprobe::icmp_socket(v6).map_err(|e| {
+        if e.kind() == io::ErrorKind::PermissionDenied {
+            io::Error::new(
+                e.kind(),
+                "ICMP、UDP、TCP 三种探测模式都需要原始套接字接收各跳的 ICMP 超时报文，\
+                 请以 root 运行或执行 setcap cap_net_raw+ep 授予 CAP_NET_RAW",
+            )
+        } else {
+            e
+        }
+    })
+}
+
+/// 检查当前进程能否进行路由追踪
+pub fn check_privilege() -> Result<(), String> {
+    open_icmp(false).map(drop).map_err(|e| e.to_string())
+}
+
+/// 对单个地址做路由追踪：按轮次依次发出 TTL 1..=max_hops 的探测，确定终点后不再探测更远的跳
+pub fn trace(target: IpAddr, job: &TraceJob) -> io::Result<TraceResult> {
+    let v6 = target.is_ipv6();
+    let max_hops = job.max_hops.max(1) as usize;
+    let rounds = job.probes.max(1) as usize;
+    let icmp = open_icmp(v6)?;
+    let sender = Sender::open(job, v6)?;
+
+    let mut replies: Vec<Vec<Option<(IpAddr, f64)>>> = vec![vec![None; rounds]; max_hops];
+    let mut pending: Vec<Probe> = Vec::new();
+    let mut final_ttl: Option<u8> = None;
+    let mut reached = false;
+    let mut next = 0usize;
+    let mut last_send: Option<Instant> = None;
+    let mut buf = vec![0u8; 2048];
+
+    loop {
+        if next < max_hops * rounds && last_send.is_none_or(|t| t.elapsed() >= SEND_INTERVAL) {
+            let (round, ttl) = (next / max_hops, (next % max_hops + 1) as u8);
+            let seq = next as u16;
+            next += 1;
+            if final_ttl.is_none_or(|f| ttl <= f) {
+                let (id, tcp) = match &sender {
+                    &Sender::Icmp { echo_id } => {
+                        netprobe::set_ttl(&icmp, v6, ttl)?;
+                        let packet = netprobe::echo_request(v6, echo_id, seq, ICMP_PAYLOAD);
+                        netprobe::send_to(&icmp, &packet, SocketAddr::new(target, 0))?;
+                        (ProbeId::Echo { id: echo_id, seq }, None)
+                    }
+                    Sender::Udp { sock, src } => {
+                        let dst = UDP_BASE_PORT + seq;
+                        netprobe::set_ttl(sock, v6, ttl)?;
+                        netprobe::send_to(sock, &[0u8; ICMP_PAYLOAD], SocketAddr::new(target, dst))?;
+                        (ProbeId::Port { proto: libc::IPPROTO_UDP as u8, src: *src, dst }, None)
+                    }
+                    &Sender::Tcp { port } => {
+                        let tcp = TcpProbe::connect(SocketAddr::new(target, port), ttl)?;
+                        (ProbeId::Port { proto: libc::IPPROTO_TCP as u8, src: tcp.local_port, dst: port }, Some(tcp))
+                    }
+                };
+                pending.push(Probe { ttl, round, id, sent: Instant::now(), tcp });
+                last_send = Some(Instant::now());
+            }
+        }
+
+        let tcp_probes: Vec<usize> = (0..pending.len()).filter(|&i| pending[i].tcp.is_some()).collect();
+        let mut fds = vec![netprobe::pollfd_in(icmp.as_raw_fd())];
+        fds.extend(tcp_probes.iter().filter_map(|&i| pending[i].tcp.as_ref().map(TcpProbe::pollfd)));
+        netprobe::poll(&mut fds, POLL_STEP)?;
+
+        let mut record = |probe: &Probe, from: IpAddr, is_final: bool, replies: &mut Vec<Vec<Option<(IpAddr, f64)>>>| {
+            let rtt = probe.sent.elapsed().as_secs_f64() * 1000.0;
+            replies[probe.ttl as usize - 1][probe.round].get_or_insert((from, rtt));
+            if is_final {
+                final_ttl = Some(final_ttl.map_or(probe.ttl, |f| f.min(probe.ttl)));
+                reached |= from == target;
+            }
+        };
+        // TCP 连接建立或被 RST 拒绝说明已到达目标；其他错误交由 ICMP 判断
+        let mut answered = Vec::new();
+        for (fd, &i) in fds[1..].iter().zip(&tcp_probes) {
+            if fd.revents != 0 {
+                if pending[i].tcp.as_ref().is_some_and(TcpProbe::answered) {
+                    answered.push(i);
+                } else {
+                    pending[i].tcp = None;
+                }
+            }
+        }
+        for &i in answered.iter().rev() {
+            let probe = pending.remove(i);
+            record(&probe, target, true, &mut replies);
+        }
+        while let Some(message) = netprobe::recv_icmp(&icmp, v6, &mut buf)? {
+            if let Some(i) = pending.iter().position(|p| p.id == message.probe) {
+                let probe = pending.remove(i);
+                record(&probe, message.from, message.is_final(), &mut replies);
+            }
+        }
+
+        pending.retain(|p| p.sent.elapsed() < job.timeout);
+        if next >= max_hops * rounds && pending.is_empty() {
+            break;
+        }
+    }
+
+    // 未到达终点时去掉末尾连续无回应的跳
+    let last = final_ttl.map(|t| t as usize).unwrap_or_else(|| {
+        replies.iter().rposition(|r| r.iter().any(Option::is_some)).map_or(0, |i| i + 1)
+    });
+    let hops = replies
+        .into_iter()
+        .take(last)
+        .enumerate()
+        .map(|(i, row)| Hop {
+            ttl: i as u8 + 1,
+            addr: row.iter().flatten().map(|(addr, _)| *addr).next(),
+            rtts: row.iter().map(|r| r.map(|(_, rtt)| rtt)).collect(),
+        })
+        .collect();
+    Ok(TraceResult { hops, reached })
+}
+
+/// 单个目标的追踪结果
+#[derive(Debug, Clone)]
+pub struct TargetTrace {
+    pub target: Target,
+    pub addr: Option<IpAddr>,
+    pub result: Result<TraceResult, String>,
+}
+
+/// 解析目标地址，取第一个结果
+pub fn resolve(host: &str) -> Result<IpAddr, String> {
+    if let Ok(addr) = host.parse::<IpAddr>() {
+        return Ok(addr);
+    }
+    (host, 0)
+        .to_socket_addrs()
+        .map_err(|e| format!("解析 {} 失败: {}", host, e))?
+        .next()
+        .map(|a| a.ip())
+        .ok_or_else(|| format!("解析 {} 失败: 无地址", host))
+}
+
+/// 并发追踪全部目标，`on_done` 按完成顺序接收结果，返回值按目标顺序排列
+pub fn run_traces(targets: &[Target], job: &TraceJob, on_done: &mut dyn FnMut(&TargetTrace)) -> Vec<TargetTrace> {
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for (index, target) in targets.iter().enumerate() {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let addr = resolve(&target.host);
+                let result = addr.clone().and_then(|addr| trace(addr, job).map_err(|e| e.to_string()));
+                let _ = tx.send((index, TargetTrace { target: target.clone(), addr: addr.ok(), result }));
+            });
+        }
+        drop(tx);
+        let mut traces: Vec<(usize, TargetTrace)> = rx
+            .iter()
+            .inspect(|(_, trace)| on_done(trace))
+            .collect();
+        traces.sort_by_key(|(index, _)| *index);
+        traces.into_iter().map(|(_, trace)| trace).collect()
+    })
+}
+
+pub fn format_trace(trace: &TargetTrace) -> String {
+    let addr = trace.addr.map(|a| a.to_string()).unwrap_or_else(|| trace.target.host.clone());
+    let result = match &trace.result {
+        Ok(result) => result,
+        Err(e) => return format!("\n{} ({}) ❌ {}\n", trace.target.name(), addr, e),
+    };
+    let mut out = format!("\n{} ({})  线路: {}\n", trace.target.name(), addr, result.route().label());
+    for hop in &result.hops {
+        let Some(hop_addr) = hop.addr else {
+            out.push_str(&format!("{:>3}  *\n", hop.ttl));
+            continue;
+        };
+        let rtts: Vec<String> = hop
+            .rtts
+            .iter()
+            .map(|r| r.map_or("*".to_string(), |ms| format!("{:.2} ms", ms)))
+            .collect();
+        out.push_str(&format!(
+            "{:>3}  {:<39} {:<16} {}\n",
+            hop.ttl,
+            hop_addr,
+            hop.network().map_or("", |n| n.label()),
+            rtts.join("  ")
+        ));
+    }
+    if !result.reached {
+        out.push_str("     未到达目标（目标可能屏蔽了探测包）\n");
+    }
+    out
+}
+
+pub fn format_summary(traces: &[TargetTrace]) -> String {
+    let mut out = format!("\n{:<10} | {:<8} | {:>4} | {:>10}\n", "目标", "线路", "跳数", "延迟");
+    for trace in traces {
+        let (route, hops, latency) = match &trace.result {
+            Ok(r) => (
+                r.route().label().to_string(),
+                r.hops.len().to_string(),
+                r.latency().map_or("-".to_string(), |ms| format!("{:.1} ms", ms)),
+            ),
+            Err(_) => ("失败".to_string(), "-".to_string(), "-".to_string()),
+        };
+        out.push_str(&format!("{:<10} | {:<8} | {:>4} | {:>10}\n", trace.target.name(), route, hops, latency));
+    }
+    out.push('\n');
+    for carrier in Carrier::ALL {
+        let mut routes: Vec<RouteType> = traces
+            .iter()
+            .filter(|t| t.target.carrier == carrier)
+            .filter_map(|t| t.result.as_ref().ok().map(TraceResult::route))
+            .collect();
+        if routes.is_empty() {
+            continue;
+        }
+        routes.sort();
+        let mut counts: Vec<(RouteType, usize)> = Vec::new();
+        for route in routes {
+            match counts.last_mut() {
+                Some((last, n)) if *last == route => *n += 1,
+                _ => counts.push((route, 1)),
+            }
+        }
+        let summary: Vec<String> = counts.iter().map(|(route, n)| format!("{} ×{}", route.label(), n)).collect();
+        out.push_str(&format!("{}回程: {}\n", carrier.label(), summary.join("，")));
+    }
+    out
+}
+
+pub fn result_table(traces: &[TargetTrace]) -> ResultTable {
+    let mut table = ResultTable::new("回程延迟", "ms", vec!["延迟".to_string()], Direction::Lower);
+    for trace in traces {
+        let latency = trace.result.as_ref().ok().and_then(TraceResult::latency);
+        table.push_row(trace.target.name(), vec![latency]);
+    }
+    table
 }
@@ -0,0 +1,145 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+/// 回程路由中可识别的骨干网
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Cn2,
+    Chinanet,
+    Unicom4837,
+    Unicom9929,
+    Cmi,
+    Cmnet,
+    Private,
+}
+
+impl Network {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Network::Cn2 => "AS4809 电信CN2",
+            Network::Chinanet => "AS4134 电信163",
+            Network::Unicom4837 => "AS4837 联通",
+            Network::Unicom9929 => "AS9929 联通A网",
+            Network::Cmi => "AS58453 移动CMI",
+            Network::Cmnet => "AS9808 移动",
+            Network::Private => "内网",
+        }
+    }
+}
+
+/// IPv4 前缀表，仅收录各运营商骨干网的特征网段
+const PREFIXES: &[([u8; 4], u8, Network)] = &[
+    ([59, 43, 0, 0], 16, Network::Cn2),
+    ([202, 97, 0, 0], 16, Network::Chinanet),
+    ([219, 158, 0, 0], 16, Network::Unicom4837),
+    ([218, 105, 0, 0], 16, Network::Unicom9929),
+    ([210, 51, 0, 0], 16, Network::Unicom9929),
+    ([223, 118, 0, 0], 15, Network::Cmi),
+    ([223, 120, 0, 0], 15, Network::Cmi),
+    ([221, 176, 0, 0], 16, Network::Cmnet),
+    ([221, 183, 0, 0], 16, Network::Cmnet),
+    ([10, 0, 0, 0], 8, Network::Private),
+    ([172, 16, 0, 0], 12, Network::Private),
+    ([192, 168, 0, 0], 16, Network::Private),
+    ([100, 64, 0, 0], 10, Network::Private),
+];
+
+pub fn lookup(addr: IpAddr) -> Option<Network> {
+    let IpAddr::V4(addr) = addr else { return None };
+    let addr = u32::from(addr);
+    PREFIXES.iter().find_map(|&(net, len, network)| {
+        let mask = u32::MAX << (32 - len as u32);
+        (addr & mask == u32::from(Ipv4Addr::from(net))).then_some(network)
+    })
+}
+
+/// 线路类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RouteType {
+    Cn2Gia,
+    Cn2Gt,
+    Chinanet163,
+    Unicom9929,
+    Unicom4837,
+    Cmi,
+    Cmnet,
+    Unknown,
+}
+
+impl RouteType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RouteType::Cn2Gia => "CN2 GIA",
+            RouteType::Cn2Gt => "CN2 GT",
+            RouteType::Chinanet163 => "163",
+            RouteType::Unicom9929 => "9929",
+            RouteType::Unicom4837 => "4837",
+            RouteType::Cmi => "CMI",
+            RouteType::Cmnet => "CMNET",
+            RouteType::Unknown => "未知",
+        }
+    }
+}
+
+/// 按路径经过的骨干网判断线路类型
+///
+/// CN2 GT 会先经过 163 骨干 (202.97.x.x) 再进入 CN2，全程只走 59.43.x.x 的为 CN2 GIA。
+pub fn classify(networks: &[Network]) -> RouteType {
+    let has = |n: Network| networks.contains(&n);
+    if has(Network::Cn2) {
+        if has(Network::Chinanet) { RouteType::Cn2Gt } else { RouteType::Cn2Gia }
+    } else if has(Network::Chinanet) {
+        RouteType::Chinanet163
+    } else if has(Network::Unicom9929) {
+        RouteType::Unicom9929
+    } else if has(Network::Unicom4837) {
+        RouteType::Unicom4837
+    } else if has(Network::Cmi) {
+        RouteType::Cmi
+    } else if has(Network::Cmnet) {
+        RouteType::Cmnet
+    } else {
+        RouteType::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup_v4(a: u8, b: u8, c: u8, d: u8) -> Option<Network> {
+        lookup(IpAddr::V4(Ipv4Addr::new(a, b, c, d)))
+    }
+
+    #[test]
+    fn lookup_matches_prefix_lengths() {
+        assert_eq!(lookup_v4(59, 43, 1, 2), Some(Network::Cn2));
+        assert_eq!(lookup_v4(202, 97, 255, 1), Some(Network::Chinanet));
+        // /15 覆盖 223.118 与 223.119，不含 223.117
+        assert_eq!(lookup_v4(223, 119, 0, 1), Some(Network::Cmi));
+        assert_eq!(lookup_v4(223, 117, 0, 1), None);
+        // /12 与 /10 的边界
+        assert_eq!(lookup_v4(172, 31, 255, 255), Some(Network::Private));
+        assert_eq!(lookup_v4(172, 32, 0, 0), None);
+        assert_eq!(lookup_v4(100, 127, 0, 1), Some(Network::Private));
+        assert_eq!(lookup_v4(100, 128, 0, 1), None);
+        assert_eq!(lookup(IpAddr::V6("2001:db8::1".parse().unwrap())), None);
+    }
+
+    #[test]
+    fn classify_distinguishes_cn2_gia_and_gt() {
+        assert_eq!(classify(&[Network::Private, Network::Cn2]), RouteType::Cn2Gia);
+        assert_eq!(classify(&[Network::Chinanet, Network::Cn2]), RouteType::Cn2Gt);
+        assert_eq!(classify(&[Network::Cn2, Network::Chinanet]), RouteType::Cn2Gt);
+        assert_eq!(classify(&[Network::Chinanet]), RouteType::Chinanet163);
+    }
+
+    #[test]
+    fn classify_prefers_premium_networks() {
+        assert_eq!(classify(&[Network::Unicom4837, Network::Unicom9929]), RouteType::Unicom9929);
+        assert_eq!(classify(&[Network::Unicom4837]), RouteType::Unicom4837);
+        assert_eq!(classify(&[Network::Cmnet, Network::Cmi]), RouteType::Cmi);
+        assert_eq!(classify(&[Network::Cmnet]), RouteType::Cmnet);
+        assert_eq!(classify(&[Network::Private]), RouteType::Unknown);
+        assert_eq!(classify(&[]), RouteType::Unknown);
+    }
+}
@@ -0,0 +1,335 @@
+mod kernels;
+pub mod stress;
+
+use std::hint::black_box;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::Rng;
+use sysinfo::{CpuRefreshKind, RefreshKind, System};
+use crate::models::{Direction, Metric, ResultTable};
+
+const MATRIX_N: usize = 64;
+const INTEGER_ITERATIONS: u64 = 1 << 20;
+const HASH_CHUNK: usize = 64 * 1024;
+const AES_CHUNK: usize = 64 * 1024;
+const COMPRESS_CHUNK: usize = 256 * 1024;
+const COMPRESS_TABLE: usize = 1 << 14;
+const MB: f64 = 1024.0 * 1024.0;
+
+/// CPU 测试负载
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    Integer,
+    Float,
+    Hash,
+    Aes,
+    Compress,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 5] = [Workload::Integer, Workload::Float, Workload::Hash, Workload::Aes, Workload::Compress];
+    /// 参数中使用的负载名，与 [`Workload::ALL`] 顺序一致
+    pub const KEYS: &'static [&'static str] = &["integer", "float", "sha256", "aes", "compress"];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Workload::Integer => "integer",
+            Workload::Float => "float",
+            Workload::Hash => "sha256",
+            Workload::Aes => "aes",
+            Workload::Compress => "compress",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Workload> {
+        Workload::ALL.into_iter().find(|w| w.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Workload::Integer => "整数运算",
+            Workload::Float => "浮点矩阵乘",
+            Workload::Hash => "SHA-256",
+            Workload::Aes => "AES-128 (AES-NI)",
+            Workload::Compress => "LZ77 压缩",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Workload::Integer => "Mops/s",
+            Workload::Float => "GFLOPS",
+            Workload::Hash | Workload::Aes | Workload::Compress => "MB/s",
+        }
+    }
+
+    /// 单个工作单元折算成 [`Workload::unit`] 的数量
+    fn unit_amount(&self) -> f64 {
+        match self {
+            Workload::Integer => INTEGER_ITERATIONS as f64 / 1e6,
+            Workload::Float => 2.0 * (MATRIX_N * MATRIX_N * MATRIX_N) as f64 / 1e9,
+            Workload::Hash => HASH_CHUNK as f64 / MB,
+            Workload::Aes => AES_CHUNK as f64 / MB,
+            Workload::Compress => COMPRESS_CHUNK as f64 / MB,
+        }
+    }
+
+    /// 得分的参考吞吐量：得分 = 吞吐量 / 参考值 × 1000。参考值为固定常数，仅用于跨机器横向比较
+    fn reference(&self) -> f64 {
+        match self {
+            Workload::Integer => 250.0,
+            Workload::Float => 2.0,
+            Workload::Hash => 200.0,
+            Workload::Aes => 2000.0,
+            Workload::Compress => 150.0,
+        }
+    }
+
+    fn available(&self) -> bool {
+        *self != Workload::Aes || kernels::has_aes_ni()
+    }
+}
+
+/// 一次 CPU 测试的配置
+#[derive(Debug, Clone)]
+pub struct CpuJob {
+    pub workloads: Vec<Workload>,
+    /// 每项负载在单核与多核阶段各自的运行时间
+    pub duration: Duration,
+    /// 多核阶段的线程数
+    pub threads: usize,
+}
+
+impl Default for CpuJob {
+    fn default() -> Self {
+        Self { workloads: Workload::ALL.to_vec(), duration: Duration::from_secs(2), threads: logical_cores() }
+    }
+}
+
+/// 单项负载的结果，吞吐量单位见 [`Workload::unit`]
+#[derive(Debug, Clone)]
+pub struct WorkloadResult {
+    pub workload: Workload,
+    /// 当前 CPU 不支持该负载（如缺少 AES-NI）时为 `None`
+    pub single: Option<f64>,
+    pub multi: Option<f64>,
+    pub threads: usize,
+}
+
+impl WorkloadResult {
+    pub fn single_score(&self) -> Option<f64> {
+        self.single.map(|v| v / self.workload.reference() * 1000.0)
+    }
+
+    pub fn multi_score(&self) -> Option<f64> {
+        self.multi.map(|v| v / self.workload.reference() * 1000.0)
+    }
+
+    /// 多核扩展效率：多核吞吐量 / (单核吞吐量 × 线程数)
+    pub fn scaling(&self) -> Option<f64> {
+        match (self.single, self.multi) {
+            (Some(single), Some(multi)) if single > 0.0 => Some(multi / (single * self.threads as f64) * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// `sysinfo` 报告的逻辑核心数
+pub fn logical_cores() -> usize {
+    let sys = System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing()));
+    sys.cpus().len().max(1)
+}
+
+/// 每个线程独占的负载状态（输入数据与缓冲区）
+struct Worker {
+    workload: Workload,
+    data: Vec<u8>,
+    scratch: Vec<u8>,
+    matrices: [Vec<f64>; 3],
+    aes_keys: [[u8; 16]; 11],
+    table: Vec<u32>,
+    seed: u64,
+}
+
+impl Worker {
+    fn new(workload: Workload) -> Self {
+        let mut rng = rand::rng();
+        let data = match workload {
+            Workload::Hash => (0..HASH_CHUNK).map(|_| rng.random()).collect(),
+            Workload::Aes => (0..AES_CHUNK).map(|_| rng.random()).collect(),
+            Workload::Compress => compressible_data(&mut rng),
+            Workload::Integer | Workload::Float => Vec::new(),
+        };
+        let matrices = match workload {
+            Workload::Float => {
+                let mut m = || (0..MATRIX_N * MATRIX_N).map(|_| rng.random_range(-1.0..1.0)).collect();
+                [m(), m(), vec![0.0; MATRIX_N * MATRIX_N]]
+            }
+            _ => [Vec::new(), Vec::new(), Vec::new()],
+        };
+        Self {
+            workload,
+            data,
+            scratch: Vec::with_capacity(COMPRESS_CHUNK * 2),
+            matrices,
+            aes_keys: rand::rng().random(),
+            table: vec![0; COMPRESS_TABLE],
+            seed: rand::rng().random(),
+        }
+    }
+
+    /// 处理一个工作单元
+    fn step(&mut self) {
+        match self.workload {
+            Workload::Integer => {
+                self.seed = black_box(kernels::integer(self.seed, INTEGER_ITERATIONS));
+            }
+            Workload::Float => {
+                let [a, b, c] = &mut self.matrices;
+                kernels::matmul(a, b, c, MATRIX_N);
+                black_box(&c);
+            }
+            Workload::Hash => {
+                let digest = kernels::sha256(&self.data);
+                // 摘要回写到输入，避免编译器复用上一次的结果
+                self.data[..32].copy_from_slice(&digest);
+            }
+            Workload::Aes => {
+                black_box(kernels::aes_ctr(&self.aes_keys, &mut self.data));
+            }
+            Workload::Compress => {
+                black_box(kernels::lz_compress(&self.data, &mut self.table, &mut self.scratch));
+            }
+        }
+    }
+}
+
+/// 近似文本的可压缩数据：从小词表中随机取词拼接
+fn compressible_data(rng: &mut impl Rng) -> Vec<u8> {
+    const WORDS: &[&str] = &[
+        "the", "server", "disk", "network", "latency", "throughput", "kernel", "memory", "cache", "packet",
+        "onekey", "benchmark", "result", "status", "error", "warning", "connection", "timeout", "thread", "core",
+    ];
+    let mut data = Vec::with_capacity(COMPRESS_CHUNK + 16);
+    while data.len() < COMPRESS_CHUNK {
+        data.extend_from_slice(WORDS[rng.random_range(0..WORDS.len())].as_bytes());
+        data.push(if rng.random_ratio(1, 12) { b'\n' } else { b' ' });
+    }
+    data.truncate(COMPRESS_CHUNK);
+    data
+}
+
+/// 以 `threads` 个线程运行负载 `duration`，返回总吞吐量
+pub fn measure(workload: Workload, threads: usize, duration: Duration) -> f64 {
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let mut worker = Worker::new(workload);
+                // 预热一次，填充缓存并触发 CPU 升频
+                worker.step();
+                barrier.wait();
+                let start = Instant::now();
+                let mut units = 0u64;
+                while start.elapsed() < duration {
+                    worker.step();
+                    units += 1;
+                }
+                units as f64 / start.elapsed().as_secs_f64()
+            })
+        })
+        .collect();
+    let units_per_sec: f64 = handles.into_iter().filter_map(|h| h.join().ok()).sum();
+    units_per_sec * workload.unit_amount()
+}
+
+/// 依次运行各项负载的单核与多核阶段，每完成一项回调一次
+pub fn run_job(job: &CpuJob, on_result: &mut dyn FnMut(&WorkloadResult)) -> Vec<WorkloadResult> {
+    let threads = job.threads.max(1);
+    job.workloads
+        .iter()
+        .map(|&workload| {
+            let (single, multi) = if workload.available() {
+                (Some(measure(workload, 1, job.duration)), Some(measure(workload, threads, job.duration)))
+            } else {
+                (None, None)
+            };
+            let result = WorkloadResult { workload, single, multi, threads };
+            on_result(&result);
+            result
+        })
+        .collect()
+}
+
+/// 各项得分的几何平均
+fn geometric_mean(scores: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = scores.filter(|s| *s > 0.0).fold((0.0, 0), |(sum, n), s| (sum + s.ln(), n + 1));
+    (count > 0).then(|| (sum / count as f64).exp())
+}
+
+/// 综合得分：(单核, 多核)
+pub fn total_scores(results: &[WorkloadResult]) -> (Option<f64>, Option<f64>) {
+    (
+        geometric_mean(results.iter().filter_map(WorkloadResult::single_score)),
+        geometric_mean(results.iter().filter_map(WorkloadResult::multi_score)),
+    )
+}
+
+pub fn result_tables(results: &[WorkloadResult]) -> Vec<ResultTable> {
+    let mut scores = ResultTable::new("CPU得分", "分", vec!["单核".to_string(), "多核".to_string()], Direction::Higher);
+    let mut scaling = ResultTable::new("多核扩展效率", "%", vec!["效率".to_string()], Direction::Higher);
+    for r in results {
+        scores.push_row(r.workload.label(), vec![r.single_score(), r.multi_score()]);
+        scaling.push_row(r.workload.label(), vec![r.scaling()]);
+    }
+    let (single, multi) = total_scores(results);
+    scores.push_row("综合", vec![single, multi]);
+    vec![scores, scaling]
+}
+
+/// 各负载的原始吞吐量指标
+pub fn metrics(results: &[WorkloadResult]) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    for r in results {
+        for (phase, value) in [("single", r.single), ("multi", r.multi)] {
+            if let Some(value) = value {
+                let key = format!("{}_{}", r.workload.key(), phase);
+                metrics.push(Metric::new(key, value, r.workload.unit(), Direction::Higher));
+            }
+        }
+    }
+    metrics
+}
+
+pub fn format_results(job: &CpuJob, results: &[WorkloadResult]) -> String {
+    let mut out = format!(
+        "\n{:<18} | {:>20} | {:>20} | {:>8}\n",
+        "负载", "单核", format!("多核 ({} 线程)", job.threads), "扩展效率"
+    );
+    for r in results {
+        let cell = |value: Option<f64>, score: Option<f64>| match (value, score) {
+            (Some(v), Some(s)) => format!("{:>9.1} {:<6} {:>4.0}分", v, r.workload.unit(), s),
+            _ => "不支持".to_string(),
+        };
+        out.push_str(&format!(
+            "{:<18} | {:>20} | {:>20} | {:>8}\n",
+            r.workload.label(),
+            cell(r.single, r.single_score()),
+            cell(r.multi, r.multi_score()),
+            r.scaling().map(|s| format!("{:.0}%", s)).unwrap_or_else(|| "-".to_string())
+        ));
+    }
+    let (single, multi) = total_scores(results);
+    let fmt = |s: Option<f64>| s.map(|s| format!("{:.0}", s)).unwrap_or_else(|| "-".to_string());
+    out.push_str(&format!("\n综合得分: 单核 {}，多核 {}\n", fmt(single), fmt(multi)));
+    if results.iter().any(|r| r.workload == Workload::Aes && r.single.is_none()) {
+        out.push_str("⚠️ 未检测到 AES-NI，已跳过 AES 测试。\n");
+    }
+    out.push_str(&format!(
+        "\n说明：每项负载单核、多核各运行 {} 秒；得分以固定参考吞吐量折算为 1000 分制，扩展效率 = 多核 / (单核 × 线程数)。\n",
+        job.duration.as_secs()
+    ));
+    out
+}
@@ -0,0 +1,274 @@
+// 各项负载的计算内核，每次调用处理固定大小的一个工作单元
+
+/// 整数：LCG + 移位异或 + 取模的混合运算
+pub(super) fn integer(seed: u64, iterations: u64) -> u64 {
+    let (mut x, mut y, mut z) = (seed | 1, seed.rotate_left(17), 0u64);
+    for i in 0..iterations {
+        x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        y ^= x >> 29;
+        y = y.rotate_left(7).wrapping_add(i);
+        z = z.wrapping_add(y % 1_000_003) ^ (x & 0xffff);
+    }
+    x ^ y ^ z
+}
+
+/// 浮点：`n × n` 双精度矩阵乘法，`c = a × b`
+pub(super) fn matmul(a: &[f64], b: &[f64], c: &mut [f64], n: usize) {
+    c.iter_mut().for_each(|v| *v = 0.0);
+    for i in 0..n {
+        for k in 0..n {
+            let aik = a[i * n + k];
+            let row = &b[k * n..(k + 1) * n];
+            for (cij, bkj) in c[i * n..(i + 1) * n].iter_mut().zip(row) {
+                *cij += aik * bkj;
+            }
+        }
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 压缩函数：处理一个 64 字节分组
+fn sha256_block(h: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(SHA256_K[i]).wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (state, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+        *state = state.wrapping_add(v);
+    }
+}
+
+/// 哈希：SHA-256（纯软件实现）。完整分组直接从输入读取，只有末尾填充使用栈上缓冲区，不分配内存
+pub(super) fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut blocks = data.chunks_exact(64);
+    for block in &mut blocks {
+        sha256_block(&mut h, block);
+    }
+
+    // 剩余字节 + 0x80 + 补零 + 64 位长度，共一到两个分组
+    let rest = blocks.remainder();
+    let mut tail = [0u8; 128];
+    tail[..rest.len()].copy_from_slice(rest);
+    tail[rest.len()] = 0x80;
+    let tail_len = if rest.len() < 56 { 64 } else { 128 };
+    tail[tail_len - 8..tail_len].copy_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in tail[..tail_len].chunks_exact(64) {
+        sha256_block(&mut h, block);
+    }
+
+    let mut digest = [0u8; 32];
+    for (out, v) in digest.chunks_exact_mut(4).zip(h) {
+        out.copy_from_slice(&v.to_be_bytes());
+    }
+    digest
+}
+
+/// CPU 是否支持 AES-NI 指令
+pub(super) fn has_aes_ni() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::arch::is_x86_feature_detected!("aes") && std::arch::is_x86_feature_detected!("sse2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// AES：以 AES-128 轮函数（AES-NI）对数据做 CTR 模式加密，返回最后一个密文块的摘要。
+/// 轮密钥直接取自随机数，不影响吞吐量测量。调用前须确认 [`has_aes_ni`]。
+#[cfg(target_arch = "x86_64")]
+pub(super) fn aes_ctr(round_keys: &[[u8; 16]; 11], data: &mut [u8]) -> u64 {
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn run(round_keys: &[[u8; 16]; 11], data: &mut [u8]) -> u64 {
+        use std::arch::x86_64::*;
+        let keys: Vec<__m128i> = round_keys.iter().map(|k| _mm_loadu_si128(k.as_ptr() as *const __m128i)).collect();
+        let mut last = _mm_setzero_si128();
+        for (counter, block) in data.chunks_exact_mut(16).enumerate() {
+            let mut state = _mm_xor_si128(_mm_set_epi64x(0, counter as i64), keys[0]);
+            for key in &keys[1..10] {
+                state = _mm_aesenc_si128(state, *key);
+            }
+            state = _mm_aesenclast_si128(state, keys[10]);
+            let plain = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+            last = _mm_xor_si128(plain, state);
+            _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, last);
+        }
+        _mm_cvtsi128_si64(last) as u64
+    }
+    // SAFETY: 调用方已通过 has_aes_ni 确认 CPU 支持所需指令
+    unsafe { run(round_keys, data) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(super) fn aes_ctr(_round_keys: &[[u8; 16]; 11], _data: &mut [u8]) -> u64 {
+    0
+}
+
+/// 压缩：LZ77 贪心匹配（哈希表查找 4 字节前缀），输出 字面量/匹配 记号流
+pub(super) fn lz_compress(input: &[u8], table: &mut [u32], out: &mut Vec<u8>) -> usize {
+    const MIN_MATCH: usize = 4;
+    const MAX_DISTANCE: usize = 65535;
+    let mask = table.len() - 1;
+    table.iter_mut().for_each(|slot| *slot = u32::MAX);
+    out.clear();
+
+    let mut literal_start = 0;
+    let mut pos = 0;
+    while pos + MIN_MATCH <= input.len() {
+        let seq = u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]]);
+        let slot = (seq.wrapping_mul(2654435761) >> 16) as usize & mask;
+        let candidate = table[slot] as usize;
+        table[slot] = pos as u32;
+        if candidate != u32::MAX as usize && pos - candidate <= MAX_DISTANCE && input[candidate..candidate + MIN_MATCH] == input[pos..pos + MIN_MATCH] {
+            let mut len = MIN_MATCH;
+            while pos + len < input.len() && input[candidate + len] == input[pos + len] && len < 255 + MIN_MATCH {
+                len += 1;
+            }
+            // 记号：字面量长度、字面量、匹配距离、匹配长度
+            let literals = &input[literal_start..pos];
+            out.extend_from_slice(&(literals.len() as u32).to_le_bytes());
+            out.extend_from_slice(literals);
+            out.extend_from_slice(&((pos - candidate) as u16).to_le_bytes());
+            out.push((len - MIN_MATCH) as u8);
+            pos += len;
+            literal_start = pos;
+        } else {
+            pos += 1;
+        }
+    }
+    let literals = &input[literal_start..];
+    out.extend_from_slice(&(literals.len() as u32).to_le_bytes());
+    out.extend_from_slice(literals);
+    out.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn sha256_known_answers() {
+        // FIPS 180-2 附录 B 及空串
+        assert_eq!(hex(&sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        // 填充跨越两个分组、以及恰好一个完整分组的边界
+        assert_eq!(hex(&sha256(&[b'a'; 56])), "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a");
+        assert_eq!(hex(&sha256(&[b'a'; 64])), "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb");
+    }
+
+    #[test]
+    fn aes_ctr_known_answer() {
+        if !has_aes_ni() {
+            return;
+        }
+        // FIPS-197 附录 A.1 密钥 2b7e1516 28aed2a6 abf71588 09cf4f3c 的轮密钥
+        let round_keys: [[u8; 16]; 11] = [
+            [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c],
+            [0xa0, 0xfa, 0xfe, 0x17, 0x88, 0x54, 0x2c, 0xb1, 0x23, 0xa3, 0x39, 0x39, 0x2a, 0x6c, 0x76, 0x05],
+            [0xf2, 0xc2, 0x95, 0xf2, 0x7a, 0x96, 0xb9, 0x43, 0x59, 0x35, 0x80, 0x7a, 0x73, 0x59, 0xf6, 0x7f],
+            [0x3d, 0x80, 0x47, 0x7d, 0x47, 0x16, 0xfe, 0x3e, 0x1e, 0x23, 0x7e, 0x44, 0x6d, 0x7a, 0x88, 0x3b],
+            [0xef, 0x44, 0xa5, 0x41, 0xa8, 0x52, 0x5b, 0x7f, 0xb6, 0x71, 0x25, 0x3b, 0xdb, 0x0b, 0xad, 0x00],
+            [0xd4, 0xd1, 0xc6, 0xf8, 0x7c, 0x83, 0x9d, 0x87, 0xca, 0xf2, 0xb8, 0xbc, 0x11, 0xf9, 0x15, 0xbc],
+            [0x6d, 0x88, 0xa3, 0x7a, 0x11, 0x0b, 0x3e, 0xfd, 0xdb, 0xf9, 0x86, 0x41, 0xca, 0x00, 0x93, 0xfd],
+            [0x4e, 0x54, 0xf7, 0x0e, 0x5f, 0x5f, 0xc9, 0xf3, 0x84, 0xa6, 0x4f, 0xb2, 0x4e, 0xa6, 0xdc, 0x4f],
+            [0xea, 0xd2, 0x73, 0x21, 0xb5, 0x8d, 0xba, 0xd2, 0x31, 0x2b, 0xf5, 0x60, 0x7f, 0x8d, 0x29, 0x2f],
+            [0xac, 0x77, 0x66, 0xf3, 0x19, 0xfa, 0xdc, 0x21, 0x28, 0xd1, 0x29, 0x41, 0x57, 0x5c, 0x00, 0x6e],
+            [0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63, 0x0c, 0xa6],
+        ];
+        // 计数器块为小端 64 位块号，高 64 位为零
+        let mut data: Vec<u8> = (0..64).collect();
+        let last = aes_ctr(&round_keys, &mut data);
+        assert_eq!(
+            hex(&data),
+            "7df6690f1ebd9fb4364bfa4cb5165a606e4825884626808a3dbcb737ff28d521\
+             3f91e018f62c8ab636ca81a101a8c5e2f27acc9a8155f871ffbed3d615da2830"
+        );
+        assert_eq!(last, 0x71f855819acc7af2);
+    }
+
+    /// 按 `lz_compress` 的记号格式解压
+    fn lz_decompress(mut tokens: &[u8]) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let literals = u32::from_le_bytes(tokens[..4].try_into().unwrap()) as usize;
+            out.extend_from_slice(&tokens[4..4 + literals]);
+            tokens = &tokens[4 + literals..];
+            if tokens.is_empty() {
+                return out;
+            }
+            let distance = u16::from_le_bytes([tokens[0], tokens[1]]) as usize;
+            let len = tokens[2] as usize + 4;
+            for _ in 0..len {
+                out.push(out[out.len() - distance]);
+            }
+            tokens = &tokens[3..];
+        }
+    }
+
+    #[test]
+    fn lz_compress_round_trips() {
+        let mut table = vec![0u32; 1 << 12];
+        let mut out = Vec::new();
+
+        // 无重复：只有一个字面量记号
+        assert_eq!(lz_compress(b"abc", &mut table, &mut out), 7);
+        assert_eq!(out, b"\x03\x00\x00\x00abc");
+
+        // 重复片段压缩为 字面量 abcd + 距离 4、长度 8 的匹配
+        assert_eq!(lz_compress(b"abcdabcdabcd", &mut table, &mut out), 15);
+        assert_eq!(out, b"\x04\x00\x00\x00abcd\x04\x00\x04\x00\x00\x00\x00");
+
+        let text: Vec<u8> = b"the quick brown fox jumps over the lazy dog; ".iter().cycle().take(10_000).copied().collect();
+        let noise: Vec<u8> = (0..10_000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        for input in [&text, &noise] {
+            let len = lz_compress(input, &mut table, &mut out);
+            assert_eq!(len, out.len());
+            assert_eq!(&lz_decompress(&out), input);
+        }
+        assert!(lz_compress(&text, &mut table, &mut out) < text.len() / 10);
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Components, CpuRefreshKind, RefreshKind, System};
+use crate::models::{Direction, Metric, ResultTable};
+use crate::performance::procstat::CpuStat;
+use super::{Worker, Workload};
+
+/// 压力测试负载：浮点矩阵乘发热最明显
+const STRESS_WORKLOAD: Workload = Workload::Float;
+/// 起始阶段取前几秒的平均作为基准
+const BASELINE_SECS: usize = 3;
+/// 单秒 steal 超过该百分比视为尖峰
+const STEAL_SPIKE_PCT: f64 = 5.0;
+/// 频率或吞吐量下降超过该百分比视为降频
+const THROTTLE_PCT: f64 = 10.0;
+/// 吞吐量变异系数超过该百分比视为不稳定
+const UNSTABLE_CV_PCT: f64 = 10.0;
+
+/// 压力测试配置
+#[derive(Debug, Clone)]
+pub struct StressJob {
+    pub threads: usize,
+    pub duration: Duration,
+}
+
+/// 每秒一次的采样
+#[derive(Debug, Clone)]
+pub struct StressSample {
+    /// 全部线程的吞吐量 (GFLOPS)
+    pub throughput: f64,
+    /// 各逻辑 CPU 的频率 (MHz)
+    pub freqs: Vec<u64>,
+    /// 各逻辑 CPU 的占用率 (%)
+    pub usages: Vec<f32>,
+    pub steal_pct: f64,
+    /// hwmon 中 CPU 相关传感器的最高温度 (°C)
+    pub temp: Option<f32>,
+}
+
+impl StressSample {
+    pub fn freq_avg(&self) -> f64 {
+        average(self.freqs.iter().map(|&f| f as f64))
+    }
+
+    pub fn freq_min(&self) -> u64 {
+        self.freqs.iter().copied().min().unwrap_or(0)
+    }
+
+    pub fn usage_avg(&self) -> f64 {
+        average(self.usages.iter().map(|&u| u as f64))
+    }
+}
+
+/// 压力测试结论
+#[derive(Debug, Clone)]
+pub struct StressReport {
+    pub threads: usize,
+    pub samples: Vec<StressSample>,
+    pub throughput_avg: f64,
+    /// 吞吐量变异系数 (%)
+    pub throughput_cv: f64,
+    /// 后半程吞吐量相对起始阶段的下降 (%)
+    pub throughput_drop: f64,
+    pub freq_start: f64,
+    /// 测试期间单核出现过的最低频率
+    pub freq_min: u64,
+    /// 后半程平均频率相对起始阶段的下降 (%)
+    pub freq_drop: f64,
+    pub steal_avg: f64,
+    pub steal_max: f64,
+    pub steal_spikes: usize,
+    pub temp_start: Option<f32>,
+    pub temp_max: Option<f32>,
+}
+
+impl StressReport {
+    pub fn throttled(&self) -> bool {
+        self.freq_drop > THROTTLE_PCT || self.throughput_drop > THROTTLE_PCT
+    }
+
+    pub fn unstable(&self) -> bool {
+        self.throughput_cv > UNSTABLE_CV_PCT
+    }
+
+    /// 存在降频、steal 尖峰或吞吐量波动
+    pub fn has_issues(&self) -> bool {
+        self.throttled() || self.unstable() || self.steal_spikes > 0
+    }
+
+    pub fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = vec![
+            Metric::new("stress_throughput", self.throughput_avg, "GFLOPS", Direction::Higher),
+            Metric::new("stress_throughput_variance", self.throughput_cv, "%", Direction::Lower),
+            Metric::new("stress_throughput_drop", self.throughput_drop, "%", Direction::Lower),
+            Metric::new("stress_freq_start", self.freq_start, "MHz", Direction::Higher),
+            Metric::new("stress_freq_drop", self.freq_drop, "%", Direction::Lower),
+            Metric::new("stress_steal_avg", self.steal_avg, "%", Direction::Lower),
+            Metric::new("stress_steal_max", self.steal_max, "%", Direction::Lower),
+        ];
+        if let Some(temp) = self.temp_max {
+            metrics.push(Metric::new("stress_temp_max", temp as f64, "°C", Direction::Lower));
+        }
+        metrics
+    }
+
+    /// 每秒吞吐量表，便于在报告中观察降速过程
+    pub fn table(&self) -> ResultTable {
+        let mut table = ResultTable::new("压力测试每秒吞吐量", "GFLOPS", vec!["吞吐量".to_string()], Direction::Higher);
+        for (i, sample) in self.samples.iter().enumerate() {
+            table.push_row(format!("{}s", i + 1), vec![Some(sample.throughput)]);
+        }
+        table
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = format!(
+            "\n{:>4} | {:>10} | {:>10} | {:>10} | {:>8} | {:>7} | {:>6}\n",
+            "秒", "GFLOPS", "平均频率", "最低频率", "占用率", "steal", "温度"
+        );
+        for (i, s) in self.samples.iter().enumerate() {
+            out.push_str(&format!(
+                "{:>4} | {:>10.2} | {:>7.0} MHz | {:>7} MHz | {:>7.1}% | {:>6.1}% | {:>6}\n",
+                i + 1,
+                s.throughput,
+                s.freq_avg(),
+                s.freq_min(),
+                s.usage_avg(),
+                s.steal_pct,
+                s.temp.map(|t| format!("{:.0}°C", t)).unwrap_or_else(|| "-".to_string())
+            ));
+        }
+
+        out.push_str(&format!("\n================ 压力测试结论 ({} 线程) ================\n", self.threads));
+        out.push_str(&format!(
+            "吞吐量: 平均 {:.2} GFLOPS，波动 (变异系数) {:.1}%，后半程较起始下降 {:.1}%\n",
+            self.throughput_avg, self.throughput_cv, self.throughput_drop
+        ));
+        out.push_str(&format!(
+            "频率:   起始 {:.0} MHz，后半程较起始下降 {:.1}%，单核最低 {} MHz\n",
+            self.freq_start, self.freq_drop, self.freq_min
+        ));
+        out.push_str(&format!(
+            "steal:  平均 {:.2}%，最高 {:.2}%，超过 {}% 的秒数 {}\n",
+            self.steal_avg, self.steal_max, STEAL_SPIKE_PCT, self.steal_spikes
+        ));
+        match (self.temp_start, self.temp_max) {
+            (Some(start), Some(max)) => out.push_str(&format!("温度:   起始 {:.0}°C，最高 {:.0}°C\n", start, max)),
+            _ => out.push_str("温度:   未找到 hwmon 温度传感器（虚拟机通常不提供）\n"),
+        }
+
+        if self.throttled() {
+            out.push_str("⚠️ 检测到降频/降速：持续满载后性能明显低于起始阶段，可能存在温控或宿主机限制。\n");
+        }
+        if self.unstable() {
+            out.push_str("⚠️ 吞吐量波动较大，可能存在邻居争抢 (noisy neighbour) 或 CPU 超售。\n");
+        }
+        if self.steal_spikes > 0 {
+            out.push_str("⚠️ 出现 steal 时间尖峰，宿主机 CPU 资源紧张。\n");
+        }
+        if !self.has_issues() {
+            out.push_str("✅ 满载期间性能稳定，未发现降频、steal 尖峰或明显波动。\n");
+        }
+        out
+    }
+}
+
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, n), v| (sum + v, n + 1));
+    if count == 0 { 0.0 } else { sum / count as f64 }
+}
+
+/// 前 [`BASELINE_SECS`] 秒与后半程的平均值，返回 (起始, 后半程, 下降百分比)
+fn start_vs_end(values: &[f64]) -> (f64, f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let start = average(values[..values.len().min(BASELINE_SECS)].iter().copied());
+    let end = average(values[values.len() / 2..].iter().copied());
+    let drop = if start > 0.0 { ((start - end) / start * 100.0).max(0.0) } else { 0.0 };
+    (start, end, drop)
+}
+
+/// hwmon 中 CPU 相关传感器的最高温度；没有 CPU 传感器时取全部传感器
+fn cpu_temperature(components: &mut Components) -> Option<f32> {
+    components.refresh(false);
+    const CPU_LABELS: &[&str] = &["cpu", "core", "package", "tctl", "tdie", "k10temp", "coretemp"];
+    let temps = |cpu_only: bool| {
+        components
+            .iter()
+            .filter(|c| !cpu_only || CPU_LABELS.iter().any(|l| c.label().to_lowercase().contains(l)))
+            .filter_map(|c| c.temperature())
+            .filter(|t| t.is_finite() && *t > 0.0)
+            .fold(None, |max: Option<f32>, t| Some(max.map_or(t, |m| m.max(t))))
+    };
+    temps(true).or_else(|| temps(false))
+}
+
+/// 让全部线程满载运行，每秒采样吞吐量、频率、占用率、steal 与温度
+pub fn run(job: &StressJob, on_sample: &mut dyn FnMut(usize, &StressSample)) -> StressReport {
+    let threads = job.threads.max(1);
+    let stop = Arc::new(AtomicBool::new(false));
+    let counters: Vec<Arc<AtomicU64>> = (0..threads).map(|_| Arc::new(AtomicU64::new(0))).collect();
+    let handles: Vec<_> = counters
+        .iter()
+        .map(|counter| {
+            let (stop, counter) = (stop.clone(), counter.clone());
+            thread::spawn(move || {
+                let mut worker = Worker::new(STRESS_WORKLOAD);
+                while !stop.load(Ordering::Relaxed) {
+                    worker.step();
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        })
+        .collect();
+
+    let mut sys = System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::everything()));
+    let mut components = Components::new_with_refreshed_list();
+    let mut last_stat = CpuStat::read().unwrap_or_default();
+    let mut last_units = 0u64;
+    let mut last_time = Instant::now();
+    let mut samples = Vec::new();
+    let seconds = job.duration.as_secs().max(1) as usize;
+
+    for second in 1..=seconds {
+        thread::sleep(Duration::from_secs(1).saturating_sub(last_time.elapsed()));
+        let units: u64 = counters.iter().map(|c| c.load(Ordering::Relaxed)).sum();
+        let elapsed = last_time.elapsed().as_secs_f64();
+        last_time = Instant::now();
+        sys.refresh_cpu_specifics(CpuRefreshKind::everything());
+        let stat = CpuStat::read().unwrap_or_default();
+
+        let sample = StressSample {
+            throughput: (units - last_units) as f64 / elapsed * STRESS_WORKLOAD.unit_amount(),
+            freqs: sys.cpus().iter().map(|c| c.frequency()).collect(),
+            usages: sys.cpus().iter().map(|c| c.cpu_usage()).collect(),
+            steal_pct: stat.since(&last_stat).total.steal_pct(),
+            temp: cpu_temperature(&mut components),
+        };
+        last_units = units;
+        last_stat = stat;
+        on_sample(second, &sample);
+        samples.push(sample);
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    for handle in handles {
+        let _ = handle.join();
+    }
+    analyze(threads, samples)
+}
+
+fn analyze(threads: usize, samples: Vec<StressSample>) -> StressReport {
+    let throughputs: Vec<f64> = samples.iter().map(|s| s.throughput).collect();
+    let throughput_avg = average(throughputs.iter().copied());
+    let variance = average(throughputs.iter().map(|t| (t - throughput_avg).powi(2)));
+    let throughput_cv = if throughput_avg > 0.0 { variance.sqrt() / throughput_avg * 100.0 } else { 0.0 };
+    let (_, _, throughput_drop) = start_vs_end(&throughputs);
+
+    let freqs: Vec<f64> = samples.iter().map(StressSample::freq_avg).collect();
+    let (freq_start, _, freq_drop) = start_vs_end(&freqs);
+
+    let steals: Vec<f64> = samples.iter().map(|s| s.steal_pct).collect();
+    let temps: Vec<f32> = samples.iter().filter_map(|s| s.temp).collect();
+
+    StressReport {
+        threads,
+        throughput_avg,
+        throughput_cv,
+        throughput_drop,
+        freq_start,
+        freq_min: samples.iter().map(StressSample::freq_min).min().unwrap_or(0),
+        freq_drop,
+        steal_avg: average(steals.iter().copied()),
+        steal_max: steals.iter().copied().fold(0.0, f64::max),
+        steal_spikes: steals.iter().filter(|&&s| s > STEAL_SPIKE_PCT).count(),
+        temp_start: temps.first().copied(),
+        temp_max: temps.iter().copied().reduce(f32::max),
+        samples,
+    }
+}
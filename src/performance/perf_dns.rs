@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use crate::dns::{self, Resolver, ResolverSource, RCODE_NOERROR, RCODE_NXDOMAIN, RCODE_SERVFAIL};
+use crate::models::{Direction, ResultTable};
+
+pub const DEFAULT_DOMAINS: &str =
+    "google.com,youtube.com,github.com,cloudflare.com,wikipedia.org,apple.com,baidu.com,qq.com,taobao.com,bilibili.com";
+
+/// 对比用的公共解析器
+const PUBLIC_RESOLVERS: &[(&str, Ipv4Addr)] = &[
+    ("Cloudflare", Ipv4Addr::new(1, 1, 1, 1)),
+    ("Google", Ipv4Addr::new(8, 8, 8, 8)),
+    ("Quad9", Ipv4Addr::new(9, 9, 9, 9)),
+    ("AliDNS", Ipv4Addr::new(223, 5, 5, 5)),
+    ("DNSPod", Ipv4Addr::new(119, 29, 29, 29)),
+    ("114DNS", Ipv4Addr::new(114, 114, 114, 114)),
+];
+/// 篡改检查的参考解析器，通过 TCP 查询以避开 UDP 投毒
+const REFERENCE: (&str, Ipv4Addr) = ("Cloudflare", Ipv4Addr::new(1, 1, 1, 1));
+/// 已签名且签名有效的域名
+const DNSSEC_SIGNED: &str = "cloudflare.com";
+/// 签名故意损坏的域名，验证型解析器应返回 SERVFAIL
+const DNSSEC_BROKEN: &str = "dnssec-failed.org";
+/// 返回查询其权威服务器的递归解析器出口地址
+const WHOAMI: &str = "whoami.akamai.net";
+const WHOAMI_QUERIES: usize = 3;
+
+pub fn public_resolvers() -> Vec<Resolver> {
+    PUBLIC_RESOLVERS
+        .iter()
+        .map(|&(name, ip)| Resolver::new(IpAddr::V4(ip), ResolverSource::Public(name)))
+        .collect()
+}
+
+/// 解析逗号分隔的解析器地址
+pub fn parse_resolvers(raw: &str) -> Result<Vec<Resolver>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<IpAddr>()
+                .map(|ip| Resolver::new(ip, ResolverSource::Custom))
+                .map_err(|_| format!("'{}' 不是有效的 IP 地址", s))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsJob {
+    /// 待测解析器（系统或自定义），同时接受 DNSSEC、篡改与出口检查
+    pub resolvers: Vec<Resolver>,
+    /// 是否加入公共解析器做延迟对比
+    pub include_public: bool,
+    pub domains: Vec<String>,
+    /// 每个域名的查询轮数
+    pub rounds: u32,
+    pub timeout: Duration,
+    pub checks: bool,
+}
+
+/// 单个解析器的延迟测试结果
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub resolver: Resolver,
+    pub latencies: Vec<f64>,
+    pub queries: usize,
+    pub failures: usize,
+    pub last_error: Option<String>,
+}
+
+impl BenchResult {
+    pub fn avg(&self) -> Option<f64> {
+        (!self.latencies.is_empty()).then(|| self.latencies.iter().sum::<f64>() / self.latencies.len() as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.latencies.iter().copied().reduce(f64::min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.latencies.iter().copied().reduce(f64::max)
+    }
+
+    /// 失败率 (%)：超时、网络错误与 SERVFAIL/REFUSED 等应答
+    pub fn fail_rate(&self) -> f64 {
+        if self.queries == 0 { 0.0 } else { self.failures as f64 * 100.0 / self.queries as f64 }
+    }
+}
+
+fn bench_resolver(resolver: &Resolver, job: &DnsJob) -> BenchResult {
+    let mut result = BenchResult { resolver: resolver.clone(), latencies: Vec::new(), queries: 0, failures: 0, last_error: None };
+    for _ in 0..job.rounds {
+        for domain in &job.domains {
+            result.queries += 1;
+            match dns::query(resolver.addr, domain, false, false, job.timeout) {
+                Ok(response) if matches!(response.rcode, RCODE_NOERROR | RCODE_NXDOMAIN) => {
+                    result.latencies.push(response.latency.as_secs_f64() * 1000.0);
+                }
+                Ok(response) => {
+                    result.failures += 1;
+                    result.last_error = Some(format!("{}: {}", domain, response.rcode_label()));
+                }
+                Err(e) => {
+                    result.failures += 1;
+                    result.last_error = Some(format!("{}: {}", domain, e));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DnssecStatus {
+    /// 签名有效的域名带 AD 标志解析成功，签名损坏的域名被拒绝
+    Validating,
+    NotValidating,
+    Unknown(String),
+}
+
+impl DnssecStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            DnssecStatus::Validating => "✅ 验证 DNSSEC".to_string(),
+            DnssecStatus::NotValidating => "⚠️ 不验证 DNSSEC".to_string(),
+            DnssecStatus::Unknown(e) => format!("❔ 无法判断: {}", e),
+        }
+    }
+}
+
+fn check_dnssec(resolver: &Resolver, timeout: Duration) -> DnssecStatus {
+    let good = dns::query(resolver.addr, DNSSEC_SIGNED, true, false, timeout);
+    let bad = dns::query(resolver.addr, DNSSEC_BROKEN, true, false, timeout);
+    match (good, bad) {
+        (Ok(good), Ok(bad)) if good.authenticated && bad.rcode == RCODE_SERVFAIL => DnssecStatus::Validating,
+        (_, Ok(bad)) if bad.rcode == RCODE_NOERROR && !bad.answers.is_empty() => DnssecStatus::NotValidating,
+        (Err(e), _) | (_, Err(e)) => DnssecStatus::Unknown(e.to_string()),
+        (Ok(good), Ok(bad)) => DnssecStatus::Unknown(format!("应答异常 {} / {}", good.rcode_label(), bad.rcode_label())),
+    }
+}
+
+/// 保留地址、私有地址等不应出现在公网域名解析结果中的地址
+fn is_bogon(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_unspecified()
+                || v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 224
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_unspecified() || v6.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TamperFinding {
+    /// 不存在的域名返回了地址（NXDOMAIN 劫持）
+    NxdomainHijack(Vec<IpAddr>),
+    /// 公网域名解析到保留地址
+    Bogon { domain: String, addrs: Vec<IpAddr> },
+    /// 与参考解析器的结果无交集，CDN 调度也可能导致
+    Mismatch { domain: String, local: Vec<IpAddr>, reference: Vec<IpAddr> },
+}
+
+impl TamperFinding {
+    pub fn is_suspicious(&self) -> bool {
+        !matches!(self, TamperFinding::Mismatch { .. })
+    }
+
+    pub fn describe(&self) -> String {
+        let join = |addrs: &[IpAddr]| addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(" ");
+        match self {
+            TamperFinding::NxdomainHijack(addrs) => format!("🚨 不存在的域名被解析到 {}（NXDOMAIN 劫持）", join(addrs)),
+            TamperFinding::Bogon { domain, addrs } => format!("🚨 {} 被解析到保留地址 {}", domain, join(addrs)),
+            TamperFinding::Mismatch { domain, local, reference } => {
+                format!("⚠️ {} 结果与参考不一致: {} ↔ {}（可能为 CDN 调度）", domain, join(local), join(reference))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverChecks {
+    pub resolver: Resolver,
+    pub dnssec: DnssecStatus,
+    pub findings: Vec<TamperFinding>,
+    /// 篡改检查中未能完成的各项及原因，其余项的结果仍保留在 `findings` 中
+    pub tamper_errors: Vec<String>,
+    /// 权威服务器看到的递归解析器出口地址
+    pub egress: Result<Vec<IpAddr>, String>,
+}
+
+/// 逐项检查篡改迹象，单项查询失败只记录错误并继续，返回已得到的发现与失败原因
+fn check_tamper(resolver: &Resolver, domains: &[String], timeout: Duration) -> (Vec<TamperFinding>, Vec<String>) {
+    let mut findings = Vec::new();
+    let mut errors = Vec::new();
+    let missing = format!("onekey-{:016x}.com", rand::random::<u64>());
+    match dns::query(resolver.addr, &missing, false, false, timeout) {
+        Ok(response) if response.rcode == RCODE_NOERROR && !response.answers.is_empty() => {
+            findings.push(TamperFinding::NxdomainHijack(response.answers));
+        }
+        Ok(_) => {}
+        Err(e) => errors.push(format!("NXDOMAIN 探测失败: {}", e)),
+    }
+
+    let reference = SocketAddr::new(IpAddr::V4(REFERENCE.1), 53);
+    for domain in domains {
+        let local = match dns::query(resolver.addr, domain, false, false, timeout) {
+            Ok(local) => local,
+            Err(e) => {
+                errors.push(format!("{} 查询失败: {}", domain, e));
+                continue;
+            }
+        };
+        let bogons: Vec<IpAddr> = local.answers.iter().copied().filter(is_bogon).collect();
+        if !bogons.is_empty() {
+            findings.push(TamperFinding::Bogon { domain: domain.clone(), addrs: bogons });
+            continue;
+        }
+        let remote = match dns::query(reference, domain, false, true, timeout) {
+            Ok(remote) => remote,
+            Err(e) => {
+                errors.push(format!("{} 无法对比，参考解析器 {} 不可用: {}", domain, REFERENCE.0, e));
+                continue;
+            }
+        };
+        let expected: HashSet<&IpAddr> = remote.answers.iter().collect();
+        if !local.answers.is_empty() && !expected.is_empty() && !local.answers.iter().any(|a| expected.contains(a)) {
+            findings.push(TamperFinding::Mismatch { domain: domain.clone(), local: local.answers, reference: remote.answers });
+        }
+    }
+    (findings, errors)
+}
+
+fn check_egress(resolver: &Resolver, timeout: Duration) -> Result<Vec<IpAddr>, String> {
+    let mut addrs: Vec<IpAddr> = Vec::new();
+    let mut error = None;
+    for _ in 0..WHOAMI_QUERIES {
+        match dns::query(resolver.addr, WHOAMI, false, false, timeout) {
+            Ok(response) => {
+                for addr in response.answers {
+                    if !addrs.contains(&addr) {
+                        addrs.push(addr);
+                    }
+                }
+            }
+            Err(e) => error = Some(e.to_string()),
+        }
+    }
+    match error {
+        Some(e) if addrs.is_empty() => Err(e),
+        _ => Ok(addrs),
+    }
+}
+
+fn check_resolver(resolver: &Resolver, job: &DnsJob) -> ResolverChecks {
+    let (findings, tamper_errors) = check_tamper(resolver, &job.domains, job.timeout);
+    ResolverChecks {
+        resolver: resolver.clone(),
+        dnssec: check_dnssec(resolver, job.timeout),
+        findings,
+        tamper_errors,
+        egress: check_egress(resolver, job.timeout),
+    }
+}
+
+/// 并发对每个元素执行 `f`，结果按输入顺序返回
+fn parallel<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync, on_done: &mut dyn FnMut(&R)) -> Vec<R> {
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for (index, item) in items.iter().enumerate() {
+            let (tx, f) = (tx.clone(), &f);
+            scope.spawn(move || {
+                let _ = tx.send((index, f(item)));
+            });
+        }
+        drop(tx);
+        let mut results: Vec<(usize, R)> = rx.iter().inspect(|(_, r)| on_done(r)).collect();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, r)| r).collect()
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsReport {
+    pub bench: Vec<BenchResult>,
+    pub checks: Vec<ResolverChecks>,
+}
+
+impl DnsReport {
+    /// 是否发现明确的篡改迹象
+    pub fn tampered(&self) -> bool {
+        self.checks.iter().any(|c| c.findings.iter().any(TamperFinding::is_suspicious))
+    }
+
+    pub fn format(&self) -> String {
+        let ms = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1}", v));
+        let mut out = format!(
+            "{:<28} | {:<16} | {:>8} | {:>8} | {:>8} | {:>6}\n",
+            "解析器", "来源", "平均ms", "最低ms", "最高ms", "失败率"
+        );
+        for result in &self.bench {
+            out.push_str(&format!(
+                "{:<28} | {:<16} | {:>8} | {:>8} | {:>8} | {:>5.0}%\n",
+                result.resolver.label(),
+                result.resolver.source.label(),
+                ms(result.avg()),
+                ms(result.min()),
+                ms(result.max()),
+                result.fail_rate()
+            ));
+        }
+        let errors: Vec<&BenchResult> = self.bench.iter().filter(|r| r.last_error.is_some()).collect();
+        for result in errors {
+            out.push_str(&format!("  {} 最近错误: {}\n", result.resolver.label(), result.last_error.as_deref().unwrap_or("")));
+        }
+
+        for check in &self.checks {
+            out.push_str(&format!("\n[{}] {}\n", check.resolver.label(), check.dnssec.describe()));
+            match &check.egress {
+                Ok(addrs) if addrs.is_empty() => out.push_str("  出口解析器: 未返回\n"),
+                Ok(addrs) => out.push_str(&format!(
+                    "  出口解析器: {}\n",
+                    addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+                )),
+                Err(e) => out.push_str(&format!("  出口解析器: 查询失败 ({})\n", e)),
+            }
+            for e in &check.tamper_errors {
+                out.push_str(&format!("  篡改检查未完成: {}\n", e));
+            }
+            if check.findings.is_empty() && check.tamper_errors.is_empty() {
+                out.push_str("  ✅ 未发现篡改迹象\n");
+            }
+            for finding in &check.findings {
+                out.push_str(&format!("  {}\n", finding.describe()));
+            }
+        }
+        out
+    }
+
+    pub fn tables(&self) -> Vec<ResultTable> {
+        let columns = ["平均", "最低", "最高"].iter().map(|c| c.to_string()).collect();
+        let mut latency = ResultTable::new("DNS 延迟", "ms", columns, Direction::Lower);
+        let mut failures = ResultTable::new("DNS 失败率", "%", vec!["失败率".to_string()], Direction::Lower);
+        for result in &self.bench {
+            latency.push_row(result.resolver.label(), vec![result.avg(), result.min(), result.max()]);
+            failures.push_row(result.resolver.label(), vec![Some(result.fail_rate())]);
+        }
+        vec![latency, failures]
+    }
+}
+
+/// 延迟测试覆盖全部解析器，DNSSEC、篡改与出口检查只针对待测解析器
+pub fn run(job: &DnsJob, on_progress: &mut dyn FnMut(&str)) -> DnsReport {
+    let mut targets = job.resolvers.clone();
+    if job.include_public {
+        targets.extend(public_resolvers().into_iter().filter(|p| !job.resolvers.iter().any(|r| r.addr == p.addr)));
+    }
+    on_progress(&format!("测试 {} 个解析器的查询延迟...", targets.len()));
+    let bench = parallel(&targets, |r| bench_resolver(r, job), &mut |r: &BenchResult| {
+        on_progress(&format!("  {} 完成", r.resolver.label()));
+    });
+    let checks = if job.checks && !job.resolvers.is_empty() {
+        on_progress("检查 DNSSEC 验证、解析篡改与出口解析器...");
+        parallel(&job.resolvers, |r| check_resolver(r, job), &mut |_| {})
+    } else {
+        Vec::new()
+    };
+    DnsReport { bench, checks }
+}
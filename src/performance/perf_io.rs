@@ -1,27 +1,46 @@
-use std::fs::OpenOptions;
-use std::os::unix::fs::OpenOptionsExt;
+mod uring;
+pub mod verify;
+
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use rand::rngs::ThreadRng;
 use rand::Rng;
-use std::io::{Read, Seek, SeekFrom, Write};
-use std::sync::{Arc, Barrier, Mutex};
-use std::thread;
-use std::time::Instant;
-
-// use ratatui::{
-//     backend::CrosstermBackend,
-//     prelude::*,
-//     widgets::{Block, Borders, Cell, Row, Table},
-// };
-// use tempfile::tempfile;
-
-const SIZES: &[(usize, &str)] = &[
-    (4 * 1024, "4k"),
-    (64 * 1024, "64k"),
-    (512 * 1024, "512k"),
-    (1024 * 1024, "1m"),
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+use tempfile::NamedTempFile;
+use crate::models::{Direction, ResultTable};
+use crate::utils::StopSignal;
+use super::histogram::{LatencyHistogram, LatencySummary};
+use crate::params::format_size;
+
+const DEFAULT_BLOCK_SIZES: &[u64] = &[4 * 1024, 64 * 1024, 512 * 1024, 1024 * 1024];
+const DEFAULT_FILE_SIZE: u64 = 256 * 1024 * 1024;
+const TEST_FILE_PREFIX: &str = "onekey_benchio_";
+/// 不参与硬盘测试的虚拟/内存文件系统
+const VIRTUAL_FS: &[&str] = &[
+    "tmpfs", "devtmpfs", "ramfs", "proc", "sysfs", "devpts", "cgroup", "cgroup2", "squashfs", "autofs", "nsfs",
+    "tracefs", "debugfs", "securityfs", "pstore", "bpf", "efivarfs", "configfs", "mqueue", "hugetlbfs", "fusectl",
+    "binfmt_misc", "fuse.lxcfs",
 ];
-const TEST_FILE_SIZE: usize = 256 * 1024 * 1024;
+/// 测试文件之外至少保留总容量的 5%，最多 1GiB
+const MAX_FREE_RESERVE: u64 = 1024 * 1024 * 1024;
+/// O_DIRECT 要求缓冲区地址、偏移和长度按扇区对齐，这里统一按 4k 对齐
+const DIRECT_ALIGN: u64 = 4096;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 少于该采样数时不做突发/持续分析
+const MIN_STEADY_SAMPLES: usize = 4;
+/// 持续吞吐量低于突发吞吐量的该比例时视为降速
+const THROTTLE_RATIO: f64 = 0.7;
+const MB: f64 = 1024.0 * 1024.0;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     SeqRead,
     SeqWrite,
@@ -30,218 +49,769 @@ pub enum Mode {
     RandRW,
 }
 
-// impl Mode {
-//     pub fn label(&self) -> &'static str {
-//         match self {
-//             Mode::SeqRead => "顺序读",
-//             Mode::SeqWrite => "顺序写",
-//             Mode::RandRead => "随机读",
-//             Mode::RandWrite => "随机写",
-//             Mode::RandRW => "混合读写",
-//         }
-//     }
-// }
-
-// impl Mode {
-//     fn label(&self) -> &'static str {
-//         match self {
-//             Mode::Seq => "顺序读写",
-//             Mode::RandRW => "随机读写",
-//             Mode::SeqRead => "顺序读",
-//             Mode::SeqWrite => "顺序写",
-//             Mode::RandRead => "随机读",
-//             Mode::RandWrite => "随机写",
-//         }
-//     }
-// }
-
-fn bench_rw(block_size: usize, mode: Mode, concurrency: usize, o_direct: bool, rw_mix: f64) -> (f64, f64, f64, f64) {
-    let file_size = TEST_FILE_SIZE;
-    let blocks = file_size / block_size;
-    let testfile_path = "./testfile_benchio";
-    // 先创建大文件，避免多线程竞争
-    {
-        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(testfile_path).expect("无法创建测试文件");
-        let buf = vec![0u8; block_size];
-        for _ in 0..blocks {
-            file.write_all(&buf).unwrap();
-        }
-        file.sync_all().unwrap();
-    }
-
-    let barrier = Arc::new(Barrier::new(concurrency));
-    let read_bytes = Arc::new(Mutex::new(0f64));
-    let write_bytes = Arc::new(Mutex::new(0f64));
-    let read_time = Arc::new(Mutex::new(0f64));
-    let write_time = Arc::new(Mutex::new(0f64));
+impl Mode {
+    pub const ALL: [Mode; 5] = [Mode::SeqRead, Mode::SeqWrite, Mode::RandRead, Mode::RandWrite, Mode::RandRW];
+    /// 参数中使用的模式名，与 [`Mode::ALL`] 顺序一致
+    pub const KEYS: &'static [&'static str] = &["seqread", "seqwrite", "randread", "randwrite", "randrw"];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Mode::SeqRead => "seqread",
+            Mode::SeqWrite => "seqwrite",
+            Mode::RandRead => "randread",
+            Mode::RandWrite => "randwrite",
+            Mode::RandRW => "randrw",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Mode> {
+        Mode::ALL.into_iter().find(|m| m.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mode::SeqRead => "顺序读",
+            Mode::SeqWrite => "顺序写",
+            Mode::RandRead => "随机读",
+            Mode::RandWrite => "随机写",
+            Mode::RandRW => "混合读写",
+        }
+    }
+}
+
+/// 是否绕过页缓存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    Cached,
+    Direct,
+}
+
+impl CacheMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CacheMode::Cached => "缓存IO",
+            CacheMode::Direct => "O_DIRECT(绕过缓存)",
+        }
+    }
+}
+
+/// IO 引擎
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoEngine {
+    /// 阻塞的 pread/pwrite，队列深度等于线程数
+    Sync,
+    /// io_uring 异步提交，每个线程保持 `iodepth` 个请求在途
+    Uring,
+}
+
+impl IoEngine {
+    pub const KEYS: &'static [&'static str] = &["sync", "uring"];
+
+    pub fn from_key(key: &str) -> Option<IoEngine> {
+        match key {
+            "sync" => Some(IoEngine::Sync),
+            "uring" => Some(IoEngine::Uring),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IoEngine::Sync => "同步IO",
+            IoEngine::Uring => "io_uring",
+        }
+    }
+}
+
+/// 一次硬盘测试的完整配置
+#[derive(Debug, Clone)]
+pub struct IoJob {
+    /// 测试文件所在目录，决定了被测的文件系统/硬盘
+    pub dir: PathBuf,
+    /// 测试文件大小（字节）
+    pub file_size: u64,
+    /// 并发线程数
+    pub threads: usize,
+    pub block_sizes: Vec<u64>,
+    pub modes: Vec<Mode>,
+    pub cache_modes: Vec<CacheMode>,
+    /// 混合读写中读操作的比例 (0.0-1.0)
+    pub read_mix: f64,
+    /// 单项测试的最长运行时间；`None` 表示每个线程跑完自己的全部块
+    pub runtime: Option<Duration>,
+    /// 按时间运行：循环读写直到 `runtime` 结束，而不是跑完固定数量的块
+    pub time_based: bool,
+    pub engine: IoEngine,
+    /// 每个线程的在途请求数，仅 io_uring 引擎有效
+    pub iodepth: usize,
+}
+
+impl Default for IoJob {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            file_size: DEFAULT_FILE_SIZE,
+            threads: 4,
+            block_sizes: DEFAULT_BLOCK_SIZES.to_vec(),
+            modes: Mode::ALL.to_vec(),
+            cache_modes: vec![CacheMode::Cached, CacheMode::Direct],
+            read_mix: 0.5,
+            runtime: None,
+            time_based: false,
+            engine: IoEngine::Sync,
+            iodepth: 1,
+        }
+    }
+}
+
+impl IoJob {
+    /// 检查参数组合是否可执行
+    pub fn validate(&self) -> Result<(), String> {
+        if self.threads == 0 {
+            return Err("线程数必须大于 0".to_string());
+        }
+        if self.block_sizes.is_empty() || self.modes.is_empty() || self.cache_modes.is_empty() {
+            return Err("块大小、测试模式和缓存模式均不能为空".to_string());
+        }
+        if self.iodepth == 0 {
+            return Err("队列深度必须大于 0".to_string());
+        }
+        if self.engine == IoEngine::Sync && self.iodepth > 1 {
+            return Err("同步引擎的队列深度固定为 1，更大的队列深度请使用 io_uring 引擎".to_string());
+        }
+        if self.time_based && self.runtime.is_none() {
+            return Err("按时间运行需要设置单项测试时间 (runtime)".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.read_mix) {
+            return Err(format!("读比例 {} 超出 0-1 范围", self.read_mix));
+        }
+        for &block_size in &self.block_sizes {
+            if block_size.checked_mul(self.threads as u64).is_none_or(|total| total > self.file_size) {
+                return Err(format!(
+                    "测试文件 {} 不足以让 {} 个线程各分到一个 {} 块",
+                    format_size(self.file_size), self.threads, format_size(block_size)
+                ));
+            }
+            if self.cache_modes.contains(&CacheMode::Direct) && !block_size.is_multiple_of(DIRECT_ALIGN) {
+                return Err(format!("O_DIRECT 模式要求块大小为 {} 的整数倍: {}", DIRECT_ALIGN, format_size(block_size)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查目标文件系统的可用空间，拒绝会把文件系统写满的测试文件大小
+    pub fn check_space(&self) -> Result<(), String> {
+        let dir = self.dir.canonicalize().map_err(|e| format!("无法访问测试路径 {}: {}", self.dir.display(), e))?;
+        let disks = Disks::new_with_refreshed_list();
+        let Some(disk) = disks
+            .list()
+            .iter()
+            .filter(|d| dir.starts_with(d.mount_point()))
+            .max_by_key(|d| d.mount_point().as_os_str().len())
+        else {
+            // 无法确定所在文件系统时不阻止测试
+            return Ok(());
+        };
+        let reserve = (disk.total_space() / 20).min(MAX_FREE_RESERVE);
+        let usable = disk.available_space().saturating_sub(reserve);
+        if self.file_size > usable {
+            let gb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+            return Err(format!(
+                "测试文件 {} 超出 {} 的安全可用空间 {:.1} GB（可用 {:.1} GB，需保留 {:.1} GB）",
+                format_size(self.file_size),
+                disk.mount_point().display(),
+                gb(usable),
+                gb(disk.available_space()),
+                gb(reserve)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 可供测试的已挂载文件系统
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub mount_point: PathBuf,
+    pub file_system: String,
+    /// 设备名，如 `/dev/vda1`
+    pub device: String,
+    pub total: u64,
+    pub available: u64,
+}
+
+/// 列出已挂载、可写且非虚拟的文件系统；同一设备的多个挂载点只保留最短的一个
+pub fn list_targets() -> Vec<Target> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut targets: Vec<Target> = disks
+        .list()
+        .iter()
+        .filter(|d| !d.is_read_only() && d.mount_point().is_dir())
+        .filter(|d| !VIRTUAL_FS.contains(&d.file_system().to_string_lossy().as_ref()))
+        .map(|d| Target {
+            mount_point: d.mount_point().to_path_buf(),
+            file_system: d.file_system().to_string_lossy().to_string(),
+            device: d.name().to_string_lossy().to_string(),
+            total: d.total_space(),
+            available: d.available_space(),
+        })
+        .collect();
+    targets.sort_by_key(|t| t.mount_point.as_os_str().len());
+    let mut seen = Vec::new();
+    targets.retain(|t| {
+        let duplicate = t.device.starts_with("/dev/") && seen.contains(&t.device);
+        seen.push(t.device.clone());
+        !duplicate && tempfile::tempfile_in(&t.mount_point).is_ok()
+    });
+    targets.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    targets
+}
+
+/// 测试文件守卫：以随机文件名创建在测试目录下，离开作用域（包括出错和 panic）时自动删除。
+/// 存续期间拦截 SIGINT/SIGTERM，让测试尽快结束并完成清理；再次收到信号则直接退出。
+struct TestFile {
+    file: NamedTempFile,
+    stop: StopSignal,
+}
+
+impl TestFile {
+    fn create(job: &IoJob) -> io::Result<TestFile> {
+        job.check_space().map_err(io::Error::other)?;
+        let file = tempfile::Builder::new().prefix(TEST_FILE_PREFIX).tempfile_in(&job.dir)?;
+        Ok(TestFile { file, stop: StopSignal::register()? })
+    }
+
+    fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// 收到中断信号后返回错误
+    fn check(&self) -> io::Result<()> {
+        if self.stop.flag().load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "测试被中断"));
+        }
+        Ok(())
+    }
+}
+
+/// 单项测试（模式 × 块大小 × 缓存模式）的结果
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub mode: Mode,
+    pub block_size: u64,
+    pub cache: CacheMode,
+    /// 请求 O_DIRECT 但文件系统不支持，实际以缓存IO运行
+    pub direct_fallback: bool,
+    /// 请求 io_uring 但系统不支持，实际以同步IO运行
+    pub engine_fallback: bool,
+    pub read_bytes: f64,
+    pub read_time: f64,
+    pub write_bytes: f64,
+    pub write_time: f64,
+    /// 单次读/写调用的延迟分布（不含最后的 fsync）
+    pub read_latency: LatencyHistogram,
+    pub write_latency: LatencyHistogram,
+    /// 每秒吞吐量采样 (MB/s)
+    pub samples: Vec<f64>,
+}
+
+/// 突发与持续吞吐量分析
+#[derive(Debug, Clone, Copy)]
+pub struct SteadyState {
+    /// 前四分之一采样中的峰值 (MB/s)
+    pub burst_mbps: f64,
+    /// 后半程采样的平均值 (MB/s)
+    pub sustained_mbps: f64,
+    /// 检测到降速时，吞吐量回落到持续水平的秒数
+    pub throttled_after: Option<usize>,
+}
+
+impl CaseResult {
+    pub fn mbps(&self) -> f64 {
+        (self.read_bytes + self.write_bytes) / (self.read_time + self.write_time).max(0.0001) / MB
+    }
+
+    pub fn iops(&self) -> f64 {
+        (self.read_bytes + self.write_bytes) / self.block_size as f64 / (self.read_time + self.write_time).max(0.0001)
+    }
+
+    /// 由每秒采样判断是否存在先突发后降速；采样过少时返回 `None`
+    pub fn steady_state(&self) -> Option<SteadyState> {
+        let samples = &self.samples;
+        if samples.len() < MIN_STEADY_SAMPLES {
+            return None;
+        }
+        let head = &samples[..samples.len() / 4];
+        let tail = &samples[samples.len() / 2..];
+        let burst_mbps = head.iter().copied().fold(0.0, f64::max);
+        let sustained_mbps = tail.iter().sum::<f64>() / tail.len() as f64;
+        let throttled_after = if sustained_mbps < burst_mbps * THROTTLE_RATIO {
+            // 最后一个明显高于持续水平的采样之后即进入持续阶段
+            let threshold = (burst_mbps + sustained_mbps) / 2.0;
+            samples.iter().rposition(|&s| s > threshold).map(|i| i + 1)
+        } else {
+            None
+        };
+        Some(SteadyState { burst_mbps, sustained_mbps, throttled_after })
+    }
+
+    fn case_label(&self) -> String {
+        format!("{} {}", self.mode.label(), format_size(self.block_size))
+    }
+
+    /// 带行标签的延迟摘要；混合读写分别给出读与写
+    pub fn latency_rows(&self) -> Vec<(String, LatencySummary)> {
+        let case = self.case_label();
+        let rows: Vec<_> = [("读", &self.read_latency), ("写", &self.write_latency)]
+            .into_iter()
+            .filter(|(_, h)| h.count() > 0)
+            .collect();
+        let split = rows.len() > 1;
+        rows.into_iter()
+            .map(|(op, h)| (if split { format!("{} {}", case, op) } else { case.clone() }, h.summary()))
+            .collect()
+    }
+}
+
+/// 按对齐要求分配的缓冲区，满足 O_DIRECT 的内存对齐
+struct AlignedBuf {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+// 缓冲区独占所有权，可在线程间转移
+unsafe impl Send for AlignedBuf {}
+
+impl AlignedBuf {
+    fn new(len: usize) -> io::Result<Self> {
+        let layout = Layout::from_size_align(len.max(1), DIRECT_ALIGN as usize)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("无效的缓冲区大小 {}: {}", len, e)))?;
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return Err(io::Error::new(io::ErrorKind::OutOfMemory, format!("分配 {} 字节测试缓冲区失败", len)));
+        }
+        Ok(Self { ptr, layout })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.layout.size()) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr, self.layout) }
+    }
+}
+
+/// 预先写满测试文件，避免读测试读到空洞，也避免多线程竞争扩展文件
+fn prepare_test_file(test_file: &TestFile, size: u64) -> io::Result<()> {
+    let chunk = 1024 * 1024;
+    let file = test_file.file.as_file();
+    let buf = vec![0u8; chunk];
+    let mut written = 0u64;
+    while written < size {
+        test_file.check()?;
+        let len = (size - written).min(chunk as u64) as usize;
+        file.write_all_at(&buf[..len], written)?;
+        written += len as u64;
+    }
+    file.sync_all()
+}
+
+/// 打开测试文件；O_DIRECT 不可用时降级为缓存IO，返回是否发生降级
+fn open_for_bench(path: &Path, o_direct: bool) -> io::Result<(File, bool)> {
+    if o_direct {
+        if let Ok(file) = OpenOptions::new().read(true).write(true).custom_flags(libc::O_DIRECT).open(path) {
+            return Ok((file, false));
+        }
+    }
+    Ok((OpenOptions::new().read(true).write(true).open(path)?, o_direct))
+}
+
+/// 单个工作线程的负载：按模式生成下一个操作的偏移与读写类型
+struct Workload {
+    mode: Mode,
+    block_size: u64,
+    blocks: u64,
+    start_offset: u64,
+    blocks_per_thread: u64,
+    read_mix: f64,
+    time_based: bool,
+    deadline: Option<Instant>,
+    issued: u64,
+    rng: ThreadRng,
+    /// 收到中断信号或其他线程出错时置位
+    stop: [Arc<AtomicBool>; 2],
+}
+
+impl Workload {
+    /// 下一个操作的 (偏移, 是否为读)；负载结束时返回 `None`
+    fn next_op(&mut self) -> Option<(u64, bool)> {
+        if !self.time_based && self.issued >= self.blocks_per_thread {
+            return None;
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) || self.stop.iter().any(|s| s.load(Ordering::Relaxed)) {
+            return None;
+        }
+        let offset = match self.mode {
+            // 按时间运行时顺序模式在自己的区间内循环
+            Mode::SeqRead | Mode::SeqWrite => self.start_offset + (self.issued % self.blocks_per_thread) * self.block_size,
+            Mode::RandRead | Mode::RandWrite | Mode::RandRW => self.rng.random_range(0..self.blocks) * self.block_size,
+        };
+        let is_read = match self.mode {
+            Mode::SeqRead | Mode::RandRead => true,
+            Mode::SeqWrite | Mode::RandWrite => false,
+            Mode::RandRW => self.rng.random_bool(self.read_mix),
+        };
+        self.issued += 1;
+        Some((offset, is_read))
+    }
+}
+
+/// 单个工作线程的统计
+#[derive(Default)]
+struct WorkerStats {
+    read_ops: u64,
+    write_ops: u64,
+    read_latency: LatencyHistogram,
+    write_latency: LatencyHistogram,
+}
+
+impl WorkerStats {
+    fn record(&mut self, is_read: bool, latency: Duration) {
+        if is_read {
+            self.read_ops += 1;
+            self.read_latency.record(latency);
+        } else {
+            self.write_ops += 1;
+            self.write_latency.record(latency);
+        }
+    }
+}
 
+/// 同步引擎：每个线程同一时刻只有一个请求在途
+fn run_sync(file: &File, workload: &mut Workload, progress: &AtomicU64) -> io::Result<WorkerStats> {
+    let block_size = workload.block_size;
+    let buf = AlignedBuf::new(block_size as usize)?;
+    let mut read_buf = AlignedBuf::new(block_size as usize)?;
+    let mut stats = WorkerStats::default();
+    while let Some((offset, is_read)) = workload.next_op() {
+        let op_start = Instant::now();
+        if is_read {
+            file.read_exact_at(read_buf.as_mut_slice(), offset)?;
+        } else {
+            file.write_all_at(buf.as_slice(), offset)?;
+        }
+        stats.record(is_read, op_start.elapsed());
+        progress.fetch_add(block_size, Ordering::Relaxed);
+    }
+    Ok(stats)
+}
+
+fn bench_rw(job: &IoJob, test_file: &TestFile, block_size: u64, mode: Mode, cache: CacheMode) -> io::Result<CaseResult> {
+    let blocks = job.file_size / block_size;
+    let threads = job.threads;
+    let read_mix = job.read_mix;
+    let runtime = job.runtime;
+    let time_based = job.time_based;
+    let iodepth = job.iodepth;
+    let o_direct = cache == CacheMode::Direct;
+    let testfile_path = test_file.path().to_path_buf();
+    // io_uring 可能被内核配置或 seccomp 禁用，此时回退为同步引擎
+    let use_uring = job.engine == IoEngine::Uring && uring::available();
+
+    // 主线程也参与同步，以便与工作线程同时开始采样
+    let barrier = Arc::new(Barrier::new(threads + 1));
+    let progress = Arc::new(AtomicU64::new(0));
+    let failed = Arc::new(AtomicBool::new(false));
     let mut handles = vec![];
-    for tid in 0..concurrency {
+    for tid in 0..threads {
         let barrier = barrier.clone();
-        let read_bytes = Arc::clone(&read_bytes);
-        let write_bytes = Arc::clone(&write_bytes);
-        let read_time = Arc::clone(&read_time);
-        let write_time = Arc::clone(&write_time);
-        let path = testfile_path.to_string();
-        let mode = mode;
-        let handle = thread::spawn(move || {
-            let mut openopt = OpenOptions::new();
-            openopt.read(true).write(true);
-            if o_direct {
-                openopt.custom_flags(libc::O_DIRECT);
-            }
-            let mut file = openopt.open(&path).unwrap_or_else(|_| {
-                // O_DIRECT 失败降级
-                let mut fallback = OpenOptions::new();
-                fallback.read(true).write(true);
-                fallback.open(&path).expect("线程无法打开文件")
-            });
-            let blocks_per_thread = blocks / concurrency;
-            let start_offset = tid * blocks_per_thread * block_size;
-            let mut rng = rand::rng();
-            let buf = vec![0u8; block_size];
-            let mut read_buf = vec![0u8; block_size];
+        let progress = progress.clone();
+        let stop = [failed.clone(), test_file.stop.flag()];
+        let path = testfile_path.clone();
+        let handle = thread::spawn(move || -> io::Result<(f64, WorkerStats, bool)> {
+            // 无论打开是否成功都要到达屏障，否则其他线程会一直等待
+            let opened = open_for_bench(&path, o_direct);
             barrier.wait();
+            let (file, fallback) = opened.inspect_err(|_| stop[0].store(true, Ordering::Relaxed))?;
+            let blocks_per_thread = blocks / threads as u64;
+            let mut workload = Workload {
+                mode,
+                block_size,
+                blocks,
+                start_offset: tid as u64 * blocks_per_thread * block_size,
+                blocks_per_thread,
+                read_mix,
+                time_based,
+                deadline: None,
+                issued: 0,
+                rng: rand::rng(),
+                stop: stop.clone(),
+            };
 
-            // Write/Read/混合
-            let mut wtime = 0.0;
-            let mut rtime = 0.0;
-            let mut wbytes = 0.0;
-            let mut rbytes = 0.0;
-            match mode {
-                Mode::SeqWrite => {
-                    let start = Instant::now();
-                    for i in 0..blocks_per_thread {
-                        let offset = (start_offset + i * block_size) as u64;
-                        file.seek(SeekFrom::Start(offset)).unwrap();
-                        file.write_all(&buf).unwrap();
-                    }
-                    file.sync_all().unwrap();
-                    wtime = start.elapsed().as_secs_f64();
-                    wbytes = (blocks_per_thread * block_size) as f64;
-                }
-                Mode::SeqRead => {
-                    let start = Instant::now();
-                    for i in 0..blocks_per_thread {
-                        let offset = (start_offset + i * block_size) as u64;
-                        file.seek(SeekFrom::Start(offset)).unwrap();
-                        file.read_exact(&mut read_buf).unwrap();
-                    }
-                    rtime = start.elapsed().as_secs_f64();
-                    rbytes = (blocks_per_thread * block_size) as f64;
-                }
-                Mode::RandWrite => {
-                    let start = Instant::now();
-                    for _ in 0..blocks_per_thread {
-                        let offset = rng.random_range(0..blocks) as u64 * block_size as u64;
-                        file.seek(SeekFrom::Start(offset)).unwrap();
-                        file.write_all(&buf).unwrap();
-                    }
-                    file.sync_all().unwrap();
-                    wtime = start.elapsed().as_secs_f64();
-                    wbytes = (blocks_per_thread * block_size) as f64;
-                }
-                Mode::RandRead => {
-                    let start = Instant::now();
-                    for _ in 0..blocks_per_thread {
-                        let offset = rng.random_range(0..blocks) as u64 * block_size as u64;
-                        file.seek(SeekFrom::Start(offset)).unwrap();
-                        file.read_exact(&mut read_buf).unwrap();
-                    }
-                    rtime = start.elapsed().as_secs_f64();
-                    rbytes = (blocks_per_thread * block_size) as f64;
-                }
-                Mode::RandRW => {
-                    let start = Instant::now();
-                    for _ in 0..blocks_per_thread {
-                        if rng.random_bool(rw_mix) {
-                            let offset = rng.random_range(0..blocks) as u64 * block_size as u64;
-                            file.seek(SeekFrom::Start(offset)).unwrap();
-                            file.read_exact(&mut read_buf).unwrap();
-                            rbytes += block_size as f64;
-                        } else {
-                            let offset = rng.random_range(0..blocks) as u64 * block_size as u64;
-                            file.seek(SeekFrom::Start(offset)).unwrap();
-                            file.write_all(&buf).unwrap();
-                            wbytes += block_size as f64;
-                        }
-                    }
-                    file.sync_all().unwrap();
-                    let elapsed = start.elapsed().as_secs_f64();
-                    // 按比例分配时间
-                    rtime = elapsed * rw_mix;
-                    wtime = elapsed * (1.0 - rw_mix);
-                }
+            let start = Instant::now();
+            workload.deadline = runtime.map(|r| start + r);
+            let stats = if use_uring {
+                uring::run(&file, &mut workload, iodepth, &progress)
+            } else {
+                run_sync(&file, &mut workload, &progress)
+            }
+            .inspect_err(|_| stop[0].store(true, Ordering::Relaxed))?;
+            if stats.write_ops > 0 {
+                file.sync_all()?;
             }
-            *write_time.lock().unwrap() += wtime;
-            *write_bytes.lock().unwrap() += wbytes;
-            *read_time.lock().unwrap() += rtime;
-            *read_bytes.lock().unwrap() += rbytes;
+            let elapsed = start.elapsed().as_secs_f64();
+            Ok((elapsed, stats, fallback))
         });
         handles.push(handle);
     }
+    barrier.wait();
+    let samples = sample_throughput(&progress, &handles);
+
+    let mut result = CaseResult {
+        mode,
+        block_size,
+        cache,
+        direct_fallback: false,
+        engine_fallback: job.engine == IoEngine::Uring && !use_uring,
+        read_bytes: 0.0,
+        read_time: 0.0,
+        write_bytes: 0.0,
+        write_time: 0.0,
+        read_latency: LatencyHistogram::new(),
+        write_latency: LatencyHistogram::new(),
+        samples,
+    };
+    let mut outcomes = Vec::new();
     for h in handles {
-        h.join().unwrap();
-    }
-
-    // 测试结束后删除文件
-    let _ = std::fs::remove_file(testfile_path);
-
-    let read_bytes = *read_bytes.lock().unwrap();
-    let write_bytes = *write_bytes.lock().unwrap();
-    let read_time = *read_time.lock().unwrap() / concurrency as f64;
-    let write_time = *write_time.lock().unwrap() / concurrency as f64;
-    (read_bytes, read_time, write_bytes, write_time)
-}
-
-// 已弃用
-// fn format_speed(bytes_per_sec: f64) -> String {
-//     if bytes_per_sec > 1024.0 * 1024.0 * 1024.0 {
-//         format!("{:.2} GB/s", bytes_per_sec / 1024.0 / 1024.0 / 1024.0)
-//     } else if bytes_per_sec > 1024.0 * 1024.0 {
-//         format!("{:.2} MB/s", bytes_per_sec / 1024.0 / 1024.0)
-//     } else if bytes_per_sec > 1024.0 {
-//         format!("{:.2} KB/s", bytes_per_sec / 1024.0)
-//     } else {
-//         format!("{:.2} B/s", bytes_per_sec)
-//     }
-// }
-
-// fn format_iops(iops: f64) -> String {
-//     if iops > 10_000.0 {
-//         format!("{:.1}k", iops / 1000.0)
-//     } else {
-//         format!("{:.0}", iops)
-//     }
-// }
-
-pub fn run_io_test() {
-    let concurrency = 4;
-    let modes = [
-        (Mode::SeqRead, "顺序读"),
-        (Mode::SeqWrite, "顺序写"),
-        (Mode::RandRead, "随机读"),
-        (Mode::RandWrite, "随机写"),
-        (Mode::RandRW, "混合读写(50%/50%)"),
+        outcomes.push(h.join().map_err(|_| io::Error::other("测试线程异常退出"))?);
+    }
+    test_file.check()?;
+    for outcome in outcomes {
+        let (elapsed, stats, fallback) = outcome?;
+        // 混合读写按实际操作数比例分配时间
+        let total_ops = (stats.read_ops + stats.write_ops).max(1) as f64;
+        result.read_bytes += (stats.read_ops * block_size) as f64;
+        result.read_time += elapsed * stats.read_ops as f64 / total_ops;
+        result.write_bytes += (stats.write_ops * block_size) as f64;
+        result.write_time += elapsed * stats.write_ops as f64 / total_ops;
+        result.direct_fallback |= o_direct && fallback;
+        result.read_latency.merge(&stats.read_latency);
+        result.write_latency.merge(&stats.write_latency);
+    }
+    // 各线程并行运行，时间取平均
+    result.read_time /= threads as f64;
+    result.write_time /= threads as f64;
+    Ok(result)
+}
+
+/// 按固定间隔采样已完成的字节数，直到所有工作线程结束；不足一个间隔的尾部丢弃
+fn sample_throughput<T>(progress: &AtomicU64, handles: &[JoinHandle<T>]) -> Vec<f64> {
+    let mut samples = Vec::new();
+    let mut last_bytes = 0;
+    let mut last_time = Instant::now();
+    while !handles.iter().all(|h| h.is_finished()) {
+        thread::sleep(POLL_INTERVAL);
+        let elapsed = last_time.elapsed();
+        if elapsed >= SAMPLE_INTERVAL {
+            let bytes = progress.load(Ordering::Relaxed);
+            samples.push((bytes - last_bytes) as f64 / elapsed.as_secs_f64() / MB);
+            last_bytes = bytes;
+            last_time = Instant::now();
+        }
+    }
+    samples
+}
+
+/// 按配置运行全部测试项，每完成一项回调一次；测试文件在返回前删除
+pub fn run_job(job: &IoJob, on_case: &mut dyn FnMut(&CaseResult)) -> io::Result<Vec<CaseResult>> {
+    let test_file = TestFile::create(job)?;
+    prepare_test_file(&test_file, job.file_size)?;
+    let mut results = Vec::new();
+    for &cache in &job.cache_modes {
+        for &mode in &job.modes {
+            for &block_size in &job.block_sizes {
+                let result = bench_rw(job, &test_file, block_size, mode, cache)?;
+                on_case(&result);
+                results.push(result);
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// 按缓存模式生成 模式 × 块大小 的吞吐量与 IOPS 表格
+pub fn result_tables(job: &IoJob, results: &[CaseResult]) -> Vec<ResultTable> {
+    let columns: Vec<String> = job.block_sizes.iter().map(|&bs| format_size(bs)).collect();
+    let mut tables = Vec::new();
+    for &cache in &job.cache_modes {
+        let title = |name: &str| format!("{} {}", cache.label(), name);
+        let mut bw_table = ResultTable::new(title("吞吐量"), "MB/s", columns.clone(), Direction::Higher);
+        let mut iops_table = ResultTable::new(title("IOPS"), "IOPS", columns.clone(), Direction::Higher);
+        for &mode in &job.modes {
+            let find = |bs: u64| results.iter().find(|r| r.cache == cache && r.mode == mode && r.block_size == bs);
+            bw_table.push_row(mode.label(), job.block_sizes.iter().map(|&bs| find(bs).map(|r| r.mbps())).collect());
+            iops_table.push_row(mode.label(), job.block_sizes.iter().map(|&bs| find(bs).map(|r| r.iops())).collect());
+        }
+        tables.push(bw_table);
+        tables.push(iops_table);
+        tables.push(latency_table(results, cache));
+
+        let steady: Vec<_> = results.iter().filter(|r| r.cache == cache).filter_map(|r| Some((r, r.steady_state()?))).collect();
+        if !steady.is_empty() {
+            let columns = vec!["突发".to_string(), "持续".to_string()];
+            let mut table = ResultTable::new(format!("{} 突发/持续吞吐量", cache.label()), "MB/s", columns, Direction::Higher);
+            for (r, s) in steady {
+                table.push_row(r.case_label(), vec![Some(s.burst_mbps), Some(s.sustained_mbps)]);
+            }
+            tables.push(table);
+        }
+    }
+    tables
+}
+
+/// 单个缓存模式下各 模式 × 块大小 的延迟分布表（微秒）
+fn latency_table(results: &[CaseResult], cache: CacheMode) -> ResultTable {
+    let columns = LatencySummary::COLUMNS.iter().map(|c| c.to_string()).collect();
+    let mut table = ResultTable::new(format!("{} 延迟", cache.label()), "us", columns, Direction::Lower);
+    for r in results.iter().filter(|r| r.cache == cache) {
+        for (label, summary) in r.latency_rows() {
+            table.push_row(label, summary.values().into_iter().map(Some).collect());
+        }
+    }
+    table
+}
+
+/// 生成文本结果表
+pub fn format_results(job: &IoJob, results: &[CaseResult]) -> String {
+    let mut out = String::new();
+    for &cache in &job.cache_modes {
+        out.push_str(&format!("\n================ {} ================\n", cache.label()));
+        out.push_str(&format!("{:<10}", "模式"));
+        for &bs in &job.block_sizes {
+            out.push_str(&format!(" | {:>24}", format!("{} (MB/s, IOPS)", format_size(bs))));
+        }
+        out.push('\n');
+        for &mode in &job.modes {
+            out.push_str(&format!("{:<10}", mode.label()));
+            for &bs in &job.block_sizes {
+                match results.iter().find(|r| r.cache == cache && r.mode == mode && r.block_size == bs) {
+                    Some(r) => out.push_str(&format!(" | {:>10.2} MB/s, {:>8.0}", r.mbps(), r.iops())),
+                    None => out.push_str(&format!(" | {:>24}", "-")),
+                }
+            }
+            out.push('\n');
+        }
+
+        out.push_str(&format!("\n{:<20}", "延迟 (µs)"));
+        for column in LatencySummary::COLUMNS {
+            out.push_str(&format!(" {:>10}", column));
+        }
+        out.push('\n');
+        for r in results.iter().filter(|r| r.cache == cache) {
+            for (label, summary) in r.latency_rows() {
+                out.push_str(&format!("{:<20}", label));
+                for value in summary.values() {
+                    out.push_str(&format!(" {:>10.1}", value));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    let steady: Vec<_> = results.iter().filter_map(|r| Some((r, r.steady_state()?))).collect();
+    if !steady.is_empty() {
+        out.push_str("\n突发/持续吞吐量 (MB/s):\n");
+        for (r, s) in steady {
+            out.push_str(&format!(
+                "[{}] {:<14} 突发 {:>9.2}  持续 {:>9.2}",
+                r.cache.label(), r.case_label(), s.burst_mbps, s.sustained_mbps
+            ));
+            if let Some(after) = s.throttled_after {
+                out.push_str(&format!("  ⚠️ 约 {} 秒后降速", after));
+            }
+            out.push('\n');
+            let samples: Vec<String> = r.samples.iter().map(|v| format!("{:.0}", v)).collect();
+            out.push_str(&format!("    每秒: {}\n", samples.join(" ")));
+        }
+    }
+    if results.iter().any(|r| r.direct_fallback) {
+        out.push_str("\n⚠️ 目标文件系统不支持 O_DIRECT，相关测试已回退为缓存IO。\n");
+    }
+    if results.iter().any(|r| r.engine_fallback) {
+        out.push_str("\n⚠️ 当前系统不支持 io_uring，测试已回退为同步IO。\n");
+    }
+    if let (true, Some(runtime)) = (job.time_based, job.runtime) {
+        out.push_str(&format!("\n按时间运行：每项测试持续 {} 秒。", runtime.as_secs()));
+    }
+    out.push_str(&format!(
+        "\n说明：{}引擎，{} 线程并发 × 队列深度 {}，测试文件 {}，混合读写中读占 {:.0}%；O_DIRECT为物理IO，缓存IO为系统缓存加速。\n",
+        job.engine.label(),
+        job.threads,
+        job.iodepth,
+        format_size(job.file_size),
+        job.read_mix * 100.0
+    ));
+    out
+}
+
+/// 多个测试目标的并排对比表：每种缓存模式一组吞吐量、IOPS 与 p99 延迟表，列为各目标
+pub fn comparison_tables(job: &IoJob, runs: &[(String, Vec<CaseResult>)]) -> Vec<ResultTable> {
+    let columns: Vec<String> = runs.iter().map(|(label, _)| label.clone()).collect();
+    type CaseMetric = fn(&CaseResult) -> f64;
+    let metrics: [(&str, &str, Direction, CaseMetric); 3] = [
+        ("吞吐量对比", "MB/s", Direction::Higher, CaseResult::mbps),
+        ("IOPS对比", "IOPS", Direction::Higher, CaseResult::iops),
+        ("p99延迟对比", "us", Direction::Lower, |r| r.latency_rows().iter().map(|(_, s)| s.p99).fold(0.0, f64::max)),
     ];
-    let o_directs = [(false, "缓存IO"), (true, "O_DIRECT(绕过缓存)")];
-    for &(o_direct, olabel) in &o_directs {
-        println!("\n================ {} ================", olabel);
-        for &(mode, mlabel) in &modes {
-            println!("\n--- {} ---", mlabel);
-            println!("Block Size |   4k (MB/s, IOPS)   |  64k (MB/s, IOPS)   | 512k (MB/s, IOPS) |   1m (MB/s, IOPS)");
-            println!("-----------|----------------------|---------------------|-------------------|-------------------");
-            print!("Result     |");
-            for &(size, _) in SIZES.iter() {
-                let (read_bytes, read_time, write_bytes, write_time) = bench_rw(size, mode, concurrency, o_direct, 0.5);
-                let mbps = (read_bytes + write_bytes) / (read_time + write_time).max(0.0001) / 1024.0 / 1024.0;
-                let iops = (read_bytes + write_bytes) / size as f64 / (read_time + write_time).max(0.0001);
-                print!(" {:>7.2} MB/s, {:>7.0} |", mbps, iops);
+    let mut tables = Vec::new();
+    for &cache in &job.cache_modes {
+        for (title, unit, direction, value) in metrics {
+            let mut table = ResultTable::new(format!("{} {}", cache.label(), title), unit, columns.clone(), direction);
+            for &mode in &job.modes {
+                for &bs in &job.block_sizes {
+                    let cells = runs
+                        .iter()
+                        .map(|(_, results)| {
+                            results.iter().find(|r| r.cache == cache && r.mode == mode && r.block_size == bs).map(value)
+                        })
+                        .collect();
+                    table.push_row(format!("{} {}", mode.label(), format_size(bs)), cells);
+                }
             }
-            println!();
+            tables.push(table);
         }
     }
-    println!("\n说明：每种模式均为多线程并发，单位已标注，O_DIRECT为物理IO，缓存IO为系统缓存加速。\n");
-    println!("按任意键返回...");
-    let _ = crossterm::event::read();
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_blocks_that_do_not_fit() {
+        let job = IoJob { file_size: 1024 * 1024, threads: 4, block_sizes: vec![256 * 1024], ..IoJob::default() };
+        assert!(job.validate().is_ok());
+        let job = IoJob { threads: 5, ..job };
+        assert!(job.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_overflowing_block_total() {
+        // 块大小乘以线程数溢出 u64 时不能绕过检查
+        let job = IoJob {
+            file_size: u64::MAX,
+            threads: 1 << 20,
+            block_sizes: vec![1 << 50],
+            cache_modes: vec![CacheMode::Cached],
+            ..IoJob::default()
+        };
+        assert!(job.validate().is_err());
+    }
 }
@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use io_uring::{opcode, types, IoUring};
+use super::{AlignedBuf, Workload, WorkerStats};
+
+/// 当前内核是否允许创建 io_uring（结果缓存）
+pub(super) fn available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| IoUring::new(2).is_ok())
+}
+
+/// io_uring 引擎：每个线程保持最多 `iodepth` 个请求在途
+///
+/// 出错后不再提交新请求，但会等待全部在途请求完成后才返回第一个错误，
+/// 内核不会读写已释放的缓冲区。
+pub(super) fn run(file: &File, workload: &mut Workload, iodepth: usize, progress: &AtomicU64) -> io::Result<WorkerStats> {
+    let block_size = workload.block_size;
+    // 每个队列槽位独占一个缓冲区，请求完成后槽位回收复用；先于 ring 声明，保证晚于 ring 释放
+    let mut bufs = (0..iodepth).map(|_| AlignedBuf::new(block_size as usize)).collect::<io::Result<Vec<_>>>()?;
+    let mut ring = IoUring::new(iodepth as u32)?;
+    let fd = types::Fd(file.as_raw_fd());
+    let mut inflight: Vec<Option<(Instant, bool)>> = vec![None; iodepth];
+    let mut free: Vec<usize> = (0..iodepth).rev().collect();
+    let mut stats = WorkerStats::default();
+    let mut exhausted = false;
+    let mut error: Option<io::Error> = None;
+
+    loop {
+        while error.is_none() && !exhausted && !free.is_empty() {
+            let Some((offset, is_read)) = workload.next_op() else {
+                exhausted = true;
+                break;
+            };
+            let slot = free.pop().unwrap_or_default();
+            let ptr = bufs[slot].as_mut_slice().as_mut_ptr();
+            let entry = if is_read {
+                opcode::Read::new(fd, ptr, block_size as u32).offset(offset).build()
+            } else {
+                opcode::Write::new(fd, ptr, block_size as u32).offset(offset).build()
+            };
+            // 缓冲区在请求完成（收到对应 CQE）前不会被释放或复用
+            if unsafe { ring.submission().push(&entry.user_data(slot as u64)) }.is_err() {
+                free.push(slot);
+                error = Some(io::Error::other("io_uring 提交队列已满"));
+                break;
+            }
+            inflight[slot] = Some((Instant::now(), is_read));
+        }
+        if free.len() == iodepth {
+            break;
+        }
+
+        match ring.submit_and_wait(1) {
+            Ok(_) => {}
+            // Ctrl+C 等信号打断等待时继续回收
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                // 无法确认在途请求已完成，宁可泄漏缓冲区也不能交还给分配器
+                std::mem::forget(bufs);
+                return Err(error.unwrap_or(e));
+            }
+        }
+        let completed: Vec<(u64, i32)> = ring.completion().map(|cqe| (cqe.user_data(), cqe.result())).collect();
+        for (slot, res) in completed {
+            let slot = slot as usize;
+            let Some((issued_at, is_read)) = inflight[slot].take() else {
+                continue;
+            };
+            free.push(slot);
+            if res < 0 {
+                error.get_or_insert_with(|| io::Error::from_raw_os_error(-res));
+            } else if res as u64 != block_size {
+                error.get_or_insert_with(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, format!("io_uring 请求不完整: {}/{} 字节", res, block_size))
+                });
+            } else {
+                stats.record(is_read, issued_at.elapsed());
+                progress.fetch_add(block_size, Ordering::Relaxed);
+            }
+        }
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(stats),
+    }
+}
@@ -0,0 +1,260 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use rand::Rng;
+use crate::models::{Direction, ResultTable};
+use std::path::Path;
+use super::{AlignedBuf, IoJob, TestFile, DIRECT_ALIGN};
+
+/// 校验块大小，与 O_DIRECT 对齐粒度一致
+const BLOCK_SIZE: usize = DIRECT_ALIGN as usize;
+const WORDS: usize = BLOCK_SIZE / 8;
+/// 每次读写的块数（1MiB）
+const CHUNK_BLOCKS: usize = 256;
+const MAGIC: u64 = 0x4f4e_454b_4559_5646; // "ONEKEYVF"
+/// 块头：magic, seed, 块号, 校验和；块尾：seed, 块号
+const HEAD_WORDS: usize = 4;
+const TAIL_WORDS: usize = 2;
+/// 最多记录的异常块偏移数
+const MAX_SAMPLES: usize = 10;
+
+/// 异常块的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    /// 块头块尾一致但内容校验和不符
+    Corrupt,
+    /// 块头与块尾来自不同的写入
+    Torn,
+    /// 读到了其他块号的数据
+    Misplaced,
+    /// 没有本次写入的数据（写入丢失或未落盘）
+    Stale,
+}
+
+impl BlockError {
+    pub const ALL: [BlockError; 4] = [BlockError::Corrupt, BlockError::Torn, BlockError::Misplaced, BlockError::Stale];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BlockError::Corrupt => "损坏",
+            BlockError::Torn => "撕裂",
+            BlockError::Misplaced => "错位",
+            BlockError::Stale => "丢失",
+        }
+    }
+}
+
+/// 一轮读回校验的结果
+#[derive(Debug, Clone)]
+pub struct VerifyPass {
+    pub label: String,
+    pub checked: u64,
+    /// 与 [`BlockError::ALL`] 顺序一致的计数
+    pub errors: [u64; 4],
+    /// 前若干个异常块的 (偏移, 类型)
+    pub samples: Vec<(u64, BlockError)>,
+}
+
+impl VerifyPass {
+    pub fn error_count(&self) -> u64 {
+        self.errors.iter().sum()
+    }
+}
+
+/// 数据校验报告
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub blocks: u64,
+    pub passes: Vec<VerifyPass>,
+    /// 未执行的读回方式及原因
+    pub notes: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.passes.iter().all(|p| p.error_count() == 0)
+    }
+
+    pub fn table(&self) -> ResultTable {
+        let mut columns = vec!["已校验".to_string()];
+        columns.extend(BlockError::ALL.iter().map(|e| e.label().to_string()));
+        let mut table = ResultTable::new("数据校验", "块", columns, Direction::Lower);
+        for pass in &self.passes {
+            let mut cells = vec![Some(pass.checked as f64)];
+            cells.extend(pass.errors.iter().map(|&n| Some(n as f64)));
+            table.push_row(pass.label.clone(), cells);
+        }
+        table
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = format!("\n================ 数据校验 ({} 个 4k 块) ================\n", self.blocks);
+        for pass in &self.passes {
+            let icon = if pass.error_count() == 0 { "✅" } else { "❌" };
+            out.push_str(&format!("{} {:<20} 已校验 {}", icon, pass.label, pass.checked));
+            for (kind, count) in BlockError::ALL.iter().zip(pass.errors) {
+                out.push_str(&format!("  {} {}", kind.label(), count));
+            }
+            out.push('\n');
+            for (offset, kind) in &pass.samples {
+                out.push_str(&format!("    偏移 {:#x}: {}\n", offset, kind.label()));
+            }
+        }
+        for note in &self.notes {
+            out.push_str(&format!("⚠️ {}\n", note));
+        }
+        out
+    }
+}
+
+/// 由种子和块号生成确定性的伪随机序列 (splitmix64)
+fn pattern(seed: u64, index: u64) -> impl FnMut() -> u64 {
+    let mut state = seed ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn checksum(words: impl Iterator<Item = u64>) -> u64 {
+    words.fold(0xcbf2_9ce4_8422_2325, |h, w| (h ^ w).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn word(block: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(block[i * 8..i * 8 + 8].try_into().unwrap_or_default())
+}
+
+fn fill_block(block: &mut [u8], seed: u64, index: u64) {
+    let mut next = pattern(seed, index);
+    let payload: Vec<u64> = (HEAD_WORDS..WORDS - TAIL_WORDS).map(|_| next()).collect();
+    let mut words = vec![MAGIC, seed, index, checksum(payload.iter().copied())];
+    words.extend(payload);
+    words.extend([seed, index]);
+    for (dst, w) in block.chunks_exact_mut(8).zip(words) {
+        dst.copy_from_slice(&w.to_le_bytes());
+    }
+}
+
+fn check_block(block: &[u8], seed: u64, index: u64) -> Option<BlockError> {
+    let head_ok = word(block, 0) == MAGIC && word(block, 1) == seed;
+    let tail_ok = word(block, WORDS - 2) == seed;
+    match (head_ok, tail_ok) {
+        (false, false) => return Some(BlockError::Stale),
+        (true, false) | (false, true) => return Some(BlockError::Torn),
+        (true, true) => {}
+    }
+    let (head_index, tail_index) = (word(block, 2), word(block, WORDS - 1));
+    if head_index != tail_index {
+        return Some(BlockError::Torn);
+    }
+    if head_index != index {
+        return Some(BlockError::Misplaced);
+    }
+    if checksum((HEAD_WORDS..WORDS - TAIL_WORDS).map(|i| word(block, i))) != word(block, 3) {
+        return Some(BlockError::Corrupt);
+    }
+    None
+}
+
+fn open(path: &Path, direct: bool, write: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(write);
+    if direct {
+        options.custom_flags(libc::O_DIRECT);
+    }
+    options.open(path)
+}
+
+/// 优先以 O_DIRECT 写入全部块，返回是否实际使用了 O_DIRECT
+fn write_pattern(test_file: &TestFile, seed: u64, blocks: u64) -> io::Result<bool> {
+    let (file, direct) = match open(test_file.path(), true, true) {
+        Ok(file) => (file, true),
+        Err(_) => (open(test_file.path(), false, true)?, false),
+    };
+    let mut buf = AlignedBuf::new(CHUNK_BLOCKS * BLOCK_SIZE)?;
+    let mut index = 0;
+    while index < blocks {
+        test_file.check()?;
+        let count = (blocks - index).min(CHUNK_BLOCKS as u64) as usize;
+        let chunk = &mut buf.as_mut_slice()[..count * BLOCK_SIZE];
+        for (i, block) in chunk.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+            fill_block(block, seed, index + i as u64);
+        }
+        file.write_all_at(chunk, index * BLOCK_SIZE as u64)?;
+        index += count as u64;
+    }
+    file.sync_all()?;
+    Ok(direct)
+}
+
+fn read_back(test_file: &TestFile, file: &File, label: &str, seed: u64, blocks: u64) -> io::Result<VerifyPass> {
+    let mut pass = VerifyPass { label: label.to_string(), checked: 0, errors: [0; 4], samples: Vec::new() };
+    let mut buf = AlignedBuf::new(CHUNK_BLOCKS * BLOCK_SIZE)?;
+    let mut index = 0;
+    while index < blocks {
+        test_file.check()?;
+        let count = (blocks - index).min(CHUNK_BLOCKS as u64) as usize;
+        let chunk = &mut buf.as_mut_slice()[..count * BLOCK_SIZE];
+        file.read_exact_at(chunk, index * BLOCK_SIZE as u64)?;
+        for (i, block) in chunk.chunks_exact(BLOCK_SIZE).enumerate() {
+            let block_index = index + i as u64;
+            if let Some(kind) = check_block(block, seed, block_index) {
+                let slot = BlockError::ALL.iter().position(|&k| k == kind).unwrap_or_default();
+                pass.errors[slot] += 1;
+                if pass.samples.len() < MAX_SAMPLES {
+                    pass.samples.push((block_index * BLOCK_SIZE as u64, kind));
+                }
+            }
+        }
+        pass.checked += count as u64;
+        index += count as u64;
+    }
+    Ok(pass)
+}
+
+/// 让后续读取绕过页缓存：root 下清空系统页缓存，否则仅丢弃测试文件的缓存页
+fn drop_caches(file: &File) -> io::Result<&'static str> {
+    file.sync_all()?;
+    if unsafe { libc::geteuid() } == 0 {
+        std::fs::write("/proc/sys/vm/drop_caches", "1")?;
+        return Ok("drop_caches");
+    }
+    match unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) } {
+        0 => Ok("posix_fadvise"),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+
+/// 写入带块号与校验和的伪随机数据，再以缓存IO、O_DIRECT 和清空缓存后三种方式读回校验
+pub fn run(job: &IoJob) -> io::Result<VerifyReport> {
+    let blocks = job.file_size / BLOCK_SIZE as u64;
+    // 每次运行使用不同的种子，以便识别残留的旧数据
+    let seed = rand::rng().random::<u64>();
+    let mut report = VerifyReport { blocks, passes: Vec::new(), notes: Vec::new() };
+    let test_file = TestFile::create(job)?;
+
+    if !write_pattern(&test_file, seed, blocks)? {
+        report.notes.push("目标文件系统不支持 O_DIRECT，写入经过页缓存".to_string());
+    }
+
+    let cached = open(test_file.path(), false, false)?;
+    report.passes.push(read_back(&test_file, &cached, "缓存读回", seed, blocks)?);
+
+    match open(test_file.path(), true, false) {
+        Ok(direct) => report.passes.push(read_back(&test_file, &direct, "O_DIRECT读回", seed, blocks)?),
+        Err(e) => report.notes.push(format!("跳过 O_DIRECT 读回: {}", e)),
+    }
+
+    match drop_caches(&cached) {
+        Ok(method) => {
+            let label = format!("清空缓存后读回 ({})", method);
+            report.passes.push(read_back(&test_file, &cached, &label, seed, blocks)?);
+        }
+        Err(e) => report.notes.push(format!("跳过清空缓存后读回: {}", e)),
+    }
+    Ok(report)
+}
@@ -0,0 +1,389 @@
+use std::fs;
+use std::hint::black_box;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::seq::SliceRandom;
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+use crate::models::{Direction, Metric, ResultTable};
+use crate::params::format_size;
+
+const MB: f64 = 1024.0 * 1024.0;
+/// 指针追逐按缓存行步进，避免同一行内的访问命中缓存
+const CACHE_LINE: usize = 64;
+const LINE_WORDS: usize = CACHE_LINE / std::mem::size_of::<usize>();
+/// 延迟测试的最小工作集
+const MIN_WORKING_SET: usize = 16 * 1024;
+/// 每轮指针追逐的步数
+const CHASE_STEPS: usize = 1 << 20;
+/// 多线程带宽测试中每线程缓冲区至少为末级缓存的倍数，避免各线程的分片落在缓存里
+const LLC_MULTIPLE: usize = 4;
+
+/// 带宽测试操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandwidthOp {
+    Read,
+    Write,
+    Copy,
+}
+
+impl BandwidthOp {
+    pub const ALL: [BandwidthOp; 3] = [BandwidthOp::Read, BandwidthOp::Write, BandwidthOp::Copy];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BandwidthOp::Read => "顺序读",
+            BandwidthOp::Write => "顺序写",
+            BandwidthOp::Copy => "复制",
+        }
+    }
+}
+
+/// 一次内存测试的配置
+#[derive(Debug, Clone)]
+pub struct MemJob {
+    /// 带宽测试缓冲区大小；多线程时平分，但每线程不小于末级缓存的 [`LLC_MULTIPLE`] 倍。
+    /// 实际大小受可用内存限制，见 [`buffer_sizes`]
+    pub buffer_size: usize,
+    pub threads: usize,
+    /// 延迟测试的最大工作集，从 16k 起按 2 倍递增，同样受可用内存限制
+    pub max_working_set: usize,
+    /// 每项带宽测试的运行时间
+    pub duration: Duration,
+}
+
+/// 带宽测试结果 (MB/s)
+#[derive(Debug, Clone)]
+pub struct BandwidthResult {
+    pub op: BandwidthOp,
+    pub single: f64,
+    pub multi: f64,
+}
+
+/// 某个工作集下的随机访问延迟
+#[derive(Debug, Clone)]
+pub struct LatencyResult {
+    pub working_set: usize,
+    pub latency_ns: f64,
+    /// 工作集可完全放入的最小缓存级别，如 `L2`；超过末级缓存时为 `DRAM`
+    pub level: String,
+}
+
+/// 完整结果
+#[derive(Debug, Clone)]
+pub struct MemReport {
+    pub threads: usize,
+    /// 单线程带宽测试实际使用的缓冲区大小
+    pub single_buffer: usize,
+    /// 多线程带宽测试中每线程的缓冲区大小
+    pub thread_buffer: usize,
+    /// 因可用内存不足而缩小的测试规模说明
+    pub notes: Vec<String>,
+    pub bandwidth: Vec<BandwidthResult>,
+    pub latency: Vec<LatencyResult>,
+    /// 测试期间新增的主缺页次数，大于 0 说明有内存被换出到磁盘
+    pub major_faults: u64,
+    pub swap_used_before: u64,
+    pub swap_used_after: u64,
+}
+
+impl MemReport {
+    /// 测试期间发生换页
+    pub fn swapping(&self) -> bool {
+        self.major_faults > 0 || self.swap_used_after > self.swap_used_before
+    }
+
+    pub fn metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::new("major_faults", self.major_faults as f64, "次", Direction::Lower),
+            Metric::new("swap_used", self.swap_used_after as f64 / MB, "MB", Direction::Lower),
+        ]
+    }
+
+    pub fn tables(&self) -> Vec<ResultTable> {
+        let columns = vec!["单线程".to_string(), format!("{} 线程", self.threads)];
+        let mut bandwidth = ResultTable::new("内存带宽", "MB/s", columns, Direction::Higher);
+        for r in &self.bandwidth {
+            bandwidth.push_row(r.op.label(), vec![Some(r.single), Some(r.multi)]);
+        }
+        let mut latency = ResultTable::new("内存随机访问延迟", "ns", vec!["延迟".to_string()], Direction::Lower);
+        for r in &self.latency {
+            latency.push_row(format!("{} ({})", format_size(r.working_set as u64), r.level), vec![Some(r.latency_ns)]);
+        }
+        vec![bandwidth, latency]
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = format!(
+            "\n带宽测试缓冲区: 单线程 {:.1} MB，多线程每线程 {:.1} MB\n",
+            self.single_buffer as f64 / MB,
+            self.thread_buffer as f64 / MB
+        );
+        for note in &self.notes {
+            out.push_str(&format!("⚠️ {}\n", note));
+        }
+        out.push_str(&format!("{:<8} | {:>14} | {:>14}\n", "带宽", "单线程", format!("{} 线程", self.threads)));
+        for r in &self.bandwidth {
+            out.push_str(&format!("{:<8} | {:>9.0} MB/s | {:>9.0} MB/s\n", r.op.label(), r.single, r.multi));
+        }
+        out.push_str(&format!("\n{:<14} | {:>10}\n", "工作集", "延迟"));
+        for r in &self.latency {
+            out.push_str(&format!(
+                "{:<14} | {:>7.1} ns\n",
+                format!("{} ({})", format_size(r.working_set as u64), r.level),
+                r.latency_ns
+            ));
+        }
+        if self.swapping() {
+            out.push_str(&format!(
+                "\n⚠️ 测试期间发生换页：主缺页 {} 次，交换分区使用 {:.0} MB → {:.0} MB，内存可能被超售或换出到磁盘。\n",
+                self.major_faults,
+                self.swap_used_before as f64 / MB,
+                self.swap_used_after as f64 / MB
+            ));
+        }
+        out.push_str("\n说明：复制按读写字节总和计算带宽；延迟为随机指针追逐的单次访问耗时。\n");
+        out
+    }
+}
+
+/// 从 sysfs 读取 CPU0 的数据缓存大小，返回 (级别, 字节数)，按级别升序
+fn cache_levels() -> Vec<(u32, usize)> {
+    let mut levels = Vec::new();
+    let Ok(entries) = fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+        return levels;
+    };
+    for entry in entries.flatten() {
+        let read = |name: &str| fs::read_to_string(entry.path().join(name)).map(|s| s.trim().to_string()).unwrap_or_default();
+        if read("type") == "Instruction" {
+            continue;
+        }
+        let (Ok(level), Some(size)) = (read("level").parse::<u32>(), parse_cache_size(&read("size"))) else {
+            continue;
+        };
+        levels.push((level, size));
+    }
+    levels.sort();
+    levels
+}
+
+/// 解析 sysfs 中的缓存大小，如 `48K`、`30M`
+fn parse_cache_size(raw: &str) -> Option<usize> {
+    let (digits, multiplier) = match raw.chars().last()? {
+        'K' => (&raw[..raw.len() - 1], 1024),
+        'M' => (&raw[..raw.len() - 1], 1024 * 1024),
+        _ => (raw, 1),
+    };
+    digits.parse::<usize>().ok().map(|v| v * multiplier)
+}
+
+fn level_label(levels: &[(u32, usize)], working_set: usize) -> String {
+    levels
+        .iter()
+        .find(|(_, size)| working_set <= *size)
+        .map(|(level, _)| format!("L{}", level))
+        .unwrap_or_else(|| "DRAM".to_string())
+}
+
+/// 当前进程累计的主缺页次数
+fn major_faults() -> u64 {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } == 0 {
+        usage.ru_majflt as u64
+    } else {
+        0
+    }
+}
+
+fn available_memory() -> usize {
+    System::new_with_specifics(RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram())).available_memory() as usize
+}
+
+/// 测试可占用的内存上限：可用内存的一半
+fn memory_budget() -> usize {
+    available_memory() / 2
+}
+
+/// 单线程缓冲区与多线程每线程缓冲区的大小
+///
+/// 多线程平分后不足末级缓存 `llc` 的若干倍时放大；复制测试需要两份缓冲区，
+/// 两种测试各自占用的内存合计都不超过 `budget`。
+fn buffer_sizes(buffer_size: usize, threads: usize, llc: usize, budget: usize) -> (usize, usize) {
+    let single = buffer_size.min(budget / 2);
+    let share = (buffer_size / threads).max(llc * LLC_MULTIPLE);
+    (single, share.min(budget / 2 / threads))
+}
+
+/// 延迟测试的最大工作集：链表占用与工作集相同，打乱顺序的索引另占 1/8
+fn latency_limit(max_working_set: usize, budget: usize) -> usize {
+    max_working_set.min(budget / 9 * 8)
+}
+
+fn swap_used() -> u64 {
+    System::new_with_specifics(RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_swap())).used_swap()
+}
+
+/// 对缓冲区执行一遍操作，返回本次涉及的字节数
+fn pass(op: BandwidthOp, src: &mut [u64], dst: &mut [u64], round: u64) -> usize {
+    let bytes = std::mem::size_of_val(src);
+    match op {
+        BandwidthOp::Read => {
+            let sum = src.iter().fold(0u64, |acc, &v| acc.wrapping_add(v));
+            black_box(sum);
+            bytes
+        }
+        BandwidthOp::Write => {
+            src.fill(round);
+            black_box(&src);
+            bytes
+        }
+        BandwidthOp::Copy => {
+            dst.copy_from_slice(src);
+            black_box(&dst);
+            bytes * 2
+        }
+    }
+}
+
+/// 以 `threads` 个线程、每线程 `per_thread` 字节运行带宽测试，返回总带宽 (MB/s)
+fn measure_bandwidth(op: BandwidthOp, threads: usize, per_thread: usize, duration: Duration) -> f64 {
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let words = (per_thread / 8).max(1);
+                // 非零初始化，确保页面在计时前已实际分配
+                let mut src = vec![1u64; words];
+                let mut dst = if op == BandwidthOp::Copy { vec![1u64; words] } else { Vec::new() };
+                pass(op, &mut src, &mut dst, 0);
+                barrier.wait();
+                let start = Instant::now();
+                let mut bytes = 0usize;
+                let mut round = 1;
+                while start.elapsed() < duration {
+                    bytes += pass(op, &mut src, &mut dst, round);
+                    round += 1;
+                }
+                bytes as f64 / start.elapsed().as_secs_f64() / MB
+            })
+        })
+        .collect();
+    handles.into_iter().filter_map(|h| h.join().ok()).sum()
+}
+
+/// 在 `working_set` 字节的随机环形链表上做指针追逐，返回单次访问的平均纳秒数
+fn measure_latency(working_set: usize) -> f64 {
+    let lines = (working_set / CACHE_LINE).max(2);
+    let mut order: Vec<usize> = (0..lines).collect();
+    order.shuffle(&mut rand::rng());
+    let mut chain = vec![0usize; lines * LINE_WORDS];
+    for (i, &line) in order.iter().enumerate() {
+        chain[line * LINE_WORDS] = order[(i + 1) % lines] * LINE_WORDS;
+    }
+
+    let chase = |steps: usize| {
+        let mut pos = order[0] * LINE_WORDS;
+        for _ in 0..steps {
+            pos = chain[pos];
+        }
+        black_box(pos)
+    };
+    // 预热一圈，让工作集进入缓存
+    chase(lines.min(CHASE_STEPS));
+    let start = Instant::now();
+    chase(CHASE_STEPS);
+    start.elapsed().as_nanos() as f64 / CHASE_STEPS as f64
+}
+
+/// 依次运行带宽与延迟测试；`on_progress` 接收每完成一项的描述
+pub fn run(job: &MemJob, on_progress: &mut dyn FnMut(&str)) -> MemReport {
+    let threads = job.threads.max(1);
+    let faults_before = major_faults();
+    let swap_used_before = swap_used();
+    let levels = cache_levels();
+    let budget = memory_budget();
+    let llc = levels.last().map_or(0, |(_, size)| *size);
+    let (single_buffer, thread_buffer) = buffer_sizes(job.buffer_size, threads, llc, budget);
+    let max_working_set = latency_limit(job.max_working_set, budget);
+    let mut notes = Vec::new();
+    if single_buffer < job.buffer_size {
+        notes.push(format!(
+            "可用内存不足，单线程带宽测试缓冲区由 {:.0} MB 缩小为 {:.1} MB",
+            job.buffer_size as f64 / MB,
+            single_buffer as f64 / MB
+        ));
+    }
+    if max_working_set < job.max_working_set {
+        notes.push(format!(
+            "可用内存不足，延迟测试最大工作集由 {:.0} MB 缩小为 {:.1} MB",
+            job.max_working_set as f64 / MB,
+            max_working_set as f64 / MB
+        ));
+    }
+    for note in &notes {
+        on_progress(note);
+    }
+
+    let bandwidth = BandwidthOp::ALL
+        .iter()
+        .map(|&op| {
+            let single = measure_bandwidth(op, 1, single_buffer, job.duration);
+            let multi = measure_bandwidth(op, threads, thread_buffer, job.duration);
+            on_progress(&format!("{}: 单线程 {:.0} MB/s，{} 线程 {:.0} MB/s", op.label(), single, threads, multi));
+            BandwidthResult { op, single, multi }
+        })
+        .collect();
+
+    let mut latency = Vec::new();
+    let mut working_set = MIN_WORKING_SET;
+    while working_set <= max_working_set {
+        let result = LatencyResult {
+            working_set,
+            latency_ns: measure_latency(working_set),
+            level: level_label(&levels, working_set),
+        };
+        on_progress(&format!("{} ({}): {:.1} ns", format_size(working_set as u64), result.level, result.latency_ns));
+        latency.push(result);
+        working_set *= 2;
+    }
+
+    MemReport {
+        threads,
+        single_buffer,
+        thread_buffer,
+        notes,
+        bandwidth,
+        latency,
+        major_faults: major_faults().saturating_sub(faults_before),
+        swap_used_before,
+        swap_used_after: swap_used(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: usize = 1024 * 1024;
+
+    #[test]
+    fn buffers_are_clamped_to_the_memory_budget() {
+        // 内存充足：单线程用原值，多线程平分后不足末级缓存 4 倍时放大
+        assert_eq!(buffer_sizes(256 * MIB, 8, 32 * MIB, 64 * 1024 * MIB), (256 * MIB, 128 * MIB));
+        assert_eq!(buffer_sizes(1024 * MIB, 2, 8 * MIB, 64 * 1024 * MIB), (1024 * MIB, 512 * MIB));
+        // 内存不足：复制测试的两份缓冲区合计不超过预算
+        let (single, per_thread) = buffer_sizes(8192 * MIB, 4, 32 * MIB, 1024 * MIB);
+        assert_eq!(single, 512 * MIB);
+        assert_eq!(per_thread, 128 * MIB);
+        assert!(per_thread * 4 * 2 <= 1024 * MIB);
+    }
+
+    #[test]
+    fn latency_working_set_leaves_room_for_the_index() {
+        assert_eq!(latency_limit(256 * MIB, 64 * 1024 * MIB), 256 * MIB);
+        let limit = latency_limit(4096 * MIB, 900 * MIB);
+        assert_eq!(limit, 800 * MIB);
+        assert!(limit + limit / 8 <= 900 * MIB);
+    }
+}
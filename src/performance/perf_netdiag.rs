@@ -0,0 +1,484 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::{Direction, Family, ResultTable};
+use crate::netprobe::{self, EchoSocket, IcmpKind, ProbeId};
+use crate::sysinfo;
+
+pub const DEFAULT_TARGETS: &str = "www.cloudflare.com:443,www.google.com:443,github.com:443,www.baidu.com:443";
+const ICMP_PROBES: usize = 3;
+const SMALL_PAYLOAD: usize = 56;
+/// 每个尺寸的探测次数，避免偶发丢包被误判为报文过大
+const PMTU_TRIES: usize = 2;
+/// 用于检查默认路由的公网地址，只查询路由表，不发送报文
+const ROUTE_CHECK_V4: Ipv4Addr = Ipv4Addr::new(1, 1, 1, 1);
+const ROUTE_CHECK_V6: Ipv6Addr = Ipv6Addr::new(0x2606, 0x4700, 0x4700, 0, 0, 0, 0, 0x1111);
+/// IPv6 连接耗时超过 IPv4 的该倍数时提示
+const SLOW_FAMILY_RATIO: f64 = 1.5;
+
+#[derive(Debug, Clone)]
+pub struct DiagTarget {
+    pub host: String,
+    pub port: u16,
+}
+
+impl DiagTarget {
+    pub fn label(&self) -> String {
+        if self.host.contains(':') { format!("[{}]:{}", self.host, self.port) } else { format!("{}:{}", self.host, self.port) }
+    }
+}
+
+/// 解析逗号分隔的 `主机:端口` 列表，IPv6 地址写作 `[::1]:443`
+pub fn parse_targets(raw: &str) -> Result<Vec<DiagTarget>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let (host, port) = entry.rsplit_once(':').ok_or_else(|| format!("'{}' 格式应为 主机:端口", entry))?;
+            let port = port.parse().map_err(|_| format!("'{}' 不是有效的端口", port))?;
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            if host.is_empty() {
+                return Err(format!("'{}' 缺少主机名", entry));
+            }
+            Ok(DiagTarget { host: host.to_string(), port })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagJob {
+    pub families: Vec<Family>,
+    /// 每个目标的 TCP 连接次数
+    pub count: u32,
+    pub timeout: Duration,
+    /// 是否探测路径 MTU
+    pub mtu: bool,
+}
+
+/// 本机协议族不可用的原因；可用时返回 `None`
+pub fn family_unavailable(family: Family) -> Option<String> {
+    let probe = match family {
+        Family::V4 => IpAddr::V4(ROUTE_CHECK_V4),
+        Family::V6 if !sysinfo::check_ipv6_support() => return Some("本机未启用 IPv6".to_string()),
+        Family::V6 => IpAddr::V6(ROUTE_CHECK_V6),
+    };
+    netprobe::route_mtu(probe).err().map(|e| format!("没有 {} 默认路由 ({})", family.label(), e))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TcpStats {
+    /// 成功建立连接的耗时 (ms)
+    pub samples: Vec<f64>,
+    /// 被 RST 拒绝的次数：主机可达但端口未开放
+    pub refused: usize,
+    pub failed: usize,
+    pub last_error: Option<String>,
+}
+
+impl TcpStats {
+    pub fn avg(&self) -> Option<f64> {
+        (!self.samples.is_empty()).then(|| self.samples.iter().sum::<f64>() / self.samples.len() as f64)
+    }
+
+    pub fn reachable(&self) -> bool {
+        !self.samples.is_empty() || self.refused > 0
+    }
+
+    pub fn describe(&self) -> String {
+        let attempts = self.samples.len() + self.refused + self.failed;
+        match self.avg() {
+            Some(avg) => format!("{:.1} ms ({}/{})", avg, self.samples.len(), attempts),
+            None if self.refused > 0 => "端口拒绝".to_string(),
+            None => format!("失败: {}", self.last_error.as_deref().unwrap_or("-")),
+        }
+    }
+}
+
+fn tcp_stats(addr: SocketAddr, job: &DiagJob) -> TcpStats {
+    let mut stats = TcpStats::default();
+    for _ in 0..job.count {
+        let start = Instant::now();
+        match TcpStream::connect_timeout(&addr, job.timeout) {
+            Ok(_) => stats.samples.push(start.elapsed().as_secs_f64() * 1000.0),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => stats.refused += 1,
+            Err(e) => {
+                stats.failed += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+    stats
+}
+
+/// 路径 MTU 探测结果
+#[derive(Debug, Clone)]
+pub struct Pmtu {
+    pub mtu: u32,
+    /// 本机出口的 MTU
+    pub link_mtu: u32,
+    /// 途中路由器通告的 MTU（ICMP 报文过大）
+    pub reported: Option<u32>,
+    /// 使用原始套接字，能够收到 ICMP 差错报文
+    pub raw: bool,
+}
+
+impl Pmtu {
+    /// 大包被静默丢弃且没有收到报文过大通知
+    pub fn blackhole(&self) -> bool {
+        self.mtu < self.link_mtu && self.reported.is_none() && self.raw
+    }
+}
+
+enum Echo {
+    Reply(f64),
+    TooBig(Option<u32>),
+    Lost,
+}
+
+/// 在同一 ICMP 套接字上依次发送 Echo 请求
+struct Pinger {
+    sock: EchoSocket,
+    addr: IpAddr,
+    timeout: Duration,
+    seq: u16,
+    buf: Vec<u8>,
+}
+
+impl Pinger {
+    fn echo(&mut self, payload: usize) -> io::Result<Echo> {
+        self.seq = self.seq.wrapping_add(1);
+        match self.sock.send(self.addr, self.seq, payload) {
+            Err(e) if e.raw_os_error() == Some(libc::EMSGSIZE) => return Ok(Echo::TooBig(None)),
+            result => result?,
+        }
+        let sent = Instant::now();
+        loop {
+            let remaining = self.timeout.saturating_sub(sent.elapsed());
+            if remaining.is_zero() {
+                return Ok(Echo::Lost);
+            }
+            netprobe::poll(&mut [self.sock.pollfd()], remaining)?;
+            while let Some(message) = self.sock.recv_message(&mut self.buf)? {
+                if !matches!(message.probe, ProbeId::Echo { seq, .. } if seq == self.seq) {
+                    continue;
+                }
+                match message.kind {
+                    IcmpKind::EchoReply if message.from == self.addr => {
+                        return Ok(Echo::Reply(sent.elapsed().as_secs_f64() * 1000.0));
+                    }
+                    IcmpKind::PacketTooBig(mtu) => return Ok(Echo::TooBig(Some(mtu))),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn overhead(&self) -> u32 {
+        if self.addr.is_ipv6() { 48 } else { 28 }
+    }
+
+    /// 指定大小（含 IP 头）的报文能否到达目标
+    fn fits(&mut self, size: u32, reported: &mut Option<u32>) -> io::Result<bool> {
+        for _ in 0..PMTU_TRIES {
+            match self.echo((size - self.overhead()) as usize)? {
+                Echo::Reply(_) => return Ok(true),
+                Echo::TooBig(mtu) => {
+                    *reported = reported.or(mtu);
+                    return Ok(false);
+                }
+                Echo::Lost => {}
+            }
+        }
+        Ok(false)
+    }
+
+    /// 在已知可达的小包与出口 MTU 之间二分查找
+    fn discover_pmtu(&mut self) -> io::Result<Pmtu> {
+        let link_mtu = netprobe::route_mtu(self.addr)?;
+        self.sock.set_pmtu_probe()?;
+        let mut reported = None;
+        let mut pmtu = Pmtu { mtu: link_mtu, link_mtu, reported: None, raw: self.sock.is_raw() };
+        if self.fits(link_mtu, &mut reported)? {
+            return Ok(pmtu);
+        }
+        let (mut lo, mut hi) = (self.overhead() + SMALL_PAYLOAD as u32, link_mtu);
+        // 路由器通告的 MTU 通常就是答案，先行验证
+        if let Some(mtu) = reported.filter(|m| *m > lo && *m < hi) {
+            if self.fits(mtu, &mut reported)? { lo = mtu } else { hi = mtu }
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.fits(mid, &mut reported)? { lo = mid } else { hi = mid }
+        }
+        pmtu.mtu = lo;
+        pmtu.reported = reported;
+        Ok(pmtu)
+    }
+}
+
+/// 路径 MTU 探测结果；未启用或 ICMP 不可达时为 `None`
+type PmtuOutcome = Option<Result<Pmtu, String>>;
+/// 单个协议族的诊断结果；错误为跳过原因
+pub type FamilyDiag = Result<Probes, String>;
+
+/// 单个协议族的探测结果
+#[derive(Debug, Clone)]
+pub struct Probes {
+    pub addr: IpAddr,
+    pub tcp: TcpStats,
+    /// ICMP Echo 的往返时间；无法创建 ICMP 套接字时为错误
+    pub icmp: Result<Vec<f64>, String>,
+    pub pmtu: PmtuOutcome,
+}
+
+impl Probes {
+    /// TCP 可达但 ICMP Echo 无响应
+    pub fn icmp_blocked(&self) -> bool {
+        self.tcp.reachable() && self.icmp.as_ref().is_ok_and(|rtts| rtts.is_empty())
+    }
+}
+
+fn probe_icmp(addr: IpAddr, job: &DiagJob) -> (Result<Vec<f64>, String>, PmtuOutcome) {
+    let sock = match EchoSocket::open(addr.is_ipv6()) {
+        Ok(sock) => sock,
+        Err(e) => return (Err(e.to_string()), None),
+    };
+    let mut pinger = Pinger { sock, addr, timeout: job.timeout, seq: 0, buf: vec![0u8; 65536] };
+    let mut rtts = Vec::new();
+    for _ in 0..ICMP_PROBES {
+        match pinger.echo(SMALL_PAYLOAD) {
+            Ok(Echo::Reply(rtt)) => rtts.push(rtt),
+            Ok(_) => {}
+            Err(e) => return (Err(e.to_string()), None),
+        }
+    }
+    let pmtu = (job.mtu && !rtts.is_empty()).then(|| pinger.discover_pmtu().map_err(|e| e.to_string()));
+    (Ok(rtts), pmtu)
+}
+
+fn resolve(host: &str, port: u16, family: Family) -> Result<IpAddr, String> {
+    (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("解析失败: {}", e))?
+        .map(|a| a.ip())
+        .find(|ip| ip.is_ipv6() == (family == Family::V6))
+        .ok_or_else(|| format!("没有 {} 地址", family.label()))
+}
+
+fn diagnose(target: &DiagTarget, family: Family, job: &DiagJob) -> FamilyDiag {
+    let addr = resolve(&target.host, target.port, family)?;
+    let tcp = tcp_stats(SocketAddr::new(addr, target.port), job);
+    let (icmp, pmtu) = probe_icmp(addr, job);
+    Ok(Probes { addr, tcp, icmp, pmtu })
+}
+
+#[derive(Debug, Clone)]
+pub struct TargetDiag {
+    pub target: DiagTarget,
+    /// 按 [`DiagJob::families`] 的顺序排列
+    pub families: Vec<(Family, FamilyDiag)>,
+}
+
+impl TargetDiag {
+    fn probes(&self, family: Family) -> Option<&Probes> {
+        self.families.iter().find(|(f, _)| *f == family).and_then(|(_, p)| p.as_ref().ok())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagReport {
+    pub families: Vec<Family>,
+    /// 本机不可用的协议族及原因
+    pub unavailable: Vec<(Family, String)>,
+    pub targets: Vec<TargetDiag>,
+}
+
+impl DiagReport {
+    fn all_probes(&self) -> impl Iterator<Item = (&TargetDiag, Family, &Probes)> {
+        self.targets
+            .iter()
+            .flat_map(|t| t.families.iter().filter_map(move |(f, p)| p.as_ref().ok().map(|p| (t, *f, p))))
+    }
+
+    pub fn any_reachable(&self) -> bool {
+        self.all_probes().any(|(_, _, p)| p.tcp.reachable())
+    }
+
+    /// 需要关注的问题：PMTU 黑洞、TCP 不可达或 IPv4/IPv6 可达性不一致
+    pub fn has_problems(&self) -> bool {
+        self.all_probes().any(|(_, _, p)| {
+            !p.tcp.reachable() || p.pmtu.as_ref().is_some_and(|r| r.as_ref().is_ok_and(Pmtu::blackhole))
+        }) || self.targets.iter().any(|t| self.family_gap(t).is_some())
+    }
+
+    /// 两个协议族只有一个可达时返回可达的一方
+    fn family_gap(&self, target: &TargetDiag) -> Option<Family> {
+        let v4 = target.probes(Family::V4)?.tcp.reachable();
+        let v6 = target.probes(Family::V6)?.tcp.reachable();
+        match (v4, v6) {
+            (true, false) => Some(Family::V4),
+            (false, true) => Some(Family::V6),
+            _ => None,
+        }
+    }
+
+    fn findings(&self) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .unavailable
+            .iter()
+            .map(|(family, reason)| format!("ℹ️ {} 不可用: {}", family.label(), reason))
+            .collect();
+        for (target, family, probes) in self.all_probes() {
+            let name = format!("{} [{}]", target.target.label(), family.label());
+            match &probes.pmtu {
+                Some(Ok(pmtu)) if pmtu.blackhole() => out.push(format!(
+                    "⚠️ {} 路径 MTU {} 小于出口 MTU {}，且未收到 ICMP 报文过大通知，疑似 PMTU 黑洞",
+                    name, pmtu.mtu, pmtu.link_mtu
+                )),
+                Some(Ok(pmtu)) if pmtu.mtu < pmtu.link_mtu => out.push(format!(
+                    "ℹ️ {} 路径 MTU {} 小于出口 MTU {}{}",
+                    name,
+                    pmtu.mtu,
+                    pmtu.link_mtu,
+                    pmtu.reported.map_or(String::new(), |m| format!("（路由器通告 {}）", m))
+                )),
+                _ => {}
+            }
+            if probes.icmp_blocked() {
+                out.push(format!("ℹ️ {} TCP 可达但 ICMP 无响应，ICMP 可能被屏蔽", name));
+            }
+        }
+        let tested: Vec<_> = self.all_probes().filter(|(_, _, p)| p.tcp.reachable() && p.icmp.is_ok()).collect();
+        if tested.len() > 1 && tested.iter().all(|(_, _, p)| p.icmp_blocked()) {
+            out.push("⚠️ 所有目标均无 ICMP 响应，本机防火墙或上游网络可能屏蔽了 ICMP".to_string());
+        }
+        for target in &self.targets {
+            if let Some(reachable) = self.family_gap(target) {
+                let other = if reachable == Family::V4 { Family::V6 } else { Family::V4 };
+                out.push(format!("⚠️ {}: {} 可达，{} 不可达", target.target.label(), reachable.label(), other.label()));
+                continue;
+            }
+            let avg = |f| target.probes(f).and_then(|p| p.tcp.avg());
+            if let (Some(v4), Some(v6)) = (avg(Family::V4), avg(Family::V6)) {
+                let (slow, fast, ratio) = if v6 > v4 { (Family::V6, Family::V4, v6 / v4) } else { (Family::V4, Family::V6, v4 / v6) };
+                if ratio > SLOW_FAMILY_RATIO {
+                    out.push(format!(
+                        "ℹ️ {}: {} 连接耗时为 {} 的 {:.1} 倍",
+                        target.target.label(), slow.label(), fast.label(), ratio
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = format!(
+            "{:<28} | {:<4} | {:<39} | {:<18} | {:<10} | {}\n",
+            "目标", "协议", "地址", "TCP 连接", "ICMP", "路径 MTU"
+        );
+        for target in &self.targets {
+            for (family, probes) in &target.families {
+                let probes = match probes {
+                    Ok(probes) => probes,
+                    Err(reason) => {
+                        out.push_str(&format!("{:<28} | {:<4} | {}\n", target.target.label(), family.label(), reason));
+                        continue;
+                    }
+                };
+                let icmp = match &probes.icmp {
+                    Ok(rtts) if rtts.is_empty() => "无响应".to_string(),
+                    Ok(rtts) => format!("{:.1} ms", rtts.iter().sum::<f64>() / rtts.len() as f64),
+                    Err(_) => "无法测试".to_string(),
+                };
+                let pmtu = match &probes.pmtu {
+                    Some(Ok(pmtu)) => pmtu.mtu.to_string(),
+                    Some(Err(e)) => format!("失败: {}", e),
+                    None => "-".to_string(),
+                };
+                out.push_str(&format!(
+                    "{:<28} | {:<4} | {:<39} | {:<18} | {:<10} | {}\n",
+                    target.target.label(),
+                    family.label(),
+                    probes.addr,
+                    probes.tcp.describe(),
+                    icmp,
+                    pmtu
+                ));
+            }
+        }
+        let icmp_errors: Vec<String> = self
+            .all_probes()
+            .filter_map(|(_, _, p)| p.icmp.as_ref().err().cloned())
+            .collect();
+        if let Some(e) = icmp_errors.first() {
+            out.push_str(&format!("ICMP 探测不可用: {}\n", e));
+        }
+        let findings = self.findings();
+        if !findings.is_empty() {
+            out.push('\n');
+            for finding in findings {
+                out.push_str(&finding);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn tables(&self) -> Vec<ResultTable> {
+        let columns: Vec<String> = self.families.iter().map(|f| f.label().to_string()).collect();
+        let mut connect = ResultTable::new("TCP 连接", "ms", columns.clone(), Direction::Lower);
+        let mut mtu = ResultTable::new("路径 MTU", "bytes", columns, Direction::Higher);
+        for target in &self.targets {
+            let cells = |f: &dyn Fn(&Probes) -> Option<f64>| {
+                self.families.iter().map(|family| target.probes(*family).and_then(f)).collect()
+            };
+            connect.push_row(target.target.label(), cells(&|p| p.tcp.avg()));
+            mtu.push_row(
+                target.target.label(),
+                cells(&|p| p.pmtu.as_ref().and_then(|r| r.as_ref().ok()).map(|m| m.mtu as f64)),
+            );
+        }
+        vec![connect, mtu]
+    }
+}
+
+/// 并发诊断全部目标，`on_done` 按完成顺序接收每个目标与协议族的结果
+pub fn run(targets: &[DiagTarget], job: &DiagJob, on_done: &mut dyn FnMut(&DiagTarget, Family, &FamilyDiag)) -> DiagReport {
+    let unavailable: Vec<(Family, String)> = job
+        .families
+        .iter()
+        .filter_map(|&f| family_unavailable(f).map(|reason| (f, reason)))
+        .collect();
+    let mut results: Vec<TargetDiag> = targets
+        .iter()
+        .map(|t| TargetDiag { target: t.clone(), families: Vec::new() })
+        .collect();
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for (index, target) in targets.iter().enumerate() {
+            for &family in &job.families {
+                let tx = tx.clone();
+                let skipped = unavailable.iter().find(|(f, _)| *f == family).map(|(_, reason)| reason.clone());
+                scope.spawn(move || {
+                    let result = match skipped {
+                        Some(reason) => Err(reason),
+                        None => diagnose(target, family, job),
+                    };
+                    let _ = tx.send((index, family, result));
+                });
+            }
+        }
+        drop(tx);
+        for (index, family, result) in rx {
+            on_done(&targets[index], family, &result);
+            results[index].families.push((family, result));
+        }
+    });
+    for target in &mut results {
+        target.families.sort_by_key(|(family, _)| job.families.iter().position(|f| f == family));
+    }
+    DiagReport { families: job.families.clone(), unavailable, targets: results }
+}
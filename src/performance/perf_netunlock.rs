@@ -1,3 +1,598 @@
-pub fn run_netunlock_test() {
-    println!("网络解锁测试功能待实现");
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use ureq::{Agent, Response};
+use crate::models::{Direction, Family, ResultTable};
+
+/// 内置规则，可用 `--rules` 指定的 JSON 文件替换
+pub const BUILTIN_RULES: &str = include_str!("perf_netunlock/rules.json");
+/// 响应体最多读取的字节数
+const MAX_BODY: u64 = 2 * 1024 * 1024;
+
+/// 规则集：每个服务由若干请求步骤组成，按顺序执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    /// 未单独指定 User-Agent 的请求使用的默认值
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    pub services: Vec<ServiceRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceRule {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// 一次请求及其响应的判定规则，按顺序取第一个匹配的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub request: RequestSpec,
+    pub outcomes: Vec<Outcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequestSpec {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// 默认不跟随重定向，以便按 Location 判定
+    #[serde(default)]
+    pub follow_redirects: bool,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// 响应匹配条件：各字段同时满足才算匹配，列表字段满足其一即可，未填写的字段不参与判断
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Matcher {
+    pub status: Vec<u16>,
+    pub body_contains: Vec<String>,
+    /// 响应体不得包含其中任何一项
+    pub body_lacks: Vec<String>,
+    pub location_contains: Vec<String>,
+    /// 响应头（名称不区分大小写）需包含对应子串
+    pub headers: BTreeMap<String, String>,
+}
+
+/// 判定结果；`Next` 表示继续执行下一步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Unlocked,
+    Partial,
+    Blocked,
+    Next,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Outcome {
+    #[serde(default)]
+    pub when: Matcher,
+    pub result: Action,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub region: Option<Extract>,
+}
+
+/// 从响应体或指定响应头中截取 `after` 与 `before` 之间的文本作为地区
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Extract {
+    #[serde(default)]
+    pub header: Option<String>,
+    pub after: String,
+    pub before: String,
+}
+
+impl RuleSet {
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_RULES).expect("内置解锁规则无效")
+    }
+
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let rules: RuleSet = serde_json::from_str(json).map_err(|e| format!("规则格式错误: {}", e))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("读取规则文件 {} 失败: {}", path.display(), e))?;
+        Self::parse(&json)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.services.is_empty() {
+            return Err("规则中没有任何服务".to_string());
+        }
+        for service in &self.services {
+            if service.steps.is_empty() {
+                return Err(format!("{}: 至少需要一个请求步骤", service.name));
+            }
+            for step in &service.steps {
+                let url = &step.request.url;
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(format!("{}: '{}' 不是 http(s) 地址", service.name, url));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 仅保留名称匹配（不区分大小写）的服务，返回未找到的名称
+    pub fn retain(&mut self, names: &[String]) -> Vec<String> {
+        let missing = names
+            .iter()
+            .filter(|n| !self.services.iter().any(|s| s.name.eq_ignore_ascii_case(n)))
+            .cloned()
+            .collect();
+        self.services.retain(|s| names.iter().any(|n| s.name.eq_ignore_ascii_case(n)));
+        missing
+    }
+}
+
+/// 只返回指定协议族地址的解析器，使请求强制走 IPv4 或 IPv6
+struct FamilyResolver(Family);
+
+impl ureq::Resolver for FamilyResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        let addrs: Vec<SocketAddr> = netloc
+            .to_socket_addrs()?
+            .filter(|a| a.is_ipv6() == (self.0 == Family::V6))
+            .collect();
+        if addrs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} 没有 {} 地址", netloc, self.0.label())));
+        }
+        Ok(addrs)
+    }
+}
+
+/// 单个服务在一个协议族上的检测结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Unlocked,
+    Partial,
+    Blocked,
+    /// 响应未匹配任何规则
+    Unknown,
+    /// 请求失败
+    Failed(String),
+}
+
+impl Verdict {
+    fn from_action(action: Action) -> Self {
+        match action {
+            Action::Unlocked => Verdict::Unlocked,
+            Action::Partial => Verdict::Partial,
+            Action::Blocked | Action::Next => Verdict::Blocked,
+        }
+    }
+
+    /// 写入结果表的分值：解锁 1，部分解锁 0.5，不支持 0
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            Verdict::Unlocked => Some(1.0),
+            Verdict::Partial => Some(0.5),
+            Verdict::Blocked => Some(0.0),
+            Verdict::Unknown | Verdict::Failed(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub verdict: Verdict,
+    pub region: Option<String>,
+    pub note: Option<String>,
+}
+
+impl CheckResult {
+    fn failed(error: String) -> Self {
+        CheckResult { verdict: Verdict::Failed(error), region: None, note: None }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = match &self.verdict {
+            Verdict::Unlocked => "✅ 解锁".to_string(),
+            Verdict::Partial => "🟡 部分解锁".to_string(),
+            Verdict::Blocked => "❌ 不支持".to_string(),
+            Verdict::Unknown => "❔ 未知".to_string(),
+            Verdict::Failed(e) => format!("⚠️ 失败: {}", e),
+        };
+        if let Some(region) = &self.region {
+            text.push_str(&format!(" ({})", region));
+        }
+        if let Some(note) = &self.note {
+            text.push_str(&format!(" {}", note));
+        }
+        text
+    }
+}
+
+/// 用于匹配的响应内容
+struct Fetched {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Fetched {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    fn from_response(response: Response) -> Result<Self, String> {
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| response.header(&name).map(|v| (name.clone(), v.to_string())))
+            .collect();
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_BODY)
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("读取响应失败: {}", e))?;
+        Ok(Fetched { status, headers, body: String::from_utf8_lossy(&bytes).into_owned() })
+    }
+}
+
+impl Matcher {
+    fn matches(&self, fetched: &Fetched) -> bool {
+        let location = fetched.header("location").unwrap_or("");
+        (self.status.is_empty() || self.status.contains(&fetched.status))
+            && (self.body_contains.is_empty() || self.body_contains.iter().any(|s| fetched.body.contains(s.as_str())))
+            && !self.body_lacks.iter().any(|s| fetched.body.contains(s.as_str()))
+            && (self.location_contains.is_empty() || self.location_contains.iter().any(|s| location.contains(s.as_str())))
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| fetched.header(name).is_some_and(|v| v.contains(value.as_str())))
+    }
+}
+
+impl Extract {
+    fn apply(&self, fetched: &Fetched) -> Option<String> {
+        let source = match &self.header {
+            Some(name) => fetched.header(name)?,
+            None => &fetched.body,
+        };
+        let start = source.find(&self.after)? + self.after.len();
+        let rest = &source[start..];
+        let end = rest.find(&self.before).unwrap_or(rest.len());
+        let region = rest[..end].trim();
+        (!region.is_empty() && region.len() <= 16).then(|| region.to_uppercase())
+    }
+}
+
+fn agent(family: Family, timeout: Duration, follow_redirects: bool) -> Agent {
+    ureq::AgentBuilder::new()
+        .resolver(FamilyResolver(family))
+        .timeout(timeout)
+        .redirects(if follow_redirects { 5 } else { 0 })
+        .build()
+}
+
+fn fetch(agent: &Agent, spec: &RequestSpec, user_agent: Option<&str>) -> Result<Fetched, String> {
+    let mut request = agent.request(&spec.method, &spec.url);
+    if let Some(ua) = user_agent {
+        request = request.set("User-Agent", ua);
+    }
+    for (name, value) in &spec.headers {
+        request = request.set(name, value);
+    }
+    let response = match &spec.body {
+        Some(body) => request.send_string(body),
+        None => request.call(),
+    };
+    match response {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => Fetched::from_response(response),
+        Err(ureq::Error::Transport(t)) => Err(describe(&t)),
+    }
+}
+
+/// 精简的传输错误描述，不重复请求地址
+fn describe(t: &ureq::Transport) -> String {
+    use std::error::Error;
+    t.source()
+        .map(|e| e.to_string())
+        .or_else(|| t.message().map(str::to_string))
+        .unwrap_or_else(|| t.kind().to_string())
+}
+
+/// 在一个协议族上依次执行服务的请求步骤
+pub fn check_service(rules: &RuleSet, service: &ServiceRule, family: Family, timeout: Duration) -> CheckResult {
+    let agents = [agent(family, timeout, false), agent(family, timeout, true)];
+    let mut last_status = None;
+    for step in &service.steps {
+        let agent = &agents[step.request.follow_redirects as usize];
+        let fetched = match fetch(agent, &step.request, rules.user_agent.as_deref()) {
+            Ok(fetched) => fetched,
+            Err(e) => return CheckResult::failed(e),
+        };
+        last_status = Some(fetched.status);
+        let Some(outcome) = step.outcomes.iter().find(|o| o.when.matches(&fetched)) else {
+            break;
+        };
+        if outcome.result == Action::Next {
+            continue;
+        }
+        return CheckResult {
+            verdict: Verdict::from_action(outcome.result),
+            region: outcome.region.as_ref().and_then(|r| r.apply(&fetched)),
+            note: outcome.note.clone(),
+        };
+    }
+    CheckResult {
+        verdict: Verdict::Unknown,
+        region: None,
+        note: last_status.map(|status| format!("HTTP {}", status)),
+    }
+}
+
+/// 一个服务在各协议族上的结果，顺序与 `families` 一致
+#[derive(Debug, Clone)]
+pub struct ServiceReport {
+    pub name: String,
+    pub results: Vec<(Family, CheckResult)>,
+}
+
+/// 并发检测全部服务与协议族，`on_done` 按完成顺序接收结果
+pub fn run_checks(
+    rules: &RuleSet,
+    families: &[Family],
+    timeout: Duration,
+    on_done: &mut dyn FnMut(&str, Family, &CheckResult),
+) -> Vec<ServiceReport> {
+    let mut reports: Vec<ServiceReport> = rules
+        .services
+        .iter()
+        .map(|s| ServiceReport { name: s.name.clone(), results: Vec::new() })
+        .collect();
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for (index, service) in rules.services.iter().enumerate() {
+            for &family in families {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let _ = tx.send((index, family, check_service(rules, service, family, timeout)));
+                });
+            }
+        }
+        drop(tx);
+        for (index, family, result) in rx {
+            on_done(&reports[index].name, family, &result);
+            reports[index].results.push((family, result));
+        }
+    });
+    for report in &mut reports {
+        report.results.sort_by_key(|(family, _)| families.iter().position(|f| f == family));
+    }
+    reports
+}
+
+pub fn format_reports(reports: &[ServiceReport], families: &[Family]) -> String {
+    let mut out = format!("\n{:<18}", "服务");
+    for family in families {
+        out.push_str(&format!(" | {:<28}", family.label()));
+    }
+    out.push('\n');
+    for report in reports {
+        out.push_str(&format!("{:<18}", report.name));
+        for (_, result) in &report.results {
+            out.push_str(&format!(" | {:<28}", result.describe()));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 解锁结果表，分值见 [`Verdict::score`]
+pub fn result_table(reports: &[ServiceReport], families: &[Family]) -> ResultTable {
+    let columns = families.iter().map(|f| f.label().to_string()).collect();
+    let mut table = ResultTable::new("流媒体解锁", "", columns, Direction::Higher);
+    for report in reports {
+        table.push_row(report.name.clone(), report.results.iter().map(|(_, r)| r.verdict.score()).collect());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// 按路径返回固定响应的本地 HTTP 服务：(路径, 状态行, 额外响应头, 响应体)
+    fn mock(routes: &'static [(&'static str, &'static str, &'static str, &'static str)]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line).unwrap() == 0 || line.trim_end().is_empty() {
+                        break;
+                    }
+                }
+                let (status, headers, body) = routes
+                    .iter()
+                    .find(|(p, ..)| *p == path)
+                    .map(|(_, status, headers, body)| (*status, *headers, *body))
+                    .unwrap_or(("404 Not Found", "", ""));
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        base
+    }
+
+    /// 单个服务的规则，`steps` 中的 `{base}` 替换为模拟服务地址
+    fn check(base: &str, steps: &str) -> CheckResult {
+        let json = format!(r#"{{"services": [{{"name": "Mock", "steps": {}}}]}}"#, steps.replace("{base}", base));
+        let rules = RuleSet::parse(&json).unwrap();
+        check_service(&rules, &rules.services[0], Family::V4, Duration::from_secs(5))
+    }
+
+    const ROUTES: &[(&str, &str, &str, &str)] = &[
+        ("/ok", "200 OK", "X-Country: jp\r\n", r#"{"country":"us","plan":"full"}"#),
+        ("/limited", "200 OK", "", r#"{"country":"de","plan":"limited"}"#),
+        ("/moved", "302 Found", "Location: /unavailable\r\n", ""),
+        ("/error", "500 Internal Server Error", "", "oops"),
+    ];
+
+    #[test]
+    fn status_and_body_matchers_with_region_from_body() {
+        let base = mock(ROUTES);
+        let steps = r#"[{"request": {"url": "{base}/ok"}, "outcomes": [
+            {"when": {"status": [403]}, "result": "blocked"},
+            {"when": {"status": [200], "body_contains": ["nope", "\"plan\""], "body_lacks": ["limited"]},
+             "result": "unlocked", "region": {"after": "\"country\":\"", "before": "\""}}
+        ]}]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Unlocked);
+        assert_eq!(result.region.as_deref(), Some("US"));
+
+        // body_lacks 排除后落到下一条判定
+        let steps = r#"[{"request": {"url": "{base}/limited"}, "outcomes": [
+            {"when": {"body_lacks": ["limited"]}, "result": "unlocked"},
+            {"when": {"body_contains": ["limited"]}, "result": "partial", "note": "受限"}
+        ]}]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Partial);
+        assert_eq!(result.note.as_deref(), Some("受限"));
+    }
+
+    #[test]
+    fn location_and_header_matchers() {
+        let base = mock(ROUTES);
+        let steps = r#"[{"request": {"url": "{base}/moved"}, "outcomes": [
+            {"when": {"location_contains": ["available/"]}, "result": "unlocked"},
+            {"when": {"status": [302], "location_contains": ["unavailable"]}, "result": "blocked"}
+        ]}]"#;
+        assert_eq!(check(&base, steps).verdict, Verdict::Blocked);
+
+        let steps = r#"[{"request": {"url": "{base}/ok"}, "outcomes": [
+            {"when": {"headers": {"x-country": "us"}}, "result": "blocked"},
+            {"when": {"headers": {"X-COUNTRY": "jp"}}, "result": "unlocked",
+             "region": {"header": "x-country", "after": "", "before": ";"}}
+        ]}]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Unlocked);
+        assert_eq!(result.region.as_deref(), Some("JP"));
+    }
+
+    #[test]
+    fn next_continues_with_the_following_step() {
+        let base = mock(ROUTES);
+        let steps = r#"[
+            {"request": {"url": "{base}/missing"}, "outcomes": [
+                {"when": {"status": [404]}, "result": "next"},
+                {"result": "unlocked"}
+            ]},
+            {"request": {"url": "{base}/limited", "method": "POST", "body": "{}"}, "outcomes": [
+                {"when": {"status": [200]}, "result": "partial",
+                 "region": {"after": "\"country\":\"", "before": "\""}}
+            ]}
+        ]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Partial);
+        assert_eq!(result.region.as_deref(), Some("DE"));
+
+        // 最后一步仍为 next 时没有可用的判定
+        let steps = r#"[{"request": {"url": "{base}/missing"}, "outcomes": [{"result": "next"}]}]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Unknown);
+        assert_eq!(result.note.as_deref(), Some("HTTP 404"));
+    }
+
+    #[test]
+    fn unmatched_response_is_unknown_and_transport_error_is_failed() {
+        let base = mock(ROUTES);
+        let steps = r#"[{"request": {"url": "{base}/error"}, "outcomes": [
+            {"when": {"status": [200]}, "result": "unlocked"}
+        ]}]"#;
+        let result = check(&base, steps);
+        assert_eq!(result.verdict, Verdict::Unknown);
+        assert_eq!(result.note.as_deref(), Some("HTTP 500"));
+
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let steps = r#"[{"request": {"url": "{base}/ok"}, "outcomes": [{"result": "unlocked"}]}]"#;
+        let result = check(&format!("http://{}", closed), steps);
+        assert!(matches!(result.verdict, Verdict::Failed(_)), "{:?}", result.verdict);
+        assert_eq!(result.verdict.score(), None);
+    }
+
+    #[test]
+    fn chatgpt_rule_blocks_unsupported_regions() {
+        const TRACE: &[(&str, &str, &str, &str)] = &[
+            ("/compliance", "200 OK", "", r#"{"cookie_requirements":{}}"#),
+            ("/cn", "200 OK", "", "fl=1\nip=192.0.2.1\nloc=CN\ntls=TLSv1.3\n"),
+            ("/us", "200 OK", "", "fl=1\nip=192.0.2.1\nloc=US\ntls=TLSv1.3\n"),
+        ];
+        let base = mock(TRACE);
+        let mut rules = RuleSet::builtin();
+        rules.retain(&["ChatGPT".to_string()]);
+        let run = |trace: &str| {
+            let mut rules = rules.clone();
+            let steps = &mut rules.services[0].steps;
+            steps[0].request.url = format!("{}/compliance", base);
+            steps[1].request.url = format!("{}/{}", base, trace);
+            check_service(&rules, &rules.services[0], Family::V4, Duration::from_secs(5))
+        };
+        let result = run("cn");
+        assert_eq!(result.verdict, Verdict::Blocked);
+        assert_eq!(result.region.as_deref(), Some("CN"));
+        let result = run("us");
+        assert_eq!(result.verdict, Verdict::Unlocked);
+        assert_eq!(result.region.as_deref(), Some("US"));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let valid = r#"{"services": [{"name": "A", "steps": [{"request": {"url": "http://a"}, "outcomes": [{"result": "unlocked"}]}]}]}"#;
+        assert!(RuleSet::parse(valid).is_ok());
+        for (from, to) in [
+            (r#"{"services""#, r#"{"agent": "x", "services""#),
+            (r#""name": "A","#, r#""name": "A", "url": "http://a","#),
+            (r#""outcomes""#, r#""outcome": [], "outcomes""#),
+            (r#""url": "http://a""#, r#""url": "http://a", "follow": true"#),
+            (r#""result": "unlocked""#, r#""result": "unlocked", "notes": "x""#),
+            (r#""result": "unlocked""#, r#""result": "unlocked", "when": {"code": [200]}"#),
+            (r#""result": "unlocked""#, r#""result": "unlocked", "region": {"after": "a", "before": "b", "from": "c"}"#),
+        ] {
+            let json = valid.replacen(from, to, 1);
+            assert!(RuleSet::parse(&json).is_err(), "应拒绝: {}", json);
+        }
+    }
 }
@@ -1,3 +1,369 @@
-pub fn run_speedtest() {
-    println!("网速测试功能待实现");
+pub mod server;
+
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use ureq::Agent;
+use crate::models::{Direction, ResultTable};
+
+/// 默认测速服务器，与内置服务端使用相同的 `__down` / `__up` 接口
+pub const DEFAULT_SERVERS: &str = "cloudflare=https://speed.cloudflare.com";
+/// 单次下载请求的字节数，读满后立即发起下一次请求
+const DOWNLOAD_CHUNK: u64 = 25_000_000;
+/// 单次上传请求的字节数
+const UPLOAD_CHUNK: u64 = 4_000_000;
+const READ_BUF: usize = 64 * 1024;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 测速服务器
+#[derive(Debug, Clone)]
+pub struct SpeedServer {
+    pub name: String,
+    /// 基础地址，如 `https://speed.cloudflare.com` 或 `http://1.2.3.4:8080`
+    pub url: String,
+}
+
+impl SpeedServer {
+    fn down_url(&self, bytes: u64) -> String {
+        format!("{}/__down?bytes={}", self.url, bytes)
+    }
+
+    fn up_url(&self) -> String {
+        format!("{}/__up", self.url)
+    }
+}
+
+/// 解析逗号分隔的服务器列表，每项为 `名称=地址` 或仅地址（以主机名为名称）
+pub fn parse_servers(raw: &str) -> Result<Vec<SpeedServer>, String> {
+    let mut servers = Vec::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, url) = match entry.split_once('=') {
+            Some((name, url)) => (name.trim().to_string(), url.trim()),
+            None => (String::new(), entry),
+        };
+        let url = if url.contains("://") { url.to_string() } else { format!("http://{}", url) };
+        let url = url.trim_end_matches('/').to_string();
+        let host = url.split("://").nth(1).and_then(|rest| rest.split('/').next()).unwrap_or("");
+        if host.is_empty() {
+            return Err(format!("'{}' 不是有效的服务器地址", entry));
+        }
+        let name = if name.is_empty() { host.to_string() } else { name };
+        servers.push(SpeedServer { name, url });
+    }
+    if servers.is_empty() {
+        return Err("至少需要一个测速服务器".to_string());
+    }
+    Ok(servers)
+}
+
+/// 测速配置
+#[derive(Debug, Clone)]
+pub struct SpeedJob {
+    pub servers: Vec<SpeedServer>,
+    /// 并行连接数
+    pub streams: usize,
+    /// 每个方向的测试时长
+    pub duration: Duration,
+    pub ping_count: usize,
+}
+
+impl Default for SpeedJob {
+    fn default() -> Self {
+        SpeedJob {
+            servers: parse_servers(DEFAULT_SERVERS).unwrap_or_default(),
+            streams: 4,
+            duration: Duration::from_secs(10),
+            ping_count: 10,
+        }
+    }
+}
+
+/// 延迟统计（毫秒）
+#[derive(Debug, Clone, Copy)]
+pub struct PingStats {
+    pub min: f64,
+    pub avg: f64,
+    /// 相邻两次延迟差值的平均
+    pub jitter: f64,
+}
+
+/// 单个方向的吞吐量
+#[derive(Debug, Clone)]
+pub struct Throughput {
+    pub bytes: u64,
+    /// 每秒吞吐量 (Mbps)
+    pub samples: Vec<f64>,
+}
+
+impl Throughput {
+    /// 平均吞吐量 (Mbps)；采样足够时去掉第一秒，避开 TCP 慢启动
+    pub fn mbps(&self) -> f64 {
+        let steady = if self.samples.len() > 2 { &self.samples[1..] } else { &self.samples[..] };
+        if steady.is_empty() { 0.0 } else { steady.iter().sum::<f64>() / steady.len() as f64 }
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.samples.iter().cloned().fold(0.0, f64::max)
+    }
+}
+
+/// 单个服务器的测速结果，各项失败时记录错误信息
+#[derive(Debug, Clone)]
+pub struct ServerResult {
+    pub server: SpeedServer,
+    pub ping: Result<PingStats, String>,
+    pub download: Result<Throughput, String>,
+    pub upload: Result<Throughput, String>,
+}
+
+impl ServerResult {
+    pub fn failed(&self) -> bool {
+        self.ping.is_err() && self.download.is_err() && self.upload.is_err()
+    }
+
+    pub fn partial(&self) -> bool {
+        self.ping.is_err() || self.download.is_err() || self.upload.is_err()
+    }
+}
+
+fn agent() -> Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(REQUEST_TIMEOUT)
+        .timeout_read(REQUEST_TIMEOUT)
+        .timeout_write(REQUEST_TIMEOUT)
+        .build()
+}
+
+fn describe(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, _) => format!("HTTP {}", code),
+        ureq::Error::Transport(t) => t.to_string(),
+    }
+}
+
+/// 复用同一连接请求空响应，第一次请求用于建立连接，不计入统计
+fn measure_ping(server: &SpeedServer, count: usize) -> Result<PingStats, String> {
+    let agent = agent();
+    let url = server.down_url(0);
+    let request = || -> Result<f64, String> {
+        let start = Instant::now();
+        let response = agent.get(&url).call().map_err(describe)?;
+        io::copy(&mut response.into_reader(), &mut io::sink()).map_err(|e| e.to_string())?;
+        Ok(start.elapsed().as_secs_f64() * 1000.0)
+    };
+    request()?;
+    let rtts = (0..count.max(1)).map(|_| request()).collect::<Result<Vec<f64>, String>>()?;
+    let jitter = if rtts.len() > 1 {
+        rtts.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / (rtts.len() - 1) as f64
+    } else {
+        0.0
+    };
+    Ok(PingStats {
+        min: rtts.iter().cloned().fold(f64::MAX, f64::min),
+        avg: rtts.iter().sum::<f64>() / rtts.len() as f64,
+        jitter,
+    })
+}
+
+/// 上传请求体：按需生成数据并计数，超过截止时间后中止请求
+struct UploadBody {
+    remaining: u64,
+    counter: Arc<AtomicU64>,
+    deadline: Instant,
+}
+
+impl Read for UploadBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if Instant::now() >= self.deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "测试时间已到"));
+        }
+        let n = (buf.len() as u64).min(self.remaining) as usize;
+        buf[..n].fill(0);
+        self.remaining -= n as u64;
+        self.counter.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+fn download_stream(agent: &Agent, server: &SpeedServer, counter: &AtomicU64, deadline: Instant) -> Result<(), String> {
+    let mut buf = vec![0u8; READ_BUF];
+    while Instant::now() < deadline {
+        let mut reader = agent.get(&server.down_url(DOWNLOAD_CHUNK)).call().map_err(describe)?.into_reader();
+        loop {
+            let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+            if n == 0 {
+                break;
+            }
+            counter.fetch_add(n as u64, Ordering::Relaxed);
+            if Instant::now() >= deadline {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn upload_stream(agent: &Agent, server: &SpeedServer, counter: &Arc<AtomicU64>, deadline: Instant) -> Result<(), String> {
+    while Instant::now() < deadline {
+        let body = UploadBody { remaining: UPLOAD_CHUNK, counter: counter.clone(), deadline };
+        let response = agent
+            .post(&server.up_url())
+            .set("Content-Type", "application/octet-stream")
+            .set("Content-Length", &UPLOAD_CHUNK.to_string())
+            .send(body);
+        match response {
+            Ok(response) => {
+                io::copy(&mut response.into_reader(), &mut io::sink()).map_err(|e| e.to_string())?;
+            }
+            // 截止时间到达时请求体被主动中止，不算失败
+            Err(_) if Instant::now() >= deadline => break,
+            Err(e) => return Err(describe(e)),
+        }
+    }
+    Ok(())
+}
+
+/// 以 `streams` 个并行连接运行一个方向的测试，按秒采样吞吐量
+fn measure_direction(job: &SpeedJob, server: &SpeedServer, upload: bool, on_sample: &mut dyn FnMut(f64)) -> Result<Throughput, String> {
+    let counter = Arc::new(AtomicU64::new(0));
+    let error: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    let start = Instant::now();
+    let deadline = start + job.duration;
+
+    let handles: Vec<_> = (0..job.streams.max(1))
+        .map(|_| {
+            let (server, counter, error) = (server.clone(), counter.clone(), error.clone());
+            thread::spawn(move || {
+                let agent = agent();
+                let outcome = if upload {
+                    upload_stream(&agent, &server, &counter, deadline)
+                } else {
+                    download_stream(&agent, &server, &counter, deadline)
+                };
+                if let Err(e) = outcome {
+                    error.lock().unwrap().get_or_insert(e);
+                }
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::new();
+    let mut last_bytes = 0;
+    let mut last_time = start;
+    let mut next = start + SAMPLE_INTERVAL;
+    loop {
+        let now = Instant::now();
+        if now >= next || now >= deadline {
+            let bytes = counter.load(Ordering::Relaxed);
+            let elapsed = now.duration_since(last_time).as_secs_f64();
+            if elapsed > 0.0 {
+                let mbps = (bytes - last_bytes) as f64 * 8.0 / elapsed / 1e6;
+                samples.push(mbps);
+                on_sample(mbps);
+            }
+            last_bytes = bytes;
+            last_time = now;
+            next += SAMPLE_INTERVAL;
+            if now >= deadline || handles.iter().all(|h| h.is_finished()) {
+                break;
+            }
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    let bytes = last_bytes;
+    let error = error.lock().unwrap().take();
+    match error {
+        Some(e) if bytes == 0 => Err(e),
+        _ => Ok(Throughput { bytes, samples }),
+    }
+}
+
+/// 测速进度事件
+pub enum Progress<'a> {
+    Start(&'a SpeedServer),
+    Ping(&'a Result<PingStats, String>),
+    Sample { upload: bool, mbps: f64 },
+}
+
+/// 依次测试每个服务器的延迟、下载和上传
+pub fn run_job(job: &SpeedJob, on_progress: &mut dyn FnMut(Progress)) -> Vec<ServerResult> {
+    job.servers
+        .iter()
+        .map(|server| {
+            on_progress(Progress::Start(server));
+            let ping = measure_ping(server, job.ping_count);
+            on_progress(Progress::Ping(&ping));
+            let download = measure_direction(job, server, false, &mut |mbps| on_progress(Progress::Sample { upload: false, mbps }));
+            let upload = measure_direction(job, server, true, &mut |mbps| on_progress(Progress::Sample { upload: true, mbps }));
+            ServerResult { server: server.clone(), ping, download, upload }
+        })
+        .collect()
+}
+
+pub fn result_tables(results: &[ServerResult]) -> Vec<ResultTable> {
+    let mut speed = ResultTable::new("网速", "Mbps", vec!["下载".to_string(), "上传".to_string()], Direction::Higher);
+    let columns = vec!["平均".to_string(), "最低".to_string(), "抖动".to_string()];
+    let mut latency = ResultTable::new("延迟", "ms", columns, Direction::Lower);
+    for r in results {
+        let mbps = |t: &Result<Throughput, String>| t.as_ref().ok().map(Throughput::mbps);
+        speed.push_row(&r.server.name, vec![mbps(&r.download), mbps(&r.upload)]);
+        if let Ok(p) = &r.ping {
+            latency.push_row(&r.server.name, vec![Some(p.avg), Some(p.min), Some(p.jitter)]);
+        }
+    }
+    vec![speed, latency]
+}
+
+pub fn format_results(job: &SpeedJob, results: &[ServerResult]) -> String {
+    let mut out = format!(
+        "\n{:<16} | {:>10} | {:>8} | {:>14} | {:>14}\n",
+        "服务器", "延迟", "抖动", "下载", "上传"
+    );
+    let mut errors = String::new();
+    for r in results {
+        let (ping, jitter) = match &r.ping {
+            Ok(p) => (format!("{:.1} ms", p.avg), format!("{:.1} ms", p.jitter)),
+            Err(_) => ("-".to_string(), "-".to_string()),
+        };
+        let speed = |t: &Result<Throughput, String>| match t {
+            Ok(t) => format!("{:.1} Mbps", t.mbps()),
+            Err(_) => "失败".to_string(),
+        };
+        out.push_str(&format!(
+            "{:<16} | {:>10} | {:>8} | {:>14} | {:>14}\n",
+            r.server.name, ping, jitter, speed(&r.download), speed(&r.upload)
+        ));
+        for (label, error) in [("延迟", r.ping.as_ref().err()), ("下载", r.download.as_ref().err()), ("上传", r.upload.as_ref().err())] {
+            if let Some(e) = error {
+                errors.push_str(&format!("  {} {}: {}\n", r.server.name, label, e));
+            }
+        }
+        for (label, t) in [("下载", &r.download), ("上传", &r.upload)] {
+            if let Ok(t) = t {
+                out.push_str(&format!(
+                    "    {} 峰值 {:.1} Mbps，共传输 {:.1} MB\n",
+                    label,
+                    t.peak(),
+                    t.bytes as f64 / 1e6
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        out.push_str("\n❌ 以下测试失败:\n");
+        out.push_str(&errors);
+    }
+    out.push_str(&format!(
+        "\n说明：每个方向 {} 条并行连接、持续 {} 秒，吞吐量去掉第一秒慢启动后取平均。\n",
+        job.streams,
+        job.duration.as_secs()
+    ));
+    out
 }
@@ -0,0 +1,189 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// 单次下载请求允许的最大字节数
+const MAX_DOWN_BYTES: u64 = 1 << 30;
+const WRITE_BUF: usize = 64 * 1024;
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+/// 请求头总长度上限
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+struct Request {
+    method: String,
+    path: String,
+    content_length: u64,
+    close: bool,
+}
+
+/// 读取一个请求头，连接正常关闭时返回 `None`
+fn read_request(reader: &mut BufReader<TcpStream>) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "无效的请求行"));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        content_length: 0,
+        close: version == "HTTP/1.0",
+    };
+    let mut header_bytes = line.len();
+    loop {
+        line.clear();
+        header_bytes += reader.read_line(&mut line)?;
+        if header_bytes > MAX_HEADER_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "请求头过长"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else { continue };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                request.content_length = value
+                    .parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "无效的 Content-Length"))?;
+            }
+            "connection" => request.close = value.eq_ignore_ascii_case("close"),
+            "transfer-encoding" => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "不支持分块上传"));
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(request))
+}
+
+fn query_bytes(path: &str) -> Option<u64> {
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("bytes="))
+        .and_then(|v| v.parse().ok())
+}
+
+/// 响应头一次性写出，避免多次小包写入触发 Nagle 延迟
+fn write_head(stream: &mut TcpStream, status: &str, length: u64, close: bool) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: {}\r\n\r\n",
+        status,
+        length,
+        if close { "close" } else { "keep-alive" }
+    );
+    stream.write_all(head.as_bytes())
+}
+
+fn handle(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    stream.set_write_timeout(Some(IDLE_TIMEOUT))?;
+    stream.set_nodelay(true)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::with_capacity(WRITE_BUF, stream);
+    let zeros = vec![0u8; WRITE_BUF];
+    while let Some(request) = read_request(&mut reader)? {
+        // 先读完请求体，保证连接可以继续复用
+        io::copy(&mut (&mut reader).take(request.content_length), &mut io::sink())?;
+        let route = request.path.split('?').next().unwrap_or("");
+        match (request.method.as_str(), route) {
+            ("GET", "/__down") => {
+                let mut remaining = query_bytes(&request.path).unwrap_or(0).min(MAX_DOWN_BYTES);
+                write_head(&mut writer, "200 OK", remaining, request.close)?;
+                while remaining > 0 {
+                    let n = remaining.min(WRITE_BUF as u64) as usize;
+                    writer.write_all(&zeros[..n])?;
+                    remaining -= n as u64;
+                }
+            }
+            ("POST", "/__up") => write_head(&mut writer, "200 OK", 0, request.close)?,
+            _ => write_head(&mut writer, "404 Not Found", 0, request.close)?,
+        }
+        writer.flush()?;
+        if request.close {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 运行测速服务端直到 `stop` 被置位，每个连接一个线程
+///
+/// 接口与 Cloudflare 测速兼容：`GET /__down?bytes=N` 返回 N 字节，`POST /__up` 接收并丢弃请求体。
+pub fn serve(addr: SocketAddr, stop: Arc<AtomicBool>, on_connect: &mut dyn FnMut(SocketAddr)) -> io::Result<()> {
+    serve_listener(TcpListener::bind(addr)?, stop, on_connect)
+}
+
+/// 在已绑定的监听套接字上运行服务端；单个连接出错只影响该连接
+fn serve_listener(listener: TcpListener, stop: Arc<AtomicBool>, on_connect: &mut dyn FnMut(SocketAddr)) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if let Err(e) = stream.set_nonblocking(false) {
+                    eprintln!("⚠️ 连接 {} 设置阻塞模式失败: {}", peer, e);
+                    continue;
+                }
+                on_connect(peer);
+                thread::spawn(move || handle(stream));
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+            Err(e) if matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::ConnectionAborted) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::perf_speedtest::{run_job, Progress, SpeedJob, SpeedServer};
+
+    #[test]
+    fn client_measures_download_and_upload_against_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let stop = stop.clone();
+            thread::spawn(move || serve_listener(listener, stop, &mut |_| {}))
+        };
+
+        let job = SpeedJob {
+            servers: vec![SpeedServer { name: "local".to_string(), url: format!("http://{}", addr) }],
+            streams: 2,
+            duration: Duration::from_secs(2),
+            ping_count: 3,
+        };
+        let mut samples = (0, 0);
+        let results = run_job(&job, &mut |progress| {
+            if let Progress::Sample { upload, .. } = progress {
+                if upload { samples.1 += 1 } else { samples.0 += 1 }
+            }
+        });
+        stop.store(true, Ordering::Relaxed);
+        server.join().unwrap().unwrap();
+
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert!(!result.partial(), "{:?}", result);
+        let ping = result.ping.as_ref().unwrap();
+        assert!(ping.min > 0.0 && ping.min <= ping.avg);
+        for (label, throughput, count) in [("下载", &result.download, samples.0), ("上传", &result.upload, samples.1)] {
+            let throughput = throughput.as_ref().unwrap();
+            assert!(throughput.bytes > 0, "{} 没有传输数据", label);
+            assert!(throughput.mbps() > 0.0, "{} 吞吐量为 0", label);
+            assert_eq!(throughput.samples.len(), count, "{} 采样数与进度回调不一致", label);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::io;
+use std::thread;
+use std::time::Duration;
+use crate::models::{Direction, Metric, ResultTable};
+use super::perf_cpu::{self, Workload};
+use super::procstat::{CpuStat, CpuTimes};
+
+/// 校验负载：纯整数运算，不受内存带宽影响
+const PROBE_WORKLOAD: Workload = Workload::Integer;
+/// 满载期间 steal 超过该百分比视为超售
+const OVERSOLD_STEAL_PCT: f64 = 5.0;
+/// 实际/期望吞吐量低于该百分比视为超售
+const OVERSOLD_EFFICIENCY_PCT: f64 = 70.0;
+
+/// 超售检测配置
+#[derive(Debug, Clone)]
+pub struct StealJob {
+    /// 空闲采样窗口
+    pub window: Duration,
+    /// 满载校验负载的运行时间
+    pub load_time: Duration,
+    pub threads: usize,
+}
+
+/// 超售检测结果
+#[derive(Debug, Clone)]
+pub struct StealReport {
+    /// 空闲窗口内的 CPU 时间分布
+    pub idle: CpuStat,
+    /// 满载期间的 CPU 时间分布
+    pub load: CpuStat,
+    pub threads: usize,
+    /// 单线程吞吐量 × 线程数
+    pub expected: f64,
+    /// 全部线程同时运行的实际吞吐量
+    pub achieved: f64,
+}
+
+impl StealReport {
+    pub fn efficiency(&self) -> f64 {
+        if self.expected > 0.0 { self.achieved / self.expected * 100.0 } else { 0.0 }
+    }
+
+    pub fn oversold(&self) -> bool {
+        self.load.total.steal_pct() > OVERSOLD_STEAL_PCT || self.efficiency() < OVERSOLD_EFFICIENCY_PCT
+    }
+
+    pub fn metrics(&self) -> Vec<Metric> {
+        vec![
+            Metric::new("steal_idle", self.idle.total.steal_pct(), "%", Direction::Lower),
+            Metric::new("steal_load", self.load.total.steal_pct(), "%", Direction::Lower),
+            Metric::new("expected_throughput", self.expected, PROBE_WORKLOAD.unit(), Direction::Higher),
+            Metric::new("achieved_throughput", self.achieved, PROBE_WORKLOAD.unit(), Direction::Higher),
+            Metric::new("load_efficiency", self.efficiency(), "%", Direction::Higher),
+        ]
+    }
+
+    pub fn tables(&self) -> Vec<ResultTable> {
+        vec![breakdown_table("空闲时CPU时间分布", &self.idle), breakdown_table("满载时CPU时间分布", &self.load)]
+    }
+
+    pub fn format(&self) -> String {
+        let mut out = format_breakdown("空闲窗口", &self.idle);
+        out.push_str(&format_breakdown("满载期间", &self.load));
+        out.push_str(&format!(
+            "\n期望吞吐量 {:.1} {unit} (单线程 × {})，实际 {:.1} {unit}，达成率 {:.1}%\n",
+            self.expected,
+            self.threads,
+            self.achieved,
+            self.efficiency(),
+            unit = PROBE_WORKLOAD.unit()
+        ));
+        if self.oversold() {
+            out.push_str(&format!(
+                "⚠️ 疑似 CPU 超售：满载 steal {:.1}% (阈值 {}%)，达成率 {:.1}% (阈值 {}%)。\n",
+                self.load.total.steal_pct(),
+                OVERSOLD_STEAL_PCT,
+                self.efficiency(),
+                OVERSOLD_EFFICIENCY_PCT
+            ));
+        } else {
+            out.push_str("✅ 未发现明显超售：满载时 steal 低且各核心吞吐量接近线性叠加。\n");
+        }
+        out.push_str("说明：开启超线程的物理机达成率本身会低于 100%，应结合 steal 一并判断。\n");
+        out
+    }
+}
+
+fn rows(stat: &CpuStat) -> Vec<(String, &CpuTimes)> {
+    let mut rows: Vec<(String, &CpuTimes)> = stat.cpus.iter().map(|(id, t)| (format!("cpu{}", id), t)).collect();
+    rows.push(("总计".to_string(), &stat.total));
+    rows
+}
+
+/// 各列的优劣方向互不相同，整表只作记录，不参与对比时的退化判断
+fn breakdown_table(title: &str, stat: &CpuStat) -> ResultTable {
+    let columns = CpuTimes::BREAKDOWN.iter().map(|c| c.to_string()).collect();
+    let mut table = ResultTable::new(title, "%", columns, Direction::Neutral);
+    for (label, times) in rows(stat) {
+        table.push_row(label, times.breakdown().into_iter().map(Some).collect());
+    }
+    table
+}
+
+fn format_breakdown(title: &str, stat: &CpuStat) -> String {
+    let mut out = format!("\n{} (%):\n{:<6}", title, "");
+    for column in CpuTimes::BREAKDOWN {
+        out.push_str(&format!(" {:>8}", column));
+    }
+    out.push('\n');
+    for (label, times) in rows(stat) {
+        out.push_str(&format!("{:<6}", label));
+        for value in times.breakdown() {
+            out.push_str(&format!(" {:>8.2}", value));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 先在空闲窗口内采样 `/proc/stat`，再以单线程与全部线程运行校验负载，对比期望与实际吞吐量
+pub fn run(job: &StealJob) -> io::Result<StealReport> {
+    let before = CpuStat::read()?;
+    thread::sleep(job.window);
+    let idle = CpuStat::read()?.since(&before);
+
+    let single = perf_cpu::measure(PROBE_WORKLOAD, 1, job.load_time);
+    let before = CpuStat::read()?;
+    let achieved = perf_cpu::measure(PROBE_WORKLOAD, job.threads, job.load_time);
+    let load = CpuStat::read()?.since(&before);
+
+    Ok(StealReport { idle, load, threads: job.threads, expected: single * job.threads as f64, achieved })
+}
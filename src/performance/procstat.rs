@@ -0,0 +1,168 @@
+use std::fs;
+use std::io;
+
+/// `/proc/stat` 中一行 CPU 时间统计（单位 jiffies）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    /// 虚拟机等待宿主机调度的时间
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    pub const BREAKDOWN: [&'static str; 5] = ["user", "system", "iowait", "steal", "idle"];
+
+    fn parse(fields: &[&str]) -> CpuTimes {
+        let field = |i: usize| fields.get(i).and_then(|v| v.parse().ok()).unwrap_or(0);
+        CpuTimes {
+            user: field(0),
+            nice: field(1),
+            system: field(2),
+            idle: field(3),
+            iowait: field(4),
+            irq: field(5),
+            softirq: field(6),
+            steal: field(7),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+
+    /// 与更早一次快照的差值
+    pub fn since(&self, earlier: &CpuTimes) -> CpuTimes {
+        CpuTimes {
+            user: self.user.saturating_sub(earlier.user),
+            nice: self.nice.saturating_sub(earlier.nice),
+            system: self.system.saturating_sub(earlier.system),
+            idle: self.idle.saturating_sub(earlier.idle),
+            iowait: self.iowait.saturating_sub(earlier.iowait),
+            irq: self.irq.saturating_sub(earlier.irq),
+            softirq: self.softirq.saturating_sub(earlier.softirq),
+            steal: self.steal.saturating_sub(earlier.steal),
+        }
+    }
+
+    /// steal 占总时间的百分比，通常对 [`CpuTimes::since`] 的差值调用
+    pub fn steal_pct(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.steal as f64 / total as f64 * 100.0,
+        }
+    }
+
+    /// 各类时间占比 (%)，顺序与 [`CpuTimes::BREAKDOWN`] 一致；user 含 nice，system 含中断
+    pub fn breakdown(&self) -> [f64; 5] {
+        let total = self.total().max(1) as f64;
+        let pct = |v: u64| v as f64 / total * 100.0;
+        [
+            pct(self.user + self.nice),
+            pct(self.system + self.irq + self.softirq),
+            pct(self.iowait),
+            pct(self.steal),
+            pct(self.idle),
+        ]
+    }
+}
+
+/// 一次 `/proc/stat` 快照：汇总行与各逻辑 CPU
+#[derive(Debug, Clone, Default)]
+pub struct CpuStat {
+    pub total: CpuTimes,
+    /// `/proc/stat` 中的 CPU 编号及其计时；离线的 CPU 不出现，编号可能不连续
+    pub cpus: Vec<(u32, CpuTimes)>,
+}
+
+impl CpuStat {
+    pub fn read() -> io::Result<CpuStat> {
+        Ok(Self::parse(&fs::read_to_string("/proc/stat")?))
+    }
+
+    fn parse(content: &str) -> CpuStat {
+        let mut stat = CpuStat::default();
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else { continue };
+            let values: Vec<&str> = fields.collect();
+            if name == "cpu" {
+                stat.total = CpuTimes::parse(&values);
+            } else if let Some(id) = name.strip_prefix("cpu").and_then(|id| id.parse().ok()) {
+                stat.cpus.push((id, CpuTimes::parse(&values)));
+            }
+        }
+        stat
+    }
+
+    pub fn since(&self, earlier: &CpuStat) -> CpuStat {
+        CpuStat {
+            total: self.total.since(&earlier.total),
+            // 按编号配对，两次采样之间上下线的 CPU 不参与计算
+            cpus: self
+                .cpus
+                .iter()
+                .filter_map(|(id, now)| {
+                    let (_, before) = earlier.cpus.iter().find(|(other, _)| other == id)?;
+                    Some((*id, now.since(before)))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = "\
+cpu  100 10 50 1000 20 5 5 30 0 0
+cpu0 40 5 20 500 10 2 3 10 0 0
+cpu2 60 5 30 500 10 3 2 20 0 0
+cpu7 18446744073709551610 0 0 0 0 0 0 0 0 0
+intr 12345 0 0
+ctxt 67890
+";
+
+    const AFTER: &str = "\
+cpu  200 10 100 1100 20 5 5 80 0 0
+cpu0 90 5 40 550 10 2 3 30 0 0
+cpu2 50 5 35 520 10 3 2 40 0 0
+cpu5 1 1 1 1 1 1 1 1 0 0
+cpu7 4 0 0 0 0 0 0 0 0 0
+";
+
+    #[test]
+    fn parse_keeps_real_cpu_ids() {
+        let stat = CpuStat::parse(BEFORE);
+        assert_eq!(stat.total.user, 100);
+        assert_eq!(stat.total.steal, 30);
+        assert_eq!(stat.total.total(), 1220);
+        let ids: Vec<u32> = stat.cpus.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [0, 2, 7]);
+        let cpu2 = CpuTimes { user: 60, nice: 5, system: 30, idle: 500, iowait: 10, irq: 3, softirq: 2, steal: 20 };
+        assert_eq!(stat.cpus[1].1, cpu2);
+    }
+
+    #[test]
+    fn since_pairs_by_id_and_clamps_counters_that_go_backwards() {
+        let delta = CpuStat::parse(AFTER).since(&CpuStat::parse(BEFORE));
+        assert_eq!(delta.total.total(), 300);
+        assert_eq!(delta.total.steal_pct(), 50.0 / 300.0 * 100.0);
+
+        // cpu5 在两次采样之间上线，没有可配对的基准
+        let ids: Vec<u32> = delta.cpus.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [0, 2, 7]);
+        assert_eq!(delta.cpus[0].1.steal, 20);
+        // cpu2 的 user 计数回退，cpu7 的计数回绕，均记为 0 而不是溢出
+        assert_eq!(delta.cpus[1].1.user, 0);
+        assert_eq!(delta.cpus[1].1.system, 5);
+        assert_eq!(delta.cpus[2].1, CpuTimes::default());
+        assert_eq!(delta.cpus[2].1.breakdown(), [0.0; 5]);
+    }
+}
@@ -88,14 +88,10 @@ impl PortManager {
                                     self.should_exit = true;
                                 }
                                 KeyCode::Up => {
-                                    if self.selected > 0 {
-                                        self.selected -= 1;
-                                    }
+                                    self.selected = self.selected.saturating_sub(1);
                                 }
-                                KeyCode::Down => {
-                                    if self.selected < 3 {
-                                        self.selected += 1;
-                                    }
+                                KeyCode::Down if self.selected < 3 => {
+                                    self.selected += 1;
                                 }
                                 KeyCode::Enter => {
                                     self.handle_selection();
@@ -161,7 +157,7 @@ impl PortManager {
         f.render_widget(title, chunks[0]);
 
         // 菜单
-        let items = vec![
+        let items = [
             "1. 开放端口",
             "2. 关闭端口", 
             "3. 查看端口",
@@ -258,7 +254,7 @@ impl PortManager {
         self.pending_action = None;
     }
 
-    fn open_port(&self, port_info: &str) -> String {
+    pub fn open_port(&self, port_info: &str) -> String {
         let (port, protocol) = self.parse_port_info(port_info);
         
         // 验证端口号
@@ -285,7 +281,7 @@ impl PortManager {
         }
     }
 
-    fn close_port(&self, port_info: &str) -> String {
+    pub fn close_port(&self, port_info: &str) -> String {
         let (port, protocol) = self.parse_port_info(port_info);
         
         if let Err(e) = port.parse::<u16>() {
@@ -316,7 +312,7 @@ impl PortManager {
         
         // 检查监听端口
         if let Ok(output) = Command::new("ss")
-            .args(&["-tuln"])
+            .args(["-tuln"])
             .output()
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
@@ -340,7 +336,7 @@ impl PortManager {
                 }
             }
         } else if let Ok(output) = Command::new("netstat")
-            .args(&["-tuln"])
+            .args(["-tuln"])
             .output()
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
@@ -437,6 +433,8 @@ impl PortManager {
     }
 }
 
+/// 端口管理交互界面，尚未接入主菜单
+#[allow(dead_code)]
 pub fn port_manager_menu() {
     let mut port_manager = PortManager::default();
     
@@ -0,0 +1,140 @@
+// src/report.rs
+mod html;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::Serialize;
+use crate::models::{Metric, ResultTable, TaskConfig, TaskResult, TaskStatus};
+use crate::sysinfo::HostFacts;
+use crate::tasks::find_task;
+use crate::utils::{get_current_time, output_path};
+
+/// 一次（批量）运行的完整报告，可导出为 JSON、Markdown 与 HTML
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub generated_at: String,
+    pub host: HostFacts,
+    pub tasks: Vec<TaskReport>,
+}
+
+/// 单个任务在报告中的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReport {
+    pub id: usize,
+    pub command: String,
+    pub name: String,
+    pub params: BTreeMap<String, String>,
+    pub started_at: String,
+    pub status: TaskStatus,
+    pub duration_secs: f64,
+    pub metrics: Vec<Metric>,
+    pub tables: Vec<ResultTable>,
+    pub text: String,
+}
+
+impl RunReport {
+    pub fn new(host: HostFacts) -> Self {
+        Self { generated_at: get_current_time(), host, tasks: Vec::new() }
+    }
+
+    /// 记录一个任务的执行结果；`started_at` 为任务开始时间
+    pub fn push(&mut self, config: &TaskConfig, started_at: String, result: &TaskResult) {
+        self.tasks.push(TaskReport {
+            id: config.item.id,
+            command: find_task(config.item.id).map(|t| t.command()).unwrap_or_default().to_string(),
+            name: config.item.name.to_string(),
+            params: config.params.iter().map(|(k, v)| (k.clone(), v.to_string())).collect(),
+            started_at,
+            status: result.status,
+            duration_secs: result.duration.as_secs_f64(),
+            metrics: result.metrics.clone(),
+            tables: result.tables.clone(),
+            text: result.text.clone(),
+        });
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let host = &self.host;
+        let mut md = String::new();
+        md.push_str("# OneKey 运行报告\n\n");
+        md.push_str(&format!("- 生成时间: {}\n", self.generated_at));
+        md.push_str(&format!("- 主机: {} ({}, 内核 {}, {})\n", host.hostname, host.os_version, host.kernel_version, host.arch));
+        md.push_str(&format!("- CPU: {} × {} 核 @ {} MHz\n", host.cpu_brand, host.cpu_cores, host.cpu_freq_mhz));
+        md.push_str(&format!("- 内存: {:.1} GB，交换分区: {:.1} GB\n", host.total_mem_gb, host.total_swap_gb));
+        md.push_str(&format!("- 虚拟化: {}\n", host.vm_type));
+        md.push_str(&format!("- 网络: {} / {} / {}\n", host.network.ipv4, host.network.isp, host.network.location));
+
+        md.push_str("\n## 任务汇总\n\n");
+        md.push_str("| # | 任务 | 状态 | 耗时 |\n|---|---|---|---|\n");
+        for (i, task) in self.tasks.iter().enumerate() {
+            md.push_str(&format!(
+                "| {} | {} | {} {} | {:.1}s |\n",
+                i + 1, task.name, task.status.icon(), task.status.label(), task.duration_secs
+            ));
+        }
+
+        for (i, task) in self.tasks.iter().enumerate() {
+            md.push_str(&format!("\n## {}. {}\n\n", i + 1, task.name));
+            md.push_str(&format!("开始时间: {}\n", task.started_at));
+            if !task.params.is_empty() {
+                let params: Vec<String> = task.params.iter().map(|(k, v)| format!("`{}={}`", k, v)).collect();
+                md.push_str(&format!("\n参数: {}\n", params.join(", ")));
+            }
+            if !task.metrics.is_empty() {
+                md.push_str("\n| 指标 | 数值 | 单位 |\n|---|---:|---|\n");
+                for metric in &task.metrics {
+                    md.push_str(&format!("| {} | {:.2} | {} |\n", metric.key, metric.value, metric.unit));
+                }
+            }
+            for table in &task.tables {
+                md.push_str(&format!("\n**{}** ({})\n\n", table.title, table.unit));
+                md.push_str(&format!("| | {} |\n", table.columns.join(" | ")));
+                md.push_str(&format!("|---|{}\n", "---:|".repeat(table.columns.len())));
+                for row in &table.rows {
+                    let cells: Vec<String> = row.cells.iter().map(|c| format_cell(*c)).collect();
+                    md.push_str(&format!("| {} | {} |\n", row.label, cells.join(" | ")));
+                }
+            }
+            if !task.text.trim().is_empty() {
+                md.push_str("\n<details><summary>输出</summary>\n\n```text\n");
+                md.push_str(task.text.trim_end());
+                md.push_str("\n```\n\n</details>\n");
+            }
+        }
+        md
+    }
+
+    /// 生成单文件 HTML 报告（内联样式与条形图，无外部资源）
+    pub fn to_html(&self) -> String {
+        html::render(self)
+    }
+
+    /// 将 JSON、Markdown 与 HTML 报告写入输出目录，返回写入的文件路径
+    pub fn save(&self, name: &str) -> Vec<PathBuf> {
+        let mut saved = Vec::new();
+        for (extension, content) in [("json", self.to_json()), ("md", self.to_markdown()), ("html", self.to_html())] {
+            match output_path(name, extension).and_then(|path| fs::write(&path, content).map(|_| path)) {
+                Ok(path) => {
+                    println!("📄 报告已保存到: {}", path.display());
+                    saved.push(path);
+                }
+                Err(e) => eprintln!("❌ 写入 {} 报告失败: {}", extension, e),
+            }
+        }
+        saved
+    }
+}
+
+/// 表格单元格的统一格式：大数取整，小数保留两位
+fn format_cell(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.abs() >= 1000.0 => format!("{:.0}", v),
+        Some(v) => format!("{:.2}", v),
+        None => "-".to_string(),
+    }
+}
@@ -0,0 +1,176 @@
+// src/report/html.rs
+use std::collections::HashMap;
+use super::{format_cell, RunReport, TaskReport};
+use crate::models::{ResultTable, TaskStatus};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; margin: 0; background: #f4f6f8; color: #222; }
+main { max-width: 1100px; margin: 0 auto; padding: 24px; }
+h1 { font-size: 24px; margin: 0 0 4px; }
+h2 { font-size: 18px; margin: 32px 0 12px; border-bottom: 2px solid #dde3e9; padding-bottom: 6px; }
+h3 { font-size: 15px; margin: 20px 0 8px; color: #444; }
+.muted { color: #777; font-size: 13px; }
+.card { background: #fff; border-radius: 8px; padding: 16px 20px; margin-bottom: 16px; box-shadow: 0 1px 3px rgba(0,0,0,.08); }
+table { border-collapse: collapse; width: 100%; font-size: 13px; }
+th, td { padding: 6px 8px; border-bottom: 1px solid #eef1f4; text-align: left; vertical-align: middle; }
+th { background: #f8fafb; font-weight: 600; }
+td.num { text-align: right; white-space: nowrap; font-variant-numeric: tabular-nums; }
+.host td:first-child { color: #666; width: 160px; }
+.bar { position: relative; min-width: 90px; }
+.bar span { position: relative; z-index: 1; }
+.bar i { position: absolute; left: 0; top: 3px; bottom: 3px; background: #cfe3fb; border-radius: 3px; }
+.status { display: inline-block; padding: 1px 8px; border-radius: 10px; font-size: 12px; color: #fff; }
+.status-ok { background: #2e9d5b; }
+.status-warn { background: #d68a00; }
+.status-fail { background: #d33f3f; }
+.status-skipped { background: #8a949e; }
+details pre { background: #1e2227; color: #dfe3e8; padding: 12px; border-radius: 6px; overflow-x: auto; font-size: 12px; }
+"#;
+
+/// 渲染完整的 HTML 报告
+pub fn render(report: &RunReport) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>OneKey 报告 - {}</title>\n", escape(&report.host.hostname)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n<main>\n", STYLE));
+    html.push_str("<h1>OneKey 运行报告</h1>\n");
+    html.push_str(&format!("<div class=\"muted\">生成时间: {}</div>\n", escape(&report.generated_at)));
+
+    render_host(&mut html, report);
+    render_summary(&mut html, report);
+    for (i, task) in report.tasks.iter().enumerate() {
+        render_task(&mut html, i + 1, task);
+    }
+
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+fn render_host(html: &mut String, report: &RunReport) {
+    let host = &report.host;
+    let mut rows = vec![
+        ("主机名", host.hostname.clone()),
+        ("操作系统", host.os_version.clone()),
+        ("内核版本", host.kernel_version.clone()),
+        ("系统架构", host.arch.clone()),
+        ("CPU", format!("{} × {} 核 @ {} MHz", host.cpu_brand, host.cpu_cores, host.cpu_freq_mhz)),
+        ("AES-NI / 虚拟化", format!("{} / {}", host.aes_ni, host.vm_support)),
+        ("VM类型", host.vm_type.clone()),
+        ("内存", format!("{:.1}/{:.1} GB", host.used_mem_gb, host.total_mem_gb)),
+        ("交换分区", format!("{:.1} GB", host.total_swap_gb)),
+    ];
+    for disk in &host.disks {
+        rows.push(("硬盘", format!("{:.1}/{:.1} GB 可用 - {}", disk.available_gb, disk.total_gb, disk.mount_point)));
+    }
+    rows.push(("公网IPv4", host.network.ipv4.clone()));
+    if host.network.ipv6_support {
+        rows.push(("公网IPv6", host.network.ipv6.clone()));
+    }
+    rows.push(("ISP运营商", host.network.isp.clone()));
+    rows.push(("地理位置", host.network.location.clone()));
+    rows.push(("拥塞算法", host.tcp_congestion.clone()));
+
+    html.push_str("<h2>主机概况</h2>\n<div class=\"card\"><table class=\"host\">\n");
+    for (label, value) in rows {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", label, escape(&value)));
+    }
+    html.push_str("</table></div>\n");
+}
+
+fn render_summary(html: &mut String, report: &RunReport) {
+    html.push_str("<h2>任务汇总</h2>\n<div class=\"card\"><table>\n");
+    html.push_str("<tr><th>#</th><th>任务</th><th>状态</th><th>开始时间</th><th>耗时</th></tr>\n");
+    for (i, task) in report.tasks.iter().enumerate() {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{:.1}s</td></tr>\n",
+            i + 1,
+            escape(&task.name),
+            status_badge(task.status),
+            escape(&task.started_at),
+            task.duration_secs
+        ));
+    }
+    html.push_str("</table></div>\n");
+}
+
+fn render_task(html: &mut String, index: usize, task: &TaskReport) {
+    html.push_str(&format!("<h2>{}. {} {}</h2>\n<div class=\"card\">\n", index, escape(&task.name), status_badge(task.status)));
+    if !task.params.is_empty() {
+        let params: Vec<String> = task.params.iter().map(|(k, v)| format!("<code>{}={}</code>", escape(k), escape(v))).collect();
+        html.push_str(&format!("<div class=\"muted\">参数: {}</div>\n", params.join(" ")));
+    }
+
+    if !task.metrics.is_empty() {
+        // 同单位的指标共享条形图刻度
+        let mut max_by_unit: HashMap<&str, f64> = HashMap::new();
+        for metric in &task.metrics {
+            let max = max_by_unit.entry(metric.unit.as_str()).or_insert(0.0);
+            *max = max.max(metric.value);
+        }
+        html.push_str("<h3>指标</h3>\n<table>\n<tr><th>指标</th><th>数值</th><th>单位</th></tr>\n");
+        for metric in &task.metrics {
+            let max = max_by_unit.get(metric.unit.as_str()).copied().unwrap_or(0.0);
+            html.push_str(&format!(
+                "<tr><td>{}</td>{}<td>{}</td></tr>\n",
+                escape(&metric.key),
+                bar_cell(Some(metric.value), max),
+                escape(&metric.unit)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    for table in &task.tables {
+        render_table(html, table);
+    }
+
+    if !task.text.trim().is_empty() {
+        html.push_str(&format!("<details><summary>原始输出</summary><pre>{}</pre></details>\n", escape(task.text.trim_end())));
+    }
+    html.push_str("</div>\n");
+}
+
+fn render_table(html: &mut String, table: &ResultTable) {
+    let max = table.max_value();
+    html.push_str(&format!("<h3>{} <span class=\"muted\">({})</span></h3>\n<table>\n<tr><th></th>", escape(&table.title), escape(&table.unit)));
+    for column in &table.columns {
+        html.push_str(&format!("<th>{}</th>", escape(column)));
+    }
+    html.push_str("</tr>\n");
+    for row in &table.rows {
+        html.push_str(&format!("<tr><td>{}</td>", escape(&row.label)));
+        for cell in &row.cells {
+            html.push_str(&bar_cell(*cell, max));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+}
+
+/// 带背景条形图的数值单元格，条长按 `max` 归一化
+fn bar_cell(value: Option<f64>, max: f64) -> String {
+    match value {
+        Some(v) if max > 0.0 => {
+            let width = (v / max * 100.0).clamp(0.0, 100.0);
+            format!("<td class=\"num bar\"><i style=\"width:{:.1}%\"></i><span>{}</span></td>", width, format_cell(value))
+        }
+        _ => format!("<td class=\"num\">{}</td>", format_cell(value)),
+    }
+}
+
+fn status_badge(status: TaskStatus) -> String {
+    let class = match status {
+        TaskStatus::Ok => "ok",
+        TaskStatus::Warn => "warn",
+        TaskStatus::Fail => "fail",
+        TaskStatus::Skipped => "skipped",
+    };
+    format!("<span class=\"status status-{}\">{}</span>", class, status.label())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -2,8 +2,10 @@ use ratatui::{prelude::*, widgets::{Block, Borders, List, ListItem}};
 use crossterm::{event, execute, terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}};
 use std::io::{stdout};
 
+/// 常用软件安装菜单，尚未接入主菜单
+#[allow(dead_code)]
 pub fn common_software_menu() {
-    let items = vec![
+    let items = [
         "安装 Docker",
         "安装 Node.js",
         "安装 Python",
@@ -34,10 +36,10 @@ pub fn common_software_menu() {
                 if key.kind == KeyEventKind::Press {
                     match key.code {
                         KeyCode::Up => {
-                            if selected > 0 { selected -= 1; }
+                            selected = selected.saturating_sub(1);
                         }
-                        KeyCode::Down => {
-                            if selected < items.len() - 1 { selected += 1; }
+                        KeyCode::Down if selected < items.len() - 1 => {
+                            selected += 1;
                         }
                         KeyCode::Enter => break selected,
                         KeyCode::Char('q') => break items.len() - 1,
@@ -55,7 +57,7 @@ pub fn common_software_menu() {
         2 => install_python(),
         3 => install_rust(),
         4 => install_go(),
-        5 | _ => return,
+        _ => {}
     }
 }
 
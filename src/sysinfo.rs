@@ -18,21 +18,17 @@ fn get_tcp_congestion_algo() -> String {
     "未知".to_string()
 }
 
-// 获取 DNS 服务器
+// 获取 DNS 服务器（含 systemd-resolved 上游），逗号分隔
 fn get_dns() -> String {
-    if let Ok(content) = std::fs::read_to_string("/etc/resolv.conf") {
-        for line in content.lines() {
-            if line.starts_with("nameserver") {
-                if let Some(dns) = line.split_whitespace().nth(1) {
-                    return dns.to_string();
-                }
-            }
-        }
-    }
-    "".to_string()
+    crate::dns::system_resolvers()
+        .iter()
+        .map(|r| r.addr.ip().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 use chrono::{Local, DateTime};
 use hostname::get;
+use serde::Serialize;
 use std::process::Command;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use sysinfo::{System, Networks, Disks};
@@ -53,7 +49,7 @@ use std::io;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct NetworkData {
     pub isp: String,
     pub ipv4: String,
@@ -61,6 +57,7 @@ pub struct NetworkData {
     pub ipv6_support: bool,
     pub dns: String,
     pub location: String,
+    #[serde(skip)]
     pub loading: bool,
 }
 
@@ -78,6 +75,107 @@ impl Default for NetworkData {
     }
 }
 
+/// 主机概况，供命令行模式和报告使用（不依赖 TUI）
+#[derive(Clone, Debug, Serialize)]
+pub struct HostFacts {
+    pub hostname: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub arch: String,
+    pub cpu_brand: String,
+    pub cpu_cores: usize,
+    pub cpu_freq_mhz: u64,
+    pub total_mem_gb: f64,
+    pub used_mem_gb: f64,
+    pub total_swap_gb: f64,
+    pub aes_ni: String,
+    pub vm_support: String,
+    pub vm_type: String,
+    pub uptime_secs: u64,
+    pub tcp_congestion: String,
+    pub disks: Vec<DiskFacts>,
+    pub network: NetworkData,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DiskFacts {
+    pub mount_point: String,
+    pub total_gb: f64,
+    pub available_gb: f64,
+}
+
+impl HostFacts {
+    /// 同步采集主机信息（网络信息会阻塞直至请求完成或超时）
+    pub fn collect() -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+        let gb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+
+        let disks = Disks::new_with_refreshed_list()
+            .iter()
+            .map(|disk| DiskFacts {
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                total_gb: gb(disk.total_space()),
+                available_gb: gb(disk.available_space()),
+            })
+            .collect();
+
+        Self {
+            hostname: get().ok().and_then(|h| h.into_string().ok()).unwrap_or_default(),
+            os_version: System::long_os_version().unwrap_or_default(),
+            kernel_version: System::kernel_version().unwrap_or_default(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpu_brand: sys.cpus().first().map(|c| c.brand().to_string()).unwrap_or_default(),
+            cpu_cores: sys.cpus().len(),
+            cpu_freq_mhz: sys.cpus().first().map(|c| c.frequency()).unwrap_or(0),
+            total_mem_gb: gb(sys.total_memory()),
+            used_mem_gb: gb(sys.used_memory()),
+            total_swap_gb: gb(sys.total_swap()),
+            aes_ni: get_aes_ni_support(),
+            vm_support: get_vm_support(),
+            vm_type: get_vm_type(),
+            uptime_secs: System::uptime(),
+            tcp_congestion: get_tcp_congestion_algo(),
+            disks,
+            network: fetch_network_info(),
+        }
+    }
+
+    /// 生成纯文本概况
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("主机名: {}\n", self.hostname));
+        out.push_str(&format!("操作系统: {}\n", self.os_version));
+        out.push_str(&format!("内核版本: {}\n", self.kernel_version));
+        out.push_str(&format!("系统架构: {}\n", self.arch));
+        out.push_str(&format!("CPU型号: {}\n", self.cpu_brand));
+        out.push_str(&format!("CPU核心: {} 核心 @ {} MHz\n", self.cpu_cores, self.cpu_freq_mhz));
+        out.push_str(&format!("物理内存: {:.1}/{:.1} GB\n", self.used_mem_gb, self.total_mem_gb));
+        if self.total_swap_gb > 0.0 {
+            out.push_str(&format!("虚拟内存: {:.1} GB\n", self.total_swap_gb));
+        }
+        for disk in &self.disks {
+            out.push_str(&format!(
+                "硬盘: {:.1}/{:.1} GB 可用 - {}\n",
+                disk.available_gb, disk.total_gb, disk.mount_point
+            ));
+        }
+        out.push_str(&format!("AES-NI: {}\n", self.aes_ni));
+        out.push_str(&format!("VM-x/AMD-V: {}\n", self.vm_support));
+        out.push_str(&format!("VM类型: {}\n", self.vm_type));
+        out.push_str(&format!("运行时长: {} 秒\n", self.uptime_secs));
+        out.push_str(&format!("公网IPv4: {}\n", self.network.ipv4));
+        if self.network.ipv6_support {
+            out.push_str(&format!("公网IPv6: {}\n", self.network.ipv6));
+        }
+        out.push_str(&format!("ISP运营商: {}\n", self.network.isp));
+        out.push_str(&format!("地理位置: {}\n", self.network.location));
+        out.push_str(&format!("DNS服务器: {}\n", self.network.dns));
+        out.push_str(&format!("拥塞算法: {}\n", self.tcp_congestion));
+        out
+    }
+}
+
 pub struct SystemInfo {
     sys: System,
     boot_time: DateTime<Local>,
@@ -92,10 +190,9 @@ impl SystemInfo {
         let boot_time = SystemTime::now()
             .checked_sub(Duration::from_secs(System::uptime()))
             .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| DateTime::from_timestamp(d.as_secs() as i64, 0))
-            .flatten()
+            .and_then(|d| DateTime::from_timestamp(d.as_secs() as i64, 0))
             .map(|utc| utc.with_timezone(&Local::now().timezone()))
-            .unwrap_or_else(|| Local::now());
+            .unwrap_or_else(Local::now);
 
         let network_data = Arc::new(Mutex::new(NetworkData::default()));
         let network_data_clone = Arc::clone(&network_data);
@@ -137,14 +234,14 @@ impl SystemInfo {
         });
     }
 
-    fn get_system_info(&self) -> Vec<ListItem> {
+    fn get_system_info(&self) -> Vec<ListItem<'_>> {
         let hostname = get().ok().and_then(|h| h.into_string().ok()).unwrap_or_default();
         let os_version = System::long_os_version().unwrap_or_default();
         let kernel_version = System::kernel_version().unwrap_or_default();
         let arch = std::env::consts::ARCH;
         let current_user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
         let shell = std::env::var("SHELL").unwrap_or_else(|_| "Unknown".to_string());
-        let shell_name = shell.split('/').last().map(|s| s.to_string()).unwrap_or(shell.clone());
+        let shell_name = shell.split('/').next_back().map(|s| s.to_string()).unwrap_or(shell.clone());
         let rust_version = get_rust_version();
         let uptime = self.format_uptime();
         let aes_ni = get_aes_ni_support();
@@ -203,7 +300,7 @@ impl SystemInfo {
         ]
     }
 
-    fn get_hardware_info(&self) -> Vec<ListItem> {
+    fn get_hardware_info(&self) -> Vec<ListItem<'_>> {
         let cpu_brand = self.sys.cpus().first().map(|c| c.brand()).unwrap_or_default();
         let cpu_cores = self.sys.cpus().len();
         let cpu_freq = self.sys.cpus().first().map(|c| c.frequency()).unwrap_or(0);
@@ -259,7 +356,7 @@ impl SystemInfo {
         items
     }
 
-    fn get_network_info(&self) -> Vec<ListItem> {
+    fn get_network_info(&self) -> Vec<ListItem<'_>> {
         let networks = Networks::new_with_refreshed_list();
         let net_algo = get_tcp_congestion_algo();
         
@@ -550,8 +647,10 @@ pub fn run_system_monitor() -> Result<(), Box<dyn std::error::Error>> {
 
 // 新增的辅助函数
 fn fetch_network_info() -> NetworkData {
-    let mut data = NetworkData::default();
-    data.loading = false;
+    let mut data = NetworkData {
+        loading: false,
+        ..NetworkData::default()
+    };
     
     // 获取 IPv6 支持信息
     data.ipv6_support = check_ipv6_support();
@@ -613,7 +712,8 @@ fn fetch_network_info() -> NetworkData {
     data
 }
 
-fn check_ipv6_support() -> bool {
+/// 本机是否启用了 IPv6 协议栈
+pub fn check_ipv6_support() -> bool {
     // 检查 /proc/net/if_inet6 文件是否存在
     if std::path::Path::new("/proc/net/if_inet6").exists() {
         return true;
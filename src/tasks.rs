@@ -1,35 +1,150 @@
 // src/tasks.rs
 
+mod task_bandwidth;
+mod task_compare;
+mod task_cpu;
+mod task_disk;
+mod task_disk_multi;
+mod task_dns;
+mod task_mem;
+mod task_netdiag;
+mod task_ping;
+mod task_ports;
+mod task_simulated;
+mod task_speedtest;
+mod task_steal;
+mod task_sysinfo;
+mod task_traceroute;
+mod task_unlock;
+
 use std::collections::HashMap;
-use crate::models::{MenuItem, TaskConfig};
+use std::sync::OnceLock;
+use std::time::Instant;
+use crate::history::{self, HistoryRecord};
+use crate::models::{MenuItem, TaskConfig, TaskResult, TaskStatus};
+use crate::params::{ParamSpec, ParamValue};
+use crate::report::RunReport;
+use crate::sysinfo::HostFacts;
 use crate::utils::{prompt_input, save_output, wait_for_key, get_current_time};
+use task_bandwidth::BandwidthTask;
+use task_compare::CompareTask;
+use task_cpu::CpuTask;
+use task_disk::DiskTask;
+use task_disk_multi::DiskMultiTask;
+use task_dns::DnsTask;
+use task_mem::MemTask;
+use task_netdiag::NetDiagTask;
+use task_ping::PingTask;
+use task_ports::PortTask;
+use task_simulated::SimulatedTask;
+use task_speedtest::SpeedtestTask;
+use task_steal::StealTask;
+use task_sysinfo::SysInfoTask;
+use task_traceroute::TracerouteTask;
+use task_unlock::UnlockTask;
+
+/// 任务接口：每个菜单项/命令行命令对应一个实现
+///
+/// 菜单、批量执行和命令行都从 [`registry`] 枚举任务，新增任务只需新增一个模块并在此注册。
+pub trait Task: Send + Sync {
+    /// 命令行命令名，多级命令用空格分隔（如 `ports open`）
+    fn command(&self) -> &'static str;
+    /// 命令行别名
+    fn aliases(&self) -> &'static [&'static str] { &[] }
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// 任务参数定义，交互模式按顺序提示输入
+    fn params(&self) -> Vec<ParamSpec> { vec![] }
+    /// 交互模式下提示输入参数 `key` 之前显示的说明，如可供选择的候选项
+    fn param_hint(&self, _key: &str) -> Option<String> { None }
+    /// 依赖运行环境的额外校验（如候选项是否存在），交互与命令行模式共用
+    fn check_param(&self, _key: &str, _value: &ParamValue) -> Result<(), String> { Ok(()) }
+    /// 交互模式下是否接管终端（此类任务的输出不再回显）
+    fn uses_tui(&self) -> bool { false }
+    /// 是否将结果写入历史记录（仅在结果包含指标时写入）
+    fn records_history(&self) -> bool { true }
+    /// 是否可在命令行模式下调用；仅模拟执行的任务返回 `false`
+    fn cli_enabled(&self) -> bool { true }
+    /// 执行任务；耗时由 [`execute_task`] 统一填写
+    fn run(&self, config: &TaskConfig) -> TaskResult;
+}
 
-// 恢复 trait 的导入
+/// 所有已注册任务，下标即菜单项 ID
+pub fn registry() -> &'static [Box<dyn Task>] {
+    static REGISTRY: OnceLock<Vec<Box<dyn Task>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        vec![
+            Box::new(SysInfoTask),
+            Box::new(DiskTask),
+            Box::new(CpuTask),
+            Box::new(UnlockTask),
+            Box::new(SpeedtestTask),
+            Box::new(PingTask),
+            Box::new(TracerouteTask),
+            Box::new(SimulatedTask::new("sing-box", "sing-box一键脚本", "部署sing-box代理服务")),
+            Box::new(SimulatedTask::new("xray", "xray一键脚本", "部署xray代理服务")),
+            Box::new(SimulatedTask::new("mem-top", "内存占用排行", "显示内存使用排行")),
+            Box::new(PortTask::Open),
+            Box::new(PortTask::Close),
+            Box::new(SimulatedTask::new("k3s", "k3s", "安装/管理轻量化K8s")),
+            Box::new(SimulatedTask::new("k8s", "k8s", "安装/管理标准K8s")),
+            Box::new(SimulatedTask::new("tcp-tune", "tcp调优", "应用BBR等TCP网络优化")),
+            Box::new(CompareTask),
+            Box::new(DiskMultiTask),
+            Box::new(StealTask),
+            Box::new(MemTask),
+            Box::new(BandwidthTask),
+            Box::new(DnsTask),
+            Box::new(NetDiagTask),
+        ]
+    })
+}
+
+/// 按菜单项 ID 查找任务
+pub fn find_task(id: usize) -> Option<&'static dyn Task> {
+    registry().get(id).map(|task| task.as_ref())
+}
+
+/// 会写入历史记录的任务命令名，供结果对比任务选择
+pub fn history_commands() -> &'static [&'static str] {
+    static COMMANDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    COMMANDS.get_or_init(|| {
+        registry()
+            .iter()
+            .filter(|task| task.records_history() && task.cli_enabled())
+            .map(|task| task.command())
+            .collect()
+    })
+}
 
 pub fn execute_single_task(item: &MenuItem) {
     println!("▶️ 执行单个任务: {}", item.name);
-    
+
     let mut task_config = TaskConfig {
         item: item.clone(),
         params: HashMap::new(),
+        interactive: true,
     };
 
     if item.requires_params {
         collect_parameters(&mut task_config);
     }
-    
+
     // 立即显示任务输出
     println!("\n--- 任务输出 ---");
-    let output = execute_task(&task_config);
+    let result = execute_task(&task_config);
     // 只有在TUI任务之外才打印输出
-    if item.id != 0 {
-        println!("{}", output);
+    if !uses_tui(item) {
+        println!("{}", result.text);
     }
     println!("----------------");
-    
-    save_output(&format!("single_{}", item.id), &output);
-    
-    println!("\n✅ 任务 '{}' 完成。", item.name);
+
+    save_output(&format!("single_{}", item.id), &result.text);
+
+    println!(
+        "\n{} 任务 '{}' {} (耗时 {:.1}s)。",
+        result.status.icon(), item.name, result.status.label(), result.duration.as_secs_f64()
+    );
     wait_for_key();
 }
 
@@ -38,7 +153,7 @@ pub fn execute_multiple_tasks(items: Vec<MenuItem>) {
     let mut task_configs = Vec::new();
 
     for item in items {
-        let mut task_config = TaskConfig { item: item.clone(), params: HashMap::new() };
+        let mut task_config = TaskConfig { item: item.clone(), params: HashMap::new(), interactive: true };
         if item.requires_params {
             println!("\n--- 为任务 '{}' 配置参数 ---", item.name);
             collect_parameters(&mut task_config);
@@ -46,6 +161,9 @@ pub fn execute_multiple_tasks(items: Vec<MenuItem>) {
         task_configs.push(task_config);
     }
 
+    println!("\n--- 采集主机信息 ---");
+    let mut report = RunReport::new(HostFacts::collect());
+
     println!("\n--- 开始批量执行 ---");
     let mut all_output = String::new();
     let header = format!("=== 批量任务执行报告 ===\n执行时间: {}\n共 {} 个任务\n\n", get_current_time(), task_configs.len());
@@ -53,17 +171,23 @@ pub fn execute_multiple_tasks(items: Vec<MenuItem>) {
 
     for (index, config) in task_configs.iter().enumerate() {
         println!("\n[{}/{}] 执行任务: {}", index + 1, task_configs.len(), config.item.name);
-        let task_output = execute_task(config);
+        let started_at = get_current_time();
+        let result = execute_task(config);
+        report.push(config, started_at, &result);
         // TUI 任务不打印返回的字符串
-        if config.item.id != 0 {
-            println!("{}", task_output);
+        if !uses_tui(&config.item) {
+            println!("{}", result.text);
         }
-        all_output.push_str(&format!("--- 任务 {}: {} ---\n", index + 1, config.item.name));
-        all_output.push_str(&task_output);
+        all_output.push_str(&format!(
+            "--- 任务 {}: {} [{} {:.1}s] ---\n",
+            index + 1, config.item.name, result.status.label(), result.duration.as_secs_f64()
+        ));
+        all_output.push_str(&result.text);
         all_output.push_str("\n\n");
     }
 
     save_output("batch_execution", &all_output);
+    report.save("batch_report");
     println!("\n✅ 所有任务完成。");
     wait_for_key();
 }
@@ -71,48 +195,48 @@ pub fn execute_multiple_tasks(items: Vec<MenuItem>) {
 
 /// 收集任务所需参数
 fn collect_parameters(task_config: &mut TaskConfig) {
-    match task_config.item.id {
-        1 => {
-            task_config.params.insert("test_size".to_string(), prompt_input("测试文件大小 (MB)", "1024"));
-            task_config.params.insert("test_path".to_string(), prompt_input("测试路径", "/tmp"));
+    let Some(task) = find_task(task_config.item.id) else { return };
+    for spec in task.params() {
+        if let Some(hint) = task.param_hint(spec.key) {
+            println!("{}", hint.trim_end());
         }
-        2 => {
-             }
-        // ... 其他参数收集 ...
-        _ => {}
+        // 校验失败时提示错误并重新输入
+        let value = loop {
+            let parsed = spec
+                .parse(&prompt_input(spec.prompt, spec.default.unwrap_or("")))
+                .and_then(|value| task.check_param(spec.key, &value).map(|_| value));
+            match parsed {
+                Ok(value) => break value,
+                Err(e) => {
+                    println!("    ❌ {}", e);
+                    if !spec.help.is_empty() {
+                        println!("    💡 {}", spec.help);
+                    }
+                }
+            }
+        };
+        task_config.params.insert(spec.key.to_string(), value);
     }
 }
 
-/// 任务分发器：根据任务ID调用具体实现
-fn execute_task(config: &TaskConfig) -> String {
-    let mut output = String::new();
-    
-    match config.item.id {
-        0 => {
-            println!("正在启动系统信息监控界面...");
-            let result = crate::sysinfo::run_system_monitor();
-                match result {
-                    Ok(_) => output.push_str("已退出系统信息监控。\n"),
-                    Err(e) => output.push_str(&format!("启动系统监控失败: {}\n", e)),
-            }
-        }
-        1 => {
-            output.push_str(&format!("开始硬盘测试 (大小: {} MB)...\n", config.params.get("test_size").unwrap()));
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            output.push_str("测试完成。顺序写入速度: 500 MB/s\n");
-        }
-        _ => {
-            output.push_str(&format!("正在模拟执行任务: {}\n", config.item.name));
-            if !config.params.is_empty() {
-                output.push_str("参数:\n");
-                for (key, val) in &config.params {
-                    output.push_str(&format!("  - {}: {}\n", key, val));
-                }
-            }
-            std::thread::sleep(std::time::Duration::from_secs(1));
-            output.push_str("任务模拟执行完成。\n");
+fn uses_tui(item: &MenuItem) -> bool {
+    find_task(item.id).is_some_and(|task| task.uses_tui())
+}
+
+/// 任务分发器：根据任务ID调用注册表中的实现，记录耗时并写入历史
+pub fn execute_task(config: &TaskConfig) -> TaskResult {
+    let Some(task) = find_task(config.item.id) else {
+        return TaskResult::fail(format!("未知任务: {}\n", config.item.id));
+    };
+    let start = Instant::now();
+    let mut result = task.run(config);
+    result.duration = start.elapsed();
+
+    let has_data = !result.metrics.is_empty() || !result.tables.is_empty();
+    if task.records_history() && has_data && result.status != TaskStatus::Skipped {
+        if let Err(e) = history::append(&HistoryRecord::new(task.command(), config, &result)) {
+            eprintln!("⚠️ 写入历史记录失败: {}", e);
         }
     }
-    
-    output
-}
\ No newline at end of file
+    result
+}
@@ -0,0 +1,119 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_bandwidth::{self, server::Server, Protocol, TestSpec, DEFAULT_PORT};
+use crate::tasks::Task;
+use crate::utils::StopSignal;
+
+/// iperf 风格的 TCP/UDP 带宽测试，可作为客户端或服务端运行
+pub struct BandwidthTask;
+
+impl BandwidthTask {
+    fn spec(config: &TaskConfig) -> TestSpec {
+        TestSpec {
+            protocol: config.text("protocol").and_then(Protocol::from_key).unwrap_or(Protocol::Tcp),
+            streams: config.int("streams").unwrap_or(1) as u32,
+            duration_ms: config.int("duration").unwrap_or(10) as u64 * 1000,
+            reverse: config.flag("reverse").unwrap_or(false),
+            bitrate: config.int("bitrate").unwrap_or(100) as u64 * 1_000_000,
+        }
+    }
+
+    /// 运行服务端直到收到 Ctrl+C；优先监听 IPv6 双栈地址
+    fn run_server(config: &TaskConfig) -> TaskResult {
+        let port = config.int("port").map(|p| p as u16).unwrap_or(DEFAULT_PORT);
+        let server = match Server::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)))
+            .or_else(|_| Server::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))))
+        {
+            Ok(server) => server,
+            Err(e) => return TaskResult::fail(format!("❌ 监听端口 {} 失败: {}\n", port, e)),
+        };
+        let stop = match StopSignal::register() {
+            Ok(stop) => stop,
+            Err(e) => return TaskResult::fail(format!("❌ 注册信号处理失败: {}\n", e)),
+        };
+
+        let addr = match server.local_addr() {
+            Ok(addr) => addr,
+            Err(e) => return TaskResult::fail(format!("❌ 获取监听地址失败: {}\n", e)),
+        };
+        println!("带宽测试服务端已启动: {} (TCP/UDP，按 Ctrl+C 停止)", addr);
+        println!("客户端可使用: onekey bandwidth --mode client --host <本机IP> --port {}", port);
+        let outcome = server.serve(&stop.flag(), &mut |event| println!("  {}", event));
+        match outcome {
+            Ok(()) => TaskResult::ok("带宽测试服务端已停止。\n"),
+            Err(e) => TaskResult::fail(format!("❌ 带宽测试服务端异常退出: {}\n", e)),
+        }
+    }
+}
+
+impl Task for BandwidthTask {
+    fn command(&self) -> &'static str { "bandwidth" }
+    fn aliases(&self) -> &'static [&'static str] { &["iperf"] }
+    fn name(&self) -> &'static str { "带宽测试" }
+    fn description(&self) -> &'static str { "在两台主机间测试TCP/UDP带宽" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::choice("mode", "mode", "运行模式", &["loopback", "client", "server"])
+                .default("loopback")
+                .help("loopback 在本机回环地址上同时运行服务端与客户端"),
+            ParamSpec::text("host", "host", "服务端地址")
+                .default("127.0.0.1")
+                .help("仅 client 模式有效"),
+            ParamSpec::integer("port", "port", "服务端端口", 1, 65535)
+                .default("5201"),
+            ParamSpec::choice("protocol", "protocol", "传输协议", Protocol::KEYS)
+                .default("tcp"),
+            ParamSpec::integer("streams", "streams", "并行流数", 1, 128)
+                .default("1"),
+            ParamSpec::integer("duration", "duration", "测试时长 (秒)", 1, 3600)
+                .default("10"),
+            ParamSpec::boolean("reverse", "reverse", "反向测试")
+                .default("no")
+                .help("由服务端发送、客户端接收，用于测试下行方向"),
+            ParamSpec::integer("bitrate", "bitrate", "UDP 目标码率 (Mbps)", 1, 100_000)
+                .default("100")
+                .help("全部流合计，仅 UDP 有效"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let mode = config.text("mode").unwrap_or("loopback");
+        if mode == "server" {
+            return Self::run_server(config);
+        }
+        let spec = Self::spec(config);
+        let interactive = config.interactive;
+        let mut second = 0;
+        let mut on_sample = |mbps: f64| {
+            second += 1;
+            if interactive {
+                println!("  [{:>3}s] {:>10.1} Mbps", second, mbps);
+            }
+        };
+        let outcome = if mode == "client" {
+            let host = config.text("host").unwrap_or("127.0.0.1");
+            let port = config.int("port").map(|p| p as u16).unwrap_or(DEFAULT_PORT);
+            // IPv6 地址需加方括号
+            let addr = if host.contains(':') && !host.starts_with('[') {
+                format!("[{}]:{}", host, port)
+            } else {
+                format!("{}:{}", host, port)
+            };
+            perf_bandwidth::run_client(&addr, &spec, &mut on_sample)
+        } else {
+            perf_bandwidth::run_loopback(&spec, &mut on_sample)
+        };
+        let report = match outcome {
+            Ok(report) => report,
+            Err(e) => return TaskResult::fail(format!("❌ 带宽测试失败: {}\n", e)),
+        };
+
+        let status = if report.lossy() { TaskStatus::Warn } else { TaskStatus::Ok };
+        let mut result = TaskResult::new(status, report.format());
+        result.metrics = report.metrics();
+        result.tables = report.tables();
+        result
+    }
+}
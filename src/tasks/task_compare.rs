@@ -0,0 +1,90 @@
+use crate::history::{self, current_host, HistoryRecord};
+use crate::models::{Direction, TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::tasks::{history_commands, Task};
+
+/// 与历史结果对比，标出超出阈值的退化
+pub struct CompareTask;
+
+impl Task for CompareTask {
+    fn command(&self) -> &'static str { "compare" }
+    fn name(&self) -> &'static str { "结果对比" }
+    fn description(&self) -> &'static str { "对比本机最近两次测试结果" }
+    fn records_history(&self) -> bool { false }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::choice("task", "task", "要对比的任务命令", history_commands())
+                .default("io"),
+            ParamSpec::integer("threshold", "threshold", "退化阈值 (%)", 1, 1000)
+                .default("10")
+                .help("指标朝不利方向变化超过该百分比时标记为退化"),
+            ParamSpec::integer("back", "back", "与倒数第几次之前的结果对比", 1, 1000)
+                .default("1")
+                .help("1 表示与上一次参数相同的结果对比，参数不同的记录不参与对比"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let task = config.text("task").unwrap_or("io");
+        let threshold = config.int("threshold").unwrap_or(10) as f64;
+        let back = config.int("back").unwrap_or(1) as usize;
+        let host = current_host();
+
+        let records = history::load(&host, task);
+        let Some((latest, earlier)) = records.split_last() else {
+            return TaskResult::skipped(format!(
+                "主机 {} 的任务 `{}` 没有历史记录，无法对比 (历史文件: {})\n",
+                host, task, history::history_path().display()
+            ));
+        };
+        // 只与参数相同的记录对比，避免把不同负载的结果当作退化
+        let comparable: Vec<&HistoryRecord> = earlier.iter().filter(|r| r.param_diff(latest).is_empty()).collect();
+        let Some(previous) = comparable.len().checked_sub(back).map(|i| comparable[i]) else {
+            let mut output = format!(
+                "主机 {} 的任务 `{}` 仅有 {} 条与最近一次参数相同的历史记录，无法对比 (历史文件: {})\n",
+                host, task, comparable.len(), history::history_path().display()
+            );
+            if let Some(last) = earlier.last() {
+                output.push_str(&format!("与上一次 ({}) 的参数差异:\n", last.timestamp));
+                for (key, mine, theirs) in latest.param_diff(last) {
+                    output.push_str(&format!("  {}: {} → {}\n", key, theirs, mine));
+                }
+            }
+            return TaskResult::skipped(output);
+        };
+        let deltas = history::compare(previous, latest, threshold);
+
+        let mut output = format!(
+            "任务 `{}` 对比: {} → {} (阈值 {:.0}%)\n\n",
+            task, previous.timestamp, latest.timestamp, threshold
+        );
+        for delta in &deltas {
+            let marker = if delta.regression {
+                "🔻"
+            } else if delta.improvement {
+                "🔺"
+            } else {
+                "  "
+            };
+            output.push_str(&format!(
+                "{} {}: {:.2} → {:.2} {} ({:+.1}%)\n",
+                marker, delta.key, delta.previous, delta.latest, delta.unit, delta.change_pct
+            ));
+        }
+
+        let regressions = deltas.iter().filter(|d| d.regression).count();
+        if deltas.is_empty() {
+            output.push_str("两次记录没有可对比的指标\n");
+        } else if regressions > 0 {
+            output.push_str(&format!("\n⚠️ {} 项指标退化超过 {:.0}%\n", regressions, threshold));
+        } else {
+            output.push_str("\n✅ 未发现超出阈值的退化\n");
+        }
+
+        let status = if regressions > 0 { TaskStatus::Warn } else { TaskStatus::Ok };
+        TaskResult::new(status, output)
+            .with_metric("compared", deltas.len() as f64, "项", Direction::Neutral)
+            .with_metric("regressions", regressions as f64, "项", Direction::Lower)
+    }
+}
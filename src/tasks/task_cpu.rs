@@ -0,0 +1,102 @@
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_cpu::stress::{self, StressJob};
+use crate::performance::perf_cpu::{self, CpuJob, Workload};
+use crate::tasks::Task;
+
+/// CPU 性能测试
+pub struct CpuTask;
+
+impl CpuTask {
+    fn job(config: &TaskConfig) -> CpuJob {
+        let defaults = CpuJob::default();
+        CpuJob {
+            workloads: config
+                .list("workloads")
+                .map(|keys| keys.iter().filter_map(|k| Workload::from_key(k)).collect())
+                .unwrap_or(defaults.workloads),
+            duration: config.int("duration").map(|s| Duration::from_secs(s as u64)).unwrap_or(defaults.duration),
+            threads: config.int("threads").filter(|&t| t > 0).map(|t| t as usize).unwrap_or(defaults.threads),
+        }
+    }
+
+    /// 满载压力测试，检测降频、steal 尖峰与吞吐量波动
+    fn run_stress(config: &TaskConfig, threads: usize) -> TaskResult {
+        let job = StressJob {
+            threads,
+            duration: Duration::from_secs(config.int("stress_time").unwrap_or(60) as u64),
+        };
+        let mut output = format!("开始CPU压力测试 ({} 线程，持续 {} 秒)...\n", job.threads, job.duration.as_secs());
+        let interactive = config.interactive;
+        let report = stress::run(&job, &mut |second, s| {
+            if interactive {
+                println!(
+                    "  {:>4}s  {:>8.2} GFLOPS  {:>6.0} MHz  占用 {:>5.1}%  steal {:>4.1}%",
+                    second, s.throughput, s.freq_avg(), s.usage_avg(), s.steal_pct
+                );
+            }
+        });
+        output.push_str(&report.format());
+
+        let status = if report.has_issues() { TaskStatus::Warn } else { TaskStatus::Ok };
+        let mut result = TaskResult::new(status, output);
+        result.metrics = report.metrics();
+        result.tables = vec![report.table()];
+        result
+    }
+}
+
+impl Task for CpuTask {
+    fn command(&self) -> &'static str { "cpu" }
+    fn name(&self) -> &'static str { "CPU测试" }
+    fn description(&self) -> &'static str { "测试CPU性能和稳定性" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::choice("mode", "mode", "测试类型", &["bench", "stress"])
+                .default("bench")
+                .help("bench 为性能跑分，stress 为满载稳定性测试"),
+            ParamSpec::multi_choice("workloads", "workloads", "测试负载", Workload::KEYS)
+                .default("all"),
+            ParamSpec::integer("duration", "duration", "每项负载运行时间 (秒)", 1, 60)
+                .default("2"),
+            ParamSpec::integer("threads", "threads", "多核测试线程数", 0, 1024)
+                .default("0")
+                .help("0 表示使用全部逻辑核心"),
+            ParamSpec::integer("stress_time", "stress-time", "压力测试时长 (秒)", 5, 3600)
+                .default("60")
+                .help("仅 stress 模式有效，VPS 的突发额度通常在数十秒到数分钟后耗尽"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let job = Self::job(config);
+        if config.text("mode") == Some("stress") {
+            return Self::run_stress(config, job.threads);
+        }
+        let mut output = format!(
+            "开始CPU测试 (逻辑核心: {}, 多核线程: {}, 每项 {} 秒)...\n",
+            perf_cpu::logical_cores(),
+            job.threads,
+            job.duration.as_secs()
+        );
+        let interactive = config.interactive;
+        let results = perf_cpu::run_job(&job, &mut |r| {
+            if interactive {
+                match (r.single, r.multi) {
+                    (Some(single), Some(multi)) => {
+                        println!("  {}: 单核 {:.1} / 多核 {:.1} {}", r.workload.label(), single, multi, r.workload.unit())
+                    }
+                    _ => println!("  {}: 不支持", r.workload.label()),
+                }
+            }
+        });
+        output.push_str(&perf_cpu::format_results(&job, &results));
+
+        let mut result = TaskResult::ok(output);
+        result.metrics = perf_cpu::metrics(&results);
+        result.tables = perf_cpu::result_tables(&results);
+        result
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::{format_size, ParamSpec};
+use crate::performance::perf_io::{self, verify, CacheMode, IoEngine, IoJob, Mode};
+use crate::tasks::Task;
+
+/// 硬盘读写性能测试
+pub struct DiskTask;
+
+impl DiskTask {
+    /// 由任务参数构造测试配置
+    pub(super) fn job(config: &TaskConfig) -> IoJob {
+        let defaults = IoJob::default();
+        let cache_modes = match config.text("cache") {
+            Some("cached") => vec![CacheMode::Cached],
+            Some("direct") => vec![CacheMode::Direct],
+            _ => defaults.cache_modes.clone(),
+        };
+        IoJob {
+            dir: config.path("test_path").map(PathBuf::from).unwrap_or(defaults.dir),
+            file_size: config.int("test_size").map(|mb| mb as u64 * 1024 * 1024).unwrap_or(defaults.file_size),
+            threads: config.int("threads").map(|t| t as usize).unwrap_or(defaults.threads),
+            block_sizes: config.sizes("block_sizes").map(<[u64]>::to_vec).unwrap_or(defaults.block_sizes),
+            modes: config
+                .list("modes")
+                .map(|keys| keys.iter().filter_map(|k| Mode::from_key(k)).collect())
+                .unwrap_or(defaults.modes),
+            cache_modes,
+            read_mix: config.int("read_mix").map(|p| p as f64 / 100.0).unwrap_or(defaults.read_mix),
+            runtime: config.int("runtime").filter(|&s| s > 0).map(|s| Duration::from_secs(s as u64)),
+            time_based: config.flag("time_based").unwrap_or(defaults.time_based),
+            engine: config.text("engine").and_then(IoEngine::from_key).unwrap_or(defaults.engine),
+            iodepth: config.int("iodepth").map(|d| d as usize).unwrap_or(defaults.iodepth),
+        }
+    }
+}
+
+impl Task for DiskTask {
+    fn command(&self) -> &'static str { "io" }
+    fn aliases(&self) -> &'static [&'static str] { &["disk"] }
+    fn name(&self) -> &'static str { "硬盘测试" }
+    fn description(&self) -> &'static str { "测试硬盘读写性能" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::integer("test_size", "size", "测试文件大小 (MB)", 1, 1024 * 1024)
+                .default("1024")
+                .help("测试文件越大越能避开缓存影响，但耗时更长"),
+            ParamSpec::writable_dir("test_path", "path", "测试路径")
+                .default("/tmp")
+                .help("测试文件将创建在该目录下，目录必须存在且可写"),
+            ParamSpec::integer("threads", "threads", "并发线程数", 1, 256)
+                .default("4"),
+            ParamSpec::size_list("block_sizes", "bs", "块大小列表")
+                .default("4k,64k,512k,1m")
+                .help("O_DIRECT 模式下块大小须为 4k 的整数倍"),
+            ParamSpec::multi_choice("modes", "modes", "测试模式", Mode::KEYS)
+                .default("all"),
+            ParamSpec::choice("cache", "cache", "缓存模式", &["both", "cached", "direct"])
+                .default("both")
+                .help("cached 经过页缓存，direct 使用 O_DIRECT 绕过缓存"),
+            ParamSpec::choice("engine", "engine", "IO引擎", IoEngine::KEYS)
+                .default("sync")
+                .help("uring 使用 io_uring 异步提交，不可用时自动回退为 sync"),
+            ParamSpec::integer("iodepth", "iodepth", "每线程队列深度", 1, 1024)
+                .default("1")
+                .help("仅 uring 引擎有效，如 4k 随机读写常用 32"),
+            ParamSpec::integer("read_mix", "read-mix", "混合读写中读操作占比 (%)", 0, 100)
+                .default("50"),
+            ParamSpec::integer("runtime", "runtime", "单项测试最长运行时间 (秒)", 0, 3600)
+                .default("0")
+                .help("0 表示不限时，每个线程读写完自己负责的全部数据块"),
+            ParamSpec::boolean("time_based", "time-based", "按时间运行")
+                .default("no")
+                .help("开启后每项测试循环读写直到 runtime 结束，并按秒采样检测突发后降速"),
+            ParamSpec::choice("verify", "verify", "数据校验", &["no", "yes", "only"])
+                .default("no")
+                .help("写入带块号与校验和的数据并读回检查；only 表示跳过性能测试只做校验"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let job = Self::job(config);
+        if let Err(e) = job.validate() {
+            return TaskResult::fail(format!("❌ 参数错误: {}\n", e));
+        }
+
+        let mut output = format!(
+            "开始硬盘测试 (路径: {}, 文件: {}, 线程: {}, 引擎: {}, 队列深度: {})...\n",
+            job.dir.display(),
+            format_size(job.file_size),
+            job.threads,
+            job.engine.label(),
+            job.iodepth
+        );
+        let interactive = config.interactive;
+        let verify_mode = config.text("verify").unwrap_or("no");
+        let mut tables = Vec::new();
+        if verify_mode != "only" {
+            let results = match perf_io::run_job(&job, &mut |r| {
+                if interactive {
+                    println!("  [{}] {} {}: {:.2} MB/s, {:.0} IOPS", r.cache.label(), r.mode.label(), format_size(r.block_size), r.mbps(), r.iops());
+                }
+            }) {
+                Ok(results) => results,
+                Err(e) => return TaskResult::fail(format!("{}❌ 硬盘测试失败: {}\n", output, e)),
+            };
+            output.push_str(&perf_io::format_results(&job, &results));
+            tables = perf_io::result_tables(&job, &results);
+        }
+
+        let mut status = TaskStatus::Ok;
+        if verify_mode != "no" {
+            if interactive {
+                println!("  正在写入校验数据并读回检查...");
+            }
+            match verify::run(&job) {
+                Ok(report) => {
+                    output.push_str(&report.format());
+                    tables.push(report.table());
+                    if !report.is_clean() {
+                        status = TaskStatus::Fail;
+                    }
+                }
+                Err(e) => {
+                    output.push_str(&format!("❌ 数据校验失败: {}\n", e));
+                    status = TaskStatus::Fail;
+                }
+            }
+        }
+
+        let mut result = TaskResult::new(status, output);
+        result.tables = tables;
+        result
+    }
+}
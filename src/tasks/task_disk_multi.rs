@@ -0,0 +1,162 @@
+use crate::models::{ResultTable, TaskConfig, TaskResult, TaskStatus};
+use crate::params::{format_size, ParamSpec, ParamValue};
+use crate::performance::perf_io::{self, Target};
+use crate::tasks::task_disk::DiskTask;
+use crate::tasks::Task;
+
+/// 对多个已挂载文件系统依次运行硬盘测试并并排对比
+pub struct DiskMultiTask;
+
+impl DiskMultiTask {
+    /// 解析 `targets` 参数：`all`，或逗号分隔的候选序号（从 1 开始）与挂载点
+    fn select(raw: &str, candidates: &[Target]) -> Result<Vec<Target>, String> {
+        if raw.trim() == "all" {
+            return Ok(candidates.to_vec());
+        }
+        let mut selected: Vec<Target> = Vec::new();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let target = match part.parse::<usize>() {
+                Ok(n) => n
+                    .checked_sub(1)
+                    .and_then(|i| candidates.get(i))
+                    .ok_or_else(|| format!("序号 {} 超出范围 1..={}", n, candidates.len()))?,
+                Err(_) => candidates
+                    .iter()
+                    .find(|t| t.mount_point.as_os_str() == part)
+                    .ok_or_else(|| format!("{} 不是可测试的文件系统", part))?,
+            };
+            if !selected.iter().any(|t| t.mount_point == target.mount_point) {
+                selected.push(target.clone());
+            }
+        }
+        Ok(selected)
+    }
+}
+
+fn format_targets(targets: &[Target]) -> String {
+    let gb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+    let mut out = String::new();
+    for (i, t) in targets.iter().enumerate() {
+        out.push_str(&format!(
+            "  [{}] {:<24} {:<8} {:<20} {:>8.1}/{:.1} GB 可用\n",
+            i + 1,
+            t.mount_point.display(), t.file_system, t.device, gb(t.available), gb(t.total)
+        ));
+    }
+    out
+}
+
+fn format_table(table: &ResultTable) -> String {
+    let mut out = format!("\n{} ({})\n{:<16}", table.title, table.unit, "");
+    for column in &table.columns {
+        out.push_str(&format!(" | {:>14}", column));
+    }
+    out.push('\n');
+    for row in &table.rows {
+        out.push_str(&format!("{:<16}", row.label));
+        for cell in &row.cells {
+            match cell {
+                Some(v) => out.push_str(&format!(" | {:>14.2}", v)),
+                None => out.push_str(&format!(" | {:>14}", "-")),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+impl Task for DiskMultiTask {
+    fn command(&self) -> &'static str { "io-multi" }
+    fn aliases(&self) -> &'static [&'static str] { &["disk-multi"] }
+    fn name(&self) -> &'static str { "多硬盘对比测试" }
+    fn description(&self) -> &'static str { "对多个已挂载文件系统运行硬盘测试并对比" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        let mut params = vec![ParamSpec::text("targets", "targets", "测试目标 (序号或挂载点)")
+            .default("all")
+            .help("all 表示全部可写的非虚拟文件系统，或填写逗号分隔的候选序号或挂载点，如 1,3 或 /,/data；填 list 仅列出候选")];
+        // 测试目录由目标决定，数据校验请对单个目标使用硬盘测试
+        params.extend(DiskTask.params().into_iter().filter(|p| p.key != "test_path" && p.key != "verify"));
+        params
+    }
+
+    fn param_hint(&self, key: &str) -> Option<String> {
+        (key == "targets").then(|| format!("可测试的文件系统:\n{}", format_targets(&perf_io::list_targets())))
+    }
+
+    fn check_param(&self, key: &str, value: &ParamValue) -> Result<(), String> {
+        match value {
+            ParamValue::Text(raw) if key == "targets" && raw.trim() != "list" => {
+                match Self::select(raw, &perf_io::list_targets())? {
+                    targets if targets.is_empty() => Err("没有可测试的文件系统".to_string()),
+                    _ => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let candidates = perf_io::list_targets();
+        let mut output = format!("可测试的文件系统:\n{}", format_targets(&candidates));
+        let raw = config.text("targets").unwrap_or("all");
+        if raw.trim() == "list" {
+            return TaskResult::ok(output);
+        }
+        let targets = match Self::select(raw, &candidates) {
+            Ok(targets) if targets.is_empty() => return TaskResult::fail(format!("{}❌ 没有可测试的文件系统\n", output)),
+            Ok(targets) => targets,
+            Err(e) => return TaskResult::fail(format!("{}❌ 参数错误: {}\n", output, e)),
+        };
+
+        let base = DiskTask::job(config);
+        if let Err(e) = base.validate() {
+            return TaskResult::fail(format!("❌ 参数错误: {}\n", e));
+        }
+
+        let mut runs = Vec::new();
+        let mut failed = 0;
+        for target in &targets {
+            let job = perf_io::IoJob { dir: target.mount_point.clone(), ..base.clone() };
+            let label = target.mount_point.display().to_string();
+            output.push_str(&format!("\n######## {} ({}, 文件: {}) ########\n", label, target.file_system, format_size(job.file_size)));
+            if config.interactive {
+                println!("▶️ 测试 {} ...", label);
+            }
+            match perf_io::run_job(&job, &mut |r| {
+                if config.interactive {
+                    println!("  [{}] {} {}: {:.2} MB/s, {:.0} IOPS", r.cache.label(), r.mode.label(), format_size(r.block_size), r.mbps(), r.iops());
+                }
+            }) {
+                Ok(results) => {
+                    output.push_str(&perf_io::format_results(&job, &results));
+                    runs.push((label, results));
+                }
+                Err(e) => {
+                    output.push_str(&format!("❌ 测试失败: {}\n", e));
+                    failed += 1;
+                    if e.kind() == std::io::ErrorKind::Interrupted {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let tables = perf_io::comparison_tables(&base, &runs);
+        if !runs.is_empty() {
+            output.push_str("\n================ 对比 ================\n");
+            for table in &tables {
+                output.push_str(&format_table(table));
+            }
+        }
+
+        let status = match failed {
+            0 => TaskStatus::Ok,
+            n if n == targets.len() || runs.is_empty() => TaskStatus::Fail,
+            _ => TaskStatus::Warn,
+        };
+        let mut result = TaskResult::new(status, output);
+        result.tables = tables;
+        result
+    }
+}
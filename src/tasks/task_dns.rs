@@ -0,0 +1,99 @@
+use std::time::Duration;
+use crate::dns;
+use crate::models::{Direction, TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_dns::{self, DnsJob, DEFAULT_DOMAINS};
+use crate::tasks::Task;
+
+/// DNS 解析器延迟对比、DNSSEC 验证与解析篡改检查
+pub struct DnsTask;
+
+impl Task for DnsTask {
+    fn command(&self) -> &'static str { "dns" }
+    fn name(&self) -> &'static str { "DNS测试" }
+    fn description(&self) -> &'static str { "测试DNS解析延迟、DNSSEC与解析篡改" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::text("resolvers", "resolvers", "待测解析器")
+                .default("system")
+                .help("system 为系统配置的解析器（含 systemd-resolved 上游），或逗号分隔的 IP 地址"),
+            ParamSpec::boolean("public", "public", "对比公共解析器")
+                .default("yes"),
+            ParamSpec::text("domains", "domains", "测试域名")
+                .default(DEFAULT_DOMAINS)
+                .help("逗号分隔的域名列表"),
+            ParamSpec::integer("rounds", "rounds", "每个域名查询轮数", 1, 20)
+                .default("3"),
+            ParamSpec::integer("timeout", "timeout", "查询超时 (毫秒)", 100, 10000)
+                .default("2000"),
+            ParamSpec::boolean("checks", "checks", "检查 DNSSEC、篡改与出口解析器")
+                .default("yes"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let resolvers = match config.text("resolvers") {
+            Some(raw) if raw != "system" => match perf_dns::parse_resolvers(raw) {
+                Ok(resolvers) => resolvers,
+                Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+            },
+            _ => dns::system_resolvers(),
+        };
+        let include_public = config.flag("public").unwrap_or(true);
+        if resolvers.is_empty() && !include_public {
+            return TaskResult::fail("❌ 未找到系统配置的 DNS 解析器\n");
+        }
+        let domains: Vec<String> = config
+            .text("domains")
+            .unwrap_or(DEFAULT_DOMAINS)
+            .split(',')
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect();
+        if domains.is_empty() {
+            return TaskResult::fail("❌ 没有可测试的域名\n");
+        }
+        let job = DnsJob {
+            resolvers,
+            include_public,
+            domains,
+            rounds: config.int("rounds").unwrap_or(3) as u32,
+            timeout: Duration::from_millis(config.int("timeout").unwrap_or(2000) as u64),
+            checks: config.flag("checks").unwrap_or(true),
+        };
+
+        let mut output = String::new();
+        if job.resolvers.is_empty() {
+            output.push_str("⚠️ 未找到系统配置的 DNS 解析器，仅测试公共解析器\n");
+        }
+        let interactive = config.interactive;
+        let report = perf_dns::run(&job, &mut |message| {
+            if interactive {
+                println!("{}", message);
+            }
+        });
+        output.push_str(&report.format());
+
+        let local: Vec<_> = report.bench.iter().filter(|b| job.resolvers.contains(&b.resolver)).collect();
+        let status = if report.bench.iter().all(|b| b.avg().is_none()) {
+            TaskStatus::Fail
+        } else if report.tampered() || local.iter().any(|b| b.failures > 0) {
+            TaskStatus::Warn
+        } else {
+            TaskStatus::Ok
+        };
+        let mut result = TaskResult::new(status, output);
+        let latencies: Vec<f64> = local.iter().filter_map(|b| b.avg()).collect();
+        if !latencies.is_empty() {
+            let avg = latencies.iter().sum::<f64>() / latencies.len() as f64;
+            result.push_metric("latency_local", avg, "ms", Direction::Lower);
+        }
+        if !local.is_empty() {
+            let fail_rate = local.iter().map(|b| b.fail_rate()).sum::<f64>() / local.len() as f64;
+            result.push_metric("fail_local", fail_rate, "%", Direction::Lower);
+        }
+        result.tables = report.tables();
+        result
+    }
+}
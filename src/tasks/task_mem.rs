@@ -0,0 +1,60 @@
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_cpu;
+use crate::performance::perf_mem::{self, MemJob};
+use crate::tasks::Task;
+
+const MB: usize = 1024 * 1024;
+
+/// 内存带宽与延迟测试
+pub struct MemTask;
+
+impl Task for MemTask {
+    fn command(&self) -> &'static str { "mem" }
+    fn aliases(&self) -> &'static [&'static str] { &["memory"] }
+    fn name(&self) -> &'static str { "内存测试" }
+    fn description(&self) -> &'static str { "测试内存带宽和各级缓存访问延迟" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::integer("size", "size", "带宽测试缓冲区大小 (MB)", 16, 8192)
+                .default("256")
+                .help("应明显大于末级缓存；多线程时各线程平分，过小则放大到末级缓存的 4 倍；超出可用内存的一半时自动缩小"),
+            ParamSpec::integer("threads", "threads", "多线程测试线程数", 0, 1024)
+                .default("0")
+                .help("0 表示使用全部逻辑核心"),
+            ParamSpec::integer("max_ws", "max-ws", "延迟测试最大工作集 (MB)", 1, 4096)
+                .default("256")
+                .help("工作集从 16k 起按 2 倍递增，覆盖 L1 到主存；超出可用内存的一半时自动缩小"),
+            ParamSpec::integer("duration", "duration", "每项带宽测试运行时间 (秒)", 1, 60)
+                .default("1"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let job = MemJob {
+            buffer_size: config.int("size").unwrap_or(256) as usize * MB,
+            threads: config.int("threads").filter(|&t| t > 0).map(|t| t as usize).unwrap_or_else(perf_cpu::logical_cores),
+            max_working_set: config.int("max_ws").unwrap_or(256) as usize * MB,
+            duration: Duration::from_secs(config.int("duration").unwrap_or(1) as u64),
+        };
+        let output = format!(
+            "开始内存测试 (缓冲区 {} MB，{} 线程)...\n",
+            job.buffer_size / MB,
+            job.threads
+        );
+        let interactive = config.interactive;
+        let report = perf_mem::run(&job, &mut |line| {
+            if interactive {
+                println!("  {}", line);
+            }
+        });
+
+        let status = if report.swapping() { TaskStatus::Warn } else { TaskStatus::Ok };
+        let mut result = TaskResult::new(status, output + &report.format());
+        result.metrics = report.metrics();
+        result.tables = report.tables();
+        result
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+use crate::models::{Family, TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_netdiag::{self, DiagJob, DEFAULT_TARGETS};
+use crate::tasks::Task;
+
+/// 路径 MTU、TCP 连通性、ICMP 屏蔽与 IPv4/IPv6 差异诊断
+pub struct NetDiagTask;
+
+impl Task for NetDiagTask {
+    fn command(&self) -> &'static str { "netdiag" }
+    fn aliases(&self) -> &'static [&'static str] { &["mtu"] }
+    fn name(&self) -> &'static str { "网络诊断" }
+    fn description(&self) -> &'static str { "检测路径MTU、TCP连通性与IPv4/IPv6差异" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::text("targets", "targets", "诊断目标")
+                .default(DEFAULT_TARGETS)
+                .help("逗号分隔的 主机:端口，IPv6 地址写作 [2001:db8::1]:443"),
+            ParamSpec::multi_choice("family", "family", "IP 协议", Family::KEYS)
+                .default("all"),
+            ParamSpec::integer("count", "count", "每个目标 TCP 连接次数", 1, 20)
+                .default("3"),
+            ParamSpec::integer("timeout", "timeout", "超时 (毫秒)", 100, 10000)
+                .default("1000"),
+            ParamSpec::boolean("mtu", "mtu", "探测路径 MTU")
+                .default("yes")
+                .help("需要 ICMP 权限，目标须响应 ICMP Echo"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let targets = match perf_netdiag::parse_targets(config.text("targets").unwrap_or(DEFAULT_TARGETS)) {
+            Ok(targets) if !targets.is_empty() => targets,
+            Ok(_) => return TaskResult::fail("❌ 没有可诊断的目标\n"),
+            Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+        };
+        let job = DiagJob {
+            families: config
+                .list("family")
+                .map(|keys| keys.iter().filter_map(|k| Family::from_key(k)).collect())
+                .unwrap_or_else(|| Family::ALL.to_vec()),
+            count: config.int("count").unwrap_or(3) as u32,
+            timeout: Duration::from_millis(config.int("timeout").unwrap_or(1000) as u64),
+            mtu: config.flag("mtu").unwrap_or(true),
+        };
+
+        let interactive = config.interactive;
+        if interactive {
+            println!("正在诊断 {} 个目标...", targets.len());
+        }
+        let report = perf_netdiag::run(&targets, &job, &mut |target, family, _| {
+            if interactive {
+                println!("  {} [{}] 完成", target.label(), family.label());
+            }
+        });
+
+        let status = if !report.any_reachable() {
+            TaskStatus::Fail
+        } else if report.has_problems() {
+            TaskStatus::Warn
+        } else {
+            TaskStatus::Ok
+        };
+        let mut result = TaskResult::new(status, format!("网络诊断:\n{}", report.format()));
+        result.tables = report.tables();
+        result
+    }
+}
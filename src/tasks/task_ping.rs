@@ -0,0 +1,102 @@
+use std::time::Duration;
+use crate::models::{Direction, TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_cnping::{self, grid, Grade, PingJob, PingMethod};
+use crate::performance::perf_cntraceroute::{self, Carrier};
+use crate::tasks::Task;
+use crate::utils::StopSignal;
+
+/// 各省三网并发延迟测试
+pub struct PingTask;
+
+impl Task for PingTask {
+    fn command(&self) -> &'static str { "ping" }
+    fn name(&self) -> &'static str { "三网ping测试" }
+    fn description(&self) -> &'static str { "测试到三大运营商的延迟" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::text("targets", "targets", "测试目标")
+                .default("all")
+                .help("all 为内置各省三网目标，或逗号分隔的 省份/运营商/地址，如 湖北/电信/1.2.3.4"),
+            ParamSpec::multi_choice("carriers", "carriers", "运营商", Carrier::KEYS)
+                .default("all")
+                .help("仅筛选内置目标"),
+            ParamSpec::choice("method", "method", "探测方式", PingMethod::KEYS)
+                .default("auto")
+                .help("auto 优先 ICMP，无权限创建 ICMP 套接字时改用 TCP 连接"),
+            ParamSpec::integer("count", "count", "每个目标探测次数", 1, 1000)
+                .default("10"),
+            ParamSpec::integer("interval", "interval", "探测间隔 (毫秒)", 10, 10000)
+                .default("200"),
+            ParamSpec::integer("timeout", "timeout", "超时 (毫秒)", 100, 10000)
+                .default("1000"),
+            ParamSpec::integer("port", "port", "TCP 探测端口", 1, 65535)
+                .default("80")
+                .help("仅 TCP 方式有效"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let targets = match config.text("targets") {
+            Some(raw) if raw != "all" => match perf_cntraceroute::parse_targets(raw) {
+                Ok(targets) => targets,
+                Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+            },
+            _ => {
+                let carriers: Vec<Carrier> = config
+                    .list("carriers")
+                    .map(|keys| keys.iter().filter_map(|k| Carrier::parse(k)).collect())
+                    .unwrap_or_else(|| Carrier::ALL.to_vec());
+                perf_cnping::default_targets(&carriers)
+            }
+        };
+        if targets.is_empty() {
+            return TaskResult::fail("❌ 没有可测试的目标\n");
+        }
+        let defaults = PingJob::default();
+        let job = PingJob {
+            method: config.text("method").and_then(PingMethod::from_key).unwrap_or(defaults.method),
+            count: config.int("count").map_or(defaults.count, |c| c as u32),
+            interval: config.int("interval").map_or(defaults.interval, |ms| Duration::from_millis(ms as u64)),
+            timeout: config.int("timeout").map_or(defaults.timeout, |ms| Duration::from_millis(ms as u64)),
+            port: config.int("port").map_or(defaults.port, |p| p as u16),
+        };
+
+        let results = if config.interactive {
+            match grid::run(&targets, &job) {
+                Ok(results) => results,
+                Err(e) => return TaskResult::fail(format!("❌ 终端界面出错: {}\n", e)),
+            }
+        } else {
+            let stop = match StopSignal::register() {
+                Ok(stop) => stop,
+                Err(e) => return TaskResult::fail(format!("❌ 注册信号处理失败: {}\n", e)),
+            };
+            perf_cnping::run_pings(&targets, &job, &stop.flag(), &mut |_| {})
+        };
+
+        let mut output = format!("三网延迟测试: {} 个目标，每个探测 {} 次\n", targets.len(), job.count);
+        output.push_str(&perf_cnping::format_results(&results, &job));
+
+        let reachable: Vec<_> = results.iter().filter(|r| r.grade().is_some_and(|g| g != Grade::Down)).collect();
+        let status = if reachable.is_empty() {
+            TaskStatus::Fail
+        } else if reachable.len() < results.len() {
+            TaskStatus::Warn
+        } else {
+            TaskStatus::Ok
+        };
+        let mut result = TaskResult::new(status, output);
+        let avgs: Vec<f64> = reachable.iter().filter_map(|r| r.stats.avg()).collect();
+        if !avgs.is_empty() {
+            result.push_metric("latency_avg", avgs.iter().sum::<f64>() / avgs.len() as f64, "ms", Direction::Lower);
+        }
+        let losses: Vec<f64> = results.iter().filter(|r| r.error.is_none()).filter_map(|r| r.stats.loss()).collect();
+        if !losses.is_empty() {
+            result.push_metric("loss", losses.iter().sum::<f64>() / losses.len() as f64, "%", Direction::Lower);
+        }
+        result.tables = perf_cnping::result_tables(&results);
+        result
+    }
+}
@@ -0,0 +1,65 @@
+use crate::models::{Direction, TaskConfig, TaskResult, TaskStatus};
+use crate::portmgr::PortManager;
+use crate::params::ParamSpec;
+use crate::tasks::Task;
+
+/// 防火墙端口开放/关闭
+pub enum PortTask {
+    Open,
+    Close,
+}
+
+impl Task for PortTask {
+    fn command(&self) -> &'static str {
+        match self {
+            PortTask::Open => "ports open",
+            PortTask::Close => "ports close",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PortTask::Open => "开放端口",
+            PortTask::Close => "关闭端口",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            PortTask::Open => "使用防火墙开放指定端口",
+            PortTask::Close => "使用防火墙关闭指定端口",
+        }
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![ParamSpec::ports("ports", "port", "端口号 (如: 8080 或 8080/tcp,53/udp)")
+            .help("多个端口用逗号分隔，未指定协议时默认为 tcp")]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let Some(ports) = config.ports("ports") else {
+            return TaskResult::fail("错误: 请输入端口号\n");
+        };
+        let manager = PortManager::default();
+        let mut output = String::new();
+        let mut failed = 0;
+        for port in ports {
+            let message = match self {
+                PortTask::Open => manager.open_port(&port.to_string()),
+                PortTask::Close => manager.close_port(&port.to_string()),
+            };
+            if !message.starts_with("成功") {
+                failed += 1;
+            }
+            output.push_str(&format!("{}: {}\n", port, message));
+        }
+        // 部分端口失败视为警告，全部失败视为失败
+        let status = match failed {
+            0 => TaskStatus::Ok,
+            n if n == ports.len() => TaskStatus::Fail,
+            _ => TaskStatus::Warn,
+        };
+        TaskResult::new(status, output)
+            .with_metric("ports_failed", failed as f64, "个", Direction::Lower)
+    }
+}
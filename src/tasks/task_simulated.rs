@@ -0,0 +1,36 @@
+use crate::models::{TaskConfig, TaskResult};
+use crate::tasks::Task;
+
+/// 尚未实现的任务，仅模拟执行
+pub struct SimulatedTask {
+    command: &'static str,
+    name: &'static str,
+    description: &'static str,
+}
+
+impl SimulatedTask {
+    pub const fn new(command: &'static str, name: &'static str, description: &'static str) -> Self {
+        Self { command, name, description }
+    }
+}
+
+impl Task for SimulatedTask {
+    fn command(&self) -> &'static str { self.command }
+    fn name(&self) -> &'static str { self.name }
+    fn description(&self) -> &'static str { self.description }
+    fn cli_enabled(&self) -> bool { false }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let mut output = String::new();
+        output.push_str(&format!("正在模拟执行任务: {}\n", config.item.name));
+        if !config.params.is_empty() {
+            output.push_str("参数:\n");
+            for (key, val) in &config.params {
+                output.push_str(&format!("  - {}: {}\n", key, val));
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        output.push_str("任务模拟执行完成。\n");
+        TaskResult::skipped(output)
+    }
+}
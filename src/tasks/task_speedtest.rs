@@ -0,0 +1,109 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_speedtest::{self, server, Progress, SpeedJob, DEFAULT_SERVERS};
+use crate::tasks::Task;
+use crate::utils::StopSignal;
+
+/// HTTP 多线程网速测试，也可作为测速服务端运行
+pub struct SpeedtestTask;
+
+impl SpeedtestTask {
+    /// 运行内置测速服务端，收到 Ctrl+C 后退出
+    fn run_server(config: &TaskConfig) -> TaskResult {
+        let port = config.int("port").unwrap_or(8080) as u16;
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+        let stop = match StopSignal::register() {
+            Ok(stop) => stop,
+            Err(e) => return TaskResult::fail(format!("❌ 注册信号处理失败: {}\n", e)),
+        };
+
+        println!("测速服务端已启动: http://{} (按 Ctrl+C 停止)", addr);
+        println!("客户端可使用: onekey speedtest --servers http://<本机IP>:{}", port);
+        let mut connections = 0usize;
+        let outcome = server::serve(addr, stop.flag(), &mut |peer| {
+            connections += 1;
+            println!("  连接: {}", peer);
+        });
+        match outcome {
+            Ok(()) => TaskResult::ok(format!("测速服务端已停止，共处理 {} 个连接。\n", connections)),
+            Err(e) => TaskResult::fail(format!("❌ 测速服务端启动失败 ({}): {}\n", addr, e)),
+        }
+    }
+}
+
+impl Task for SpeedtestTask {
+    fn command(&self) -> &'static str { "speedtest" }
+    fn name(&self) -> &'static str { "网速测试" }
+    fn description(&self) -> &'static str { "测试网络上/下行速度" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::choice("mode", "mode", "运行模式", &["client", "server"])
+                .default("client")
+                .help("server 模式启动内置测速服务端，供其他主机测速"),
+            ParamSpec::text("servers", "servers", "测速服务器列表")
+                .default(DEFAULT_SERVERS)
+                .help("逗号分隔，每项为 名称=地址 或仅地址，如 local=http://127.0.0.1:8080"),
+            ParamSpec::integer("streams", "streams", "并行连接数", 1, 64)
+                .default("4"),
+            ParamSpec::integer("duration", "duration", "每个方向测试时长 (秒)", 3, 120)
+                .default("10"),
+            ParamSpec::integer("pings", "pings", "延迟测试次数", 1, 100)
+                .default("10"),
+            ParamSpec::integer("port", "port", "服务端监听端口", 1, 65535)
+                .default("8080")
+                .help("仅 server 模式有效"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        if config.text("mode") == Some("server") {
+            return Self::run_server(config);
+        }
+        let defaults = SpeedJob::default();
+        let servers = match perf_speedtest::parse_servers(config.text("servers").unwrap_or(DEFAULT_SERVERS)) {
+            Ok(servers) => servers,
+            Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+        };
+        let job = SpeedJob {
+            servers,
+            streams: config.int("streams").map(|s| s as usize).unwrap_or(defaults.streams),
+            duration: config.int("duration").map(|s| Duration::from_secs(s as u64)).unwrap_or(defaults.duration),
+            ping_count: config.int("pings").map(|n| n as usize).unwrap_or(defaults.ping_count),
+        };
+
+        let interactive = config.interactive;
+        let results = perf_speedtest::run_job(&job, &mut |progress| {
+            if !interactive {
+                return;
+            }
+            match progress {
+                Progress::Start(server) => println!("  正在测试 {} ({})...", server.name, server.url),
+                Progress::Ping(Ok(p)) => println!("    延迟 {:.1} ms，抖动 {:.1} ms", p.avg, p.jitter),
+                Progress::Ping(Err(e)) => println!("    延迟测试失败: {}", e),
+                Progress::Sample { upload, mbps } => {
+                    println!("    {} {:>9.1} Mbps", if upload { "上传" } else { "下载" }, mbps)
+                }
+            }
+        });
+        let output = format!(
+            "开始网速测试 ({} 个服务器，{} 条并行连接)...\n{}",
+            job.servers.len(),
+            job.streams,
+            perf_speedtest::format_results(&job, &results)
+        );
+
+        let status = if results.iter().all(|r| r.failed()) {
+            TaskStatus::Fail
+        } else if results.iter().any(|r| r.partial()) {
+            TaskStatus::Warn
+        } else {
+            TaskStatus::Ok
+        };
+        let mut result = TaskResult::new(status, output);
+        result.tables = perf_speedtest::result_tables(&results);
+        result
+    }
+}
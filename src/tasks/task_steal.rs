@@ -0,0 +1,47 @@
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_cpu;
+use crate::performance::perf_steal::{self, StealJob};
+use crate::tasks::Task;
+
+/// CPU steal 时间与超售检测
+pub struct StealTask;
+
+impl Task for StealTask {
+    fn command(&self) -> &'static str { "steal" }
+    fn aliases(&self) -> &'static [&'static str] { &["oversell"] }
+    fn name(&self) -> &'static str { "CPU超售检测" }
+    fn description(&self) -> &'static str { "分析steal时间并检测CPU超售" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::integer("window", "window", "空闲采样时间 (秒)", 1, 600)
+                .default("10"),
+            ParamSpec::integer("load_time", "load-time", "满载校验时间 (秒)", 1, 600)
+                .default("5")
+                .help("单线程与全部线程各运行一次"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let job = StealJob {
+            window: Duration::from_secs(config.int("window").unwrap_or(10) as u64),
+            load_time: Duration::from_secs(config.int("load_time").unwrap_or(5) as u64),
+            threads: perf_cpu::logical_cores(),
+        };
+        if config.interactive {
+            println!("  正在采样 (空闲 {} 秒 + 满载 {} 秒)...", job.window.as_secs(), job.load_time.as_secs() * 2);
+        }
+        let report = match perf_steal::run(&job) {
+            Ok(report) => report,
+            Err(e) => return TaskResult::fail(format!("❌ 读取 /proc/stat 失败: {}\n", e)),
+        };
+
+        let status = if report.oversold() { TaskStatus::Warn } else { TaskStatus::Ok };
+        let mut result = TaskResult::new(status, report.format());
+        result.metrics = report.metrics();
+        result.tables = report.tables();
+        result
+    }
+}
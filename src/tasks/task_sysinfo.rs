@@ -0,0 +1,28 @@
+use crate::models::{Direction, TaskConfig, TaskResult};
+use crate::tasks::Task;
+
+/// 系统信息：交互模式进入监控界面，命令行模式输出文本概况
+pub struct SysInfoTask;
+
+impl Task for SysInfoTask {
+    fn command(&self) -> &'static str { "sysinfo" }
+    fn name(&self) -> &'static str { "系统信息" }
+    fn description(&self) -> &'static str { "显示系统详细信息" }
+    fn uses_tui(&self) -> bool { true }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        if !config.interactive {
+            let facts = crate::sysinfo::HostFacts::collect();
+            return TaskResult::ok(facts.to_text())
+                .with_metric("cpu_cores", facts.cpu_cores as f64, "核", Direction::Neutral)
+                .with_metric("cpu_freq", facts.cpu_freq_mhz as f64, "MHz", Direction::Neutral)
+                .with_metric("mem_total", facts.total_mem_gb, "GB", Direction::Neutral)
+                .with_metric("swap_total", facts.total_swap_gb, "GB", Direction::Neutral);
+        }
+        println!("正在启动系统信息监控界面...");
+        match crate::sysinfo::run_system_monitor() {
+            Ok(_) => TaskResult::ok("已退出系统信息监控。\n"),
+            Err(e) => TaskResult::fail(format!("启动系统监控失败: {}\n", e)),
+        }
+    }
+}
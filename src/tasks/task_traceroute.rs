@@ -0,0 +1,87 @@
+use std::time::Duration;
+use crate::models::{TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_cntraceroute::{self, Carrier, ProbeProtocol, TraceJob};
+use crate::tasks::Task;
+
+/// 三网回程路由追踪与线路识别
+pub struct TracerouteTask;
+
+impl Task for TracerouteTask {
+    fn command(&self) -> &'static str { "traceroute" }
+    fn aliases(&self) -> &'static [&'static str] { &["backtrace"] }
+    fn name(&self) -> &'static str { "三网回程测试" }
+    fn description(&self) -> &'static str { "测试到三大运营商的回程路由" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::text("targets", "targets", "测试目标")
+                .default("all")
+                .help("all 为内置北上广三网目标，或逗号分隔的 城市/运营商/地址，如 杭州/电信/1.2.3.4"),
+            ParamSpec::multi_choice("carriers", "carriers", "运营商", Carrier::KEYS)
+                .default("all")
+                .help("仅筛选内置目标"),
+            ParamSpec::choice("protocol", "protocol", "探测协议", ProbeProtocol::KEYS)
+                .default("icmp"),
+            ParamSpec::integer("port", "port", "TCP 探测端口", 1, 65535)
+                .default("80")
+                .help("仅 tcp 协议有效"),
+            ParamSpec::integer("max_hops", "max-hops", "最大跳数", 1, 64)
+                .default("30"),
+            ParamSpec::integer("probes", "probes", "每跳探测次数", 1, 10)
+                .default("3"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        let targets = match config.text("targets") {
+            Some(raw) if raw != "all" => match perf_cntraceroute::parse_targets(raw) {
+                Ok(targets) => targets,
+                Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+            },
+            _ => {
+                let carriers: Vec<Carrier> = config
+                    .list("carriers")
+                    .map(|keys| keys.iter().filter_map(|k| Carrier::parse(k)).collect())
+                    .unwrap_or_else(|| Carrier::ALL.to_vec());
+                perf_cntraceroute::default_targets(&carriers)
+            }
+        };
+        if targets.is_empty() {
+            return TaskResult::fail("❌ 没有可测试的目标\n");
+        }
+        if let Err(e) = perf_cntraceroute::check_privilege() {
+            return TaskResult::fail(format!("❌ {}\n", e));
+        }
+        let defaults = TraceJob::default();
+        let job = TraceJob {
+            protocol: config.text("protocol").and_then(ProbeProtocol::from_key).unwrap_or(defaults.protocol),
+            max_hops: config.int("max_hops").map(|h| h as u8).unwrap_or(defaults.max_hops),
+            probes: config.int("probes").map(|p| p as u8).unwrap_or(defaults.probes),
+            port: config.int("port").map(|p| p as u16).unwrap_or(defaults.port),
+            timeout: Duration::from_secs(2),
+        };
+
+        let mut output = format!("开始{}回程路由追踪 ({} 个目标)...\n", job.protocol.label(), targets.len());
+        let interactive = config.interactive;
+        let traces = perf_cntraceroute::run_traces(&targets, &job, &mut |trace| {
+            if interactive {
+                println!("  {} 完成", trace.target.name());
+            }
+        });
+        for trace in &traces {
+            output.push_str(&perf_cntraceroute::format_trace(trace));
+        }
+        output.push_str(&perf_cntraceroute::format_summary(&traces));
+
+        let failed = traces.iter().filter(|t| t.result.is_err()).count();
+        let status = match failed {
+            0 => TaskStatus::Ok,
+            n if n == traces.len() => TaskStatus::Fail,
+            _ => TaskStatus::Warn,
+        };
+        let mut result = TaskResult::new(status, output);
+        result.tables = vec![perf_cntraceroute::result_table(&traces)];
+        result
+    }
+}
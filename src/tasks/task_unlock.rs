@@ -0,0 +1,91 @@
+use std::path::Path;
+use std::time::Duration;
+use crate::models::{Direction, Family, TaskConfig, TaskResult, TaskStatus};
+use crate::params::ParamSpec;
+use crate::performance::perf_netunlock::{self, RuleSet, Verdict, BUILTIN_RULES};
+use crate::tasks::Task;
+
+/// 基于规则文件的流媒体与服务解锁检测，IPv4 与 IPv6 分别测试
+pub struct UnlockTask;
+
+impl Task for UnlockTask {
+    fn command(&self) -> &'static str { "unlock" }
+    fn name(&self) -> &'static str { "解锁测试" }
+    fn description(&self) -> &'static str { "测试流媒体解锁情况" }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::choice("mode", "mode", "运行模式", &["check", "export"])
+                .default("check")
+                .help("export 输出内置规则，可保存后修改并通过 --rules 加载"),
+            ParamSpec::text("rules", "rules", "规则文件")
+                .default("builtin")
+                .help("builtin 使用内置规则，或 JSON 规则文件路径"),
+            ParamSpec::text("services", "services", "检测的服务")
+                .default("all")
+                .help("all 或逗号分隔的服务名称，如 Netflix,ChatGPT"),
+            ParamSpec::multi_choice("family", "family", "IP 协议", Family::KEYS)
+                .default("all"),
+            ParamSpec::integer("timeout", "timeout", "请求超时 (秒)", 1, 60)
+                .default("10"),
+        ]
+    }
+
+    fn run(&self, config: &TaskConfig) -> TaskResult {
+        if config.text("mode") == Some("export") {
+            return TaskResult::ok(BUILTIN_RULES);
+        }
+        let mut rules = match config.text("rules") {
+            Some(path) if path != "builtin" => match RuleSet::load(Path::new(path)) {
+                Ok(rules) => rules,
+                Err(e) => return TaskResult::fail(format!("❌ {}\n", e)),
+            },
+            _ => RuleSet::builtin(),
+        };
+        if let Some(raw) = config.text("services").filter(|s| *s != "all") {
+            let names: Vec<String> = raw.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+            let missing = rules.retain(&names);
+            if !missing.is_empty() {
+                return TaskResult::fail(format!("❌ 规则中没有服务: {}\n", missing.join(", ")));
+            }
+        }
+        let families: Vec<Family> = config
+            .list("family")
+            .map(|keys| keys.iter().filter_map(|k| Family::from_key(k)).collect())
+            .unwrap_or_else(|| Family::ALL.to_vec());
+        let timeout = Duration::from_secs(config.int("timeout").unwrap_or(10) as u64);
+
+        if config.interactive {
+            println!("正在检测 {} 项服务 ({})...", rules.services.len(), families.iter().map(Family::label).collect::<Vec<_>>().join("/"));
+        }
+        let interactive = config.interactive;
+        let reports = perf_netunlock::run_checks(&rules, &families, timeout, &mut |name, family, result| {
+            if interactive {
+                println!("  {} [{}] {}", name, family.label(), result.describe());
+            }
+        });
+
+        let output = format!("流媒体解锁检测:\n{}", perf_netunlock::format_reports(&reports, &families));
+        let checks: Vec<&Verdict> = reports.iter().flat_map(|r| r.results.iter().map(|(_, c)| &c.verdict)).collect();
+        let failed = checks.iter().filter(|v| matches!(v, Verdict::Failed(_))).count();
+        let status = if failed == checks.len() {
+            TaskStatus::Fail
+        } else if failed > 0 {
+            TaskStatus::Warn
+        } else {
+            TaskStatus::Ok
+        };
+        let mut result = TaskResult::new(status, output);
+        for &family in &families {
+            let unlocked = reports
+                .iter()
+                .flat_map(|r| r.results.iter())
+                .filter(|(f, c)| *f == family && c.verdict == Verdict::Unlocked)
+                .count();
+            let key = format!("unlocked_{}", family.label().to_lowercase());
+            result.push_metric(key, unlocked as f64, "项", Direction::Higher);
+        }
+        result.tables = vec![perf_netunlock::result_table(&reports, &families)];
+        result
+    }
+}
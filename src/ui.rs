@@ -1,5 +1,6 @@
 // src/ui.rs
 use crate::models::{MenuAction, MenuItem};
+use crate::tasks::registry;
 use crossterm::event::{self, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     prelude::*,
@@ -108,10 +109,10 @@ pub fn main_menu() -> MenuAction {
                     }
                     match key.code {
                         KeyCode::Up | KeyCode::Char('k') => {
-                            if selected_index > 0 { selected_index -= 1; }
+                            selected_index = selected_index.saturating_sub(1);
                         }
-                        KeyCode::Down | KeyCode::Char('j') => {
-                            if selected_index < items.len() - 1 { selected_index += 1; }
+                        KeyCode::Down | KeyCode::Char('j') if selected_index < items.len() - 1 => {
+                            selected_index += 1;
                         }
                         KeyCode::Char(' ') => {
                             selected_items[selected_index] = !selected_items[selected_index];
@@ -142,23 +143,11 @@ pub fn main_menu() -> MenuAction {
     action
 }
 
-/// 定义所有菜单项
+/// 由任务注册表生成所有菜单项
 pub fn get_menu_items() -> Vec<MenuItem> {
-    vec![
-        MenuItem::new(0, "系统信息", "显示系统详细信息", false),
-        MenuItem::new(1, "硬盘测试", "测试硬盘读写性能", true),
-        MenuItem::new(2, "CPU测试", "测试CPU性能和稳定性", true),
-        MenuItem::new(3, "解锁测试", "测试流媒体解锁情况", false),
-        MenuItem::new(4, "网速测试", "测试网络上/下行速度", true),
-        MenuItem::new(5, "三网ping测试", "测试到三大运营商的延迟", false),
-        MenuItem::new(6, "三网回程测试", "测试到三大运营商的回程路由", false),
-        MenuItem::new(7, "sing-box一键脚本", "部署sing-box代理服务", true),
-        MenuItem::new(8, "xray一键脚本", "部署xray代理服务", true),
-        MenuItem::new(9, "内存占用排行", "显示内存使用排行", false),
-        MenuItem::new(10, "开放端口", "使用防火墙开放指定端口", true),
-        MenuItem::new(11, "关闭端口", "使用防火墙关闭指定端口", true),
-        MenuItem::new(12, "k3s", "安装/管理轻量化K8s", true),
-        MenuItem::new(13, "k8s", "安装/管理标准K8s", true),
-        MenuItem::new(14, "tcp调优", "应用BBR等TCP网络优化", true),
-    ]
-}
\ No newline at end of file
+    registry()
+        .iter()
+        .enumerate()
+        .map(|(id, task)| MenuItem::new(id, task.name(), task.description(), !task.params().is_empty()))
+        .collect()
+}
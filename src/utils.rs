@@ -1,6 +1,37 @@
 // src/utils.rs
 use std::fs::File;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::{flag, SigId};
+
+static OUTPUT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 设置日志与报告的输出目录（仅首次设置生效）
+pub fn set_output_dir(dir: PathBuf) {
+    let _ = OUTPUT_DIR.set(dir);
+}
+
+/// 日志与报告的输出目录：命令行 `--output-dir` > 环境变量 `ONEKEY_OUTPUT_DIR` > 当前目录
+pub fn output_dir() -> PathBuf {
+    OUTPUT_DIR
+        .get_or_init(|| {
+            std::env::var_os("ONEKEY_OUTPUT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("."))
+        })
+        .clone()
+}
+
+/// 生成输出文件路径 `<输出目录>/<名称>_<时间戳>.<扩展名>`，并确保目录存在
+pub fn output_path(name: &str, extension: &str) -> io::Result<PathBuf> {
+    let dir = output_dir();
+    std::fs::create_dir_all(&dir)?;
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    Ok(dir.join(format!("{}_{}.{}", name, timestamp, extension)))
+}
 
 /// 提示用户输入，并提供默认值
 pub fn prompt_input(prompt: &str, default: &str) -> String {
@@ -25,15 +56,12 @@ pub fn prompt_input(prompt: &str, default: &str) -> String {
 
 /// 将任务输出内容保存到文件
 pub fn save_output(task_name: &str, content: &str) {
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let filename = format!("{}_{}.log", task_name, timestamp);
-    
-    match File::create(&filename) {
-        Ok(mut file) => {
+    match output_path(task_name, "log").and_then(|path| File::create(&path).map(|file| (path, file))) {
+        Ok((path, mut file)) => {
             if let Err(e) = file.write_all(content.as_bytes()) {
                 eprintln!("❌ 写入文件失败: {}", e);
             } else {
-                println!("💾 日志已保存到: {}", filename);
+                println!("💾 日志已保存到: {}", path.display());
             }
         }
         Err(e) => {
@@ -52,4 +80,35 @@ pub fn wait_for_key() {
     println!("\n按 Enter 键返回主菜单...");
     let mut input = String::new();
     let _ = io::stdin().read_line(&mut input);
-}
\ No newline at end of file
+}
+
+/// SIGINT/SIGTERM 停止信号：第一次收到时置位标记以便正常退出并清理，第二次直接结束进程
+pub struct StopSignal {
+    flag: Arc<AtomicBool>,
+    signals: Vec<SigId>,
+}
+
+impl StopSignal {
+    pub fn register() -> io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut signals = Vec::new();
+        for signal in [SIGINT, SIGTERM] {
+            // 条件退出须先于标记注册，否则第一次信号就会触发退出
+            signals.push(flag::register_conditional_shutdown(signal, 128 + signal, stop.clone())?);
+            signals.push(flag::register(signal, stop.clone())?);
+        }
+        Ok(StopSignal { flag: stop, signals })
+    }
+
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.flag.clone()
+    }
+}
+
+impl Drop for StopSignal {
+    fn drop(&mut self) {
+        for id in self.signals.drain(..) {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}